@@ -1,24 +1,489 @@
 /* auto-generated by NAPI-RS */
 /* eslint-disable */
-export declare function doInitialize(reportNetworkStatus: ((err: Error | null, arg: NetworkStatus) => any), reportWlanStatus: ((err: Error | null, arg: WlanStatus) => any), thresholdDrop: number, thresholdRecover: number, reportNetQuality: ((err: Error | null, arg: NetworkQualitySample) => any), log: ((err: Error | null, arg: string) => any)): void
+export interface ApiCheckResult {
+  ok: boolean
+  errorCode: number
+  error?: string
+}
+
+/**
+ * 检测目标主机一组 TCP 端口的可达性（如 53、443、3478），返回 Promise<PortResult[]>，
+ * 顺序与 ports 一致。目标只解析一次，最多 8 个端口并发建连，遵循 setProbeSourceAddress 的源地址；
+ * timeoutMs 为单个端口的握手超时（100-60000），ports 不能包含 0，最多 1024 个。
+ * 不依赖 doInitialize，也不会触发 reportNetQuality 回调；目标解析失败时 Promise 被拒绝。
+ */
+export declare function checkTcpPorts(host: string, ports: Array<number>, timeoutMs: number): Promise<Array<PortResult>>
+
+export interface DiagnosticsReport {
+  allOk: boolean
+  wlanOpenHandle: ApiCheckResult
+  icmpCreateFile: ApiCheckResult
+  networkListManager: ApiCheckResult
+  tcpStatistics: ApiCheckResult
+}
+
+export interface DispatchStats {
+  samplesEmitted: number
+  samplesDropped: number
+  logsDropped: number
+  networkStatusDropped: number
+  wlanStatusDropped: number
+  wlanStatusCoalesced: number
+  wlanEventsDropped: number
+  heartbeatsDropped: number
+  qualityDegradedDropped: number
+}
+
+/**
+ * 停止全部监控：退出后台监控线程并等待其完成清理（含网络质量探测与 WLAN 监听），
+ * 不依赖 Node 进程退出。之后可再次调用 doInitialize，新回调会替换旧回调。
+ */
+export declare function doDeinitialize(): void
+
+/**
+ * Node 侧初始化入口：注册回调并启动监控线程，等待线程完成 COM、网络与 WLAN 初始化后
+ * 返回各子系统的结果（最长等待 5 秒），调用方可据此关闭初始化失败的功能。
+ * 监控线程已在运行时（重复调用）返回上一次的初始化结果。
+ * options 可关闭 WLAN 监控或网络质量探测子系统，仅在监控线程启动时生效。
+ * 失败时抛出的错误带稳定的 code（CALLBACK_REGISTRATION_FAILED / CLEANUP_HOOK_FAILED）。
+ * 重复初始化不视为错误：新回调直接替换旧回调（不存在监听冲突），返回 alreadyInitialized: true，
+ * 因此没有 ALREADY_INITIALIZED / LISTENER_CONFLICT 错误码，页面重载后再次初始化无需捕获异常。
+ */
+export declare function doInitialize(reportNetworkStatus: ((err: Error | null, arg: NetworkStatus) => any), reportWlanStatus: ((err: Error | null, arg: WlanStatus) => any), thresholdDrop: number, thresholdRecover: number, reportNetQuality: ((err: Error | null, arg: NetworkQualitySample) => any), log: ((err: Error | null, arg: LogEvent) => any), netQualityProbEnable: boolean, pingTarget?: string | undefined | null, reportHeartbeat?: (((err: Error | null, arg: Heartbeat) => any)) | undefined | null, reportQualityDegraded?: (((err: Error | null, arg: QualityDegradedEvent) => any)) | undefined | null, reportWlanEvent?: (((err: Error | null, arg: WlanEvent) => any)) | undefined | null, options?: InitOptions | undefined | null): InitResult
+
+export declare function enableNetQualityProb(enable: boolean): void
+
+/**
+ * 将结构化日志事件格式化为单行字符串，格式与旧版字符串日志一致：
+ * `[level]:timestamp - file:line:column module - message`
+ */
+export declare function formatLogEvent(event: LogEvent): string
+
+/**
+ * 同步查询当前网络连通状态，返回值与 reportNetworkStatus 回调一致：
+ * 1 可访问互联网，2 仅本地网络（疑似认证门户），0 未连接。
+ * 可在 doInitialize 之前调用，监控线程尚未产生事件时返回 0。
+ */
+export declare function getCurrentNetworkStatus(): number
+
+/**
+ * 同步读取最近一次 WiFi 信号状态（不受网络连通性过滤），用于界面冷启动时立即展示。
+ * 尚未查询到信号、WiFi 已断开或 WLAN 监控未运行时返回 undefined。
+ */
+export declare function getCurrentWlanStatus(): WlanStatus | null
+
+/**
+ * 读取回调投递统计（进程加载以来累计）：成功/丢弃的质量采样数、丢弃的日志数、各回调丢弃数，
+ * 以及节流合并的 WiFi 信号通知数，用于排查趋势图出现缺口等数据缺失问题。
+ */
+export declare function getDispatchStats(): DispatchStats
+
+/**
+ * 同步读取最近一次网络质量采样（含平滑与评分），用于界面冷启动时立即展示。
+ * 首次探测完成前返回 undefined。
+ */
+export declare function getLastQualitySample(): NetworkQualitySample | null
+
+/**
+ * 读取监控线程运行信息：系统线程 ID（可在调试器中定位）、是否已启动与运行秒数，
+ * 用于确认监控线程是否仍在运行。未运行时 threadId 与 uptimeSecs 为 0。
+ * networkMonitorActive / wlanMonitorActive / qualityProbeActive 为各子系统的实际运行状态
+ * （如服务器系统上 WLAN 初始化失败时为 false），可据此隐藏当前机器不可用的功能。
+ */
+export declare function getMonitorInfo(): MonitorInfo
+
+/**
+ * 读取最近的周期探测采样快照（旧到新），容量由 doInitialize 的 options.recentSampleCapacity
+ * 决定（默认 60）。不包含 probeQualityNow 的按需采样；停止探测时清空。
+ */
+export declare function getRecentQualitySamples(): Array<NetworkQualitySample>
+
+export interface Heartbeat {
+  uptimeSecs: number
+  lastEventAtMs: number
+  timestamp: string
+}
+
+export interface InitOptions {
+  enableWlan?: boolean
+  enableQualityProbe?: boolean
+  recentSampleCapacity?: number
+  networkStatusDebounceMs?: number
+  logTimestampFormat?: string
+  logTimestampUtc?: boolean
+  connectedConnectivityFlags?: number
+}
+
+export interface InitResult {
+  com: SubsystemInitStatus
+  network: SubsystemInitStatus
+  wlan: SubsystemInitStatus
+  alreadyInitialized: boolean
+}
+
+export interface LogEvent {
+  level: string
+  timestamp: string
+  module: string
+  file: string
+  line: number
+  column: number
+  message: string
+}
+
+export interface MonitorInfo {
+  threadId: number
+  started: boolean
+  uptimeSecs: number
+  networkMonitorActive: boolean
+  wlanMonitorActive: boolean
+  qualityProbeActive: boolean
+}
 
 export interface NetworkQualitySample {
+  target: string
+  sampledAtMs: number
+  dnsResolveMs: number
+  dnsCached: boolean
   latencyAvgMs: number
+  latencyAvgMsRaw: number
   latencyMinMs: number
   latencyMaxMs: number
+  latencyP50Ms: number
+  latencyP95Ms: number
+  latencyP99Ms: number
   jitterMs: number
+  jitterMsRaw: number
+  jitterStddevMs: number
+  jitterRfc3550Ms: number
   packetLossPercent: number
+  packetLossPercentRaw: number
+  icmpErrorPercent: number
+  lastPingStatus: string
+  echoCount: number
+  cycleDurationMs: number
+  tcpStatsAvailable: boolean
   tcpRetransmissionPercent: number
   tcpSegmentsSent: number
   tcpSegmentsRetransmitted: number
+  tcpConnectionFailures: number
+  tcpCurrentEstablished: number
+  mtuBlackHole: boolean
+  routeChanged: boolean
+  outOfOrderCount: number
+  icmpBlocked: boolean
+  wifiBound: boolean
+  probeError: string
+  gatewayLatencyMs: number
+  gatewayLossPercent: number
+  rxBytesPerSec: number
+  txBytesPerSec: number
+  rxErrors: number
+  txErrors: number
+  rxDiscards: number
+  txDiscards: number
+  qualityScore: number
+  qualityGrade: string
+  droppedSamples: number
+  secondsSinceLastSuccess: number
 }
 
 export interface NetworkStatus {
   status: number
+  isMetered: boolean
+  ipv4Internet: boolean
+  ipv6Internet: boolean
+  rawConnectivity: number
+  vpnActive: boolean
+  connectionType: string
 }
 
+/**
+ * 暂停网络质量探测（例如应用切到后台或处于按流量计费网络），探测线程保持存活。
+ * 重复调用无副作用。
+ */
+export declare function pauseQualityProbe(): void
+
+export interface PortResult {
+  port: number
+  reachable: boolean
+  connectMs: number
+}
+
+/**
+ * 立即对首个探测目标执行一次网络质量探测，返回 Promise<NetworkQualitySample>。
+ * 探测在线程池中执行（耗时取决于回包与超时，最长约数十秒），不会阻塞主线程，也不会触发
+ * reportNetQuality 回调。与周期探测共享 TCP 重传统计基线，本次取走的增量不会在下个周期重复计算。
+ * 需在 doInitialize 之后调用，否则返回错误。
+ */
+export declare function probeQualityNow(): Promise<NetworkQualitySample>
+
+export interface QualityDegradedEvent {
+  degraded: boolean
+  target: string
+  jitterMs: number
+  packetLossPercent: number
+  reason: string
+}
+
+export interface ReadyReport {
+  initResult: InitResult
+  networkStatus: number
+}
+
+/** 恢复网络质量探测，并重新建立 TCP 重传统计基线。重复调用无副作用。 */
+export declare function resumeQualityProbe(): void
+
+/**
+ * 环境自检：依次调用 WlanOpenHandle、IcmpCreateFile、CoCreateInstance(NetworkListManager)
+ * 与 GetTcpStatisticsEx 各一次并立即释放，逐项返回成功与否及错误码（HRESULT），
+ * 用于在新的 Windows 版本或受限环境中部署前确认依赖可用。不启动监控线程，无需先调用 doInitialize。
+ */
+export declare function runDiagnostics(): DiagnosticsReport
+
+/**
+ * 扫描附近的 WiFi 网络，返回 Promise<WlanNetworkInfo[]>（每个接入点一条，按质量降序），
+ * 包含 SSID、BSSID、信号质量、RSSI、信道、频段与安全配置，用于现场勘测选择更空闲的信道。
+ * 扫描为异步过程，最长等待约 4 秒；超时的接口返回系统缓存的最近扫描结果。仅在 WLAN 监控已初始化时可用。
+ */
+export declare function scanWlanNetworks(): Promise<Array<WlanNetworkInfo>>
+
+/**
+ * 设置域名探测目标解析结果的缓存时长（秒，默认 300，最大 86400），0 表示每个周期都重新解析。
+ * 缓存期间不发起 DNS 查询，采样的 dnsCached 为 true 且 dnsResolveMs 为 0；目标探测全部失败时
+ * 立即丢弃其缓存，下个周期重新解析以适应 CDN 地址变化。修改后清空现有缓存。
+ */
+export declare function setDnsCacheTtlSecs(ttlSecs: number): void
+
+/**
+ * 设置 DNS 耗时测量使用的 DNS 服务器（IPv4 或 IPv6），设置后 dnsResolveMs 为直接向该服务器
+ * 查询 A/AAAA 记录的耗时，不经过系统解析器与缓存，可用于对比企业 DNS 与公共 DNS。
+ * 传入 undefined 或空字符串时恢复使用系统解析器。
+ */
+export declare function setDnsServer(server?: string | undefined | null): void
+
+/**
+ * 设置 IcmpCreateFile 失败后的重试次数（默认 2，最大 10，第 n 次重试前等待 n×50ms），下一次探测生效。
+ * 句柄资源紧张时创建可能短暂失败，重试仍失败时先 ICMP 模式改用 TCP 握手探测，保证本周期仍有采样。
+ */
+export declare function setIcmpCreateRetries(retries: number): void
+
+/**
+ * 设置每个目标每周期同时在途的 ICMP Echo 数（默认 1 即串行发送，最大 8），下一次探测生效。
+ * 并发发送更贴近突发流量的丢包特征并大幅缩短周期耗时，但相邻 Echo 不再等间隔，抖动数值会与串行模式不同。
+ */
+export declare function setIcmpEchoConcurrency(concurrency: number): void
+
+/**
+ * 设置是否开启 ICMP 超时升级，默认关闭，下一次探测生效。开启后每个周期的首个 Echo 超时时以加倍超时
+ * 重发一次再计为丢包，减少突发链路上偶发慢应答造成的误报丢包；只在真正超时时触发，最坏情况下每个目标
+ * 每周期增加 2 倍 setPingTimeoutMs 的耗时（默认 6000ms），且不超过 setPingDeadlineMs 的测量时限。
+ */
+export declare function setIcmpTimeoutEscalation(enable: boolean): void
+
+/**
+ * 设置日志级别：0 关闭，1 仅错误，2 错误与信息（发布版默认），3 包含调试日志（开发版默认）。
+ * 大于 3 的值按 3 处理。低于级别的日志不会进入线程安全函数队列。
+ */
+export declare function setLogLevel(level: number): void
+
+/**
+ * 设置每个目标每周期的 Echo 次数（默认 10，范围 1-100），下一次探测生效。
+ * 次数越多指标越稳定，但单个周期耗时越长，最长受 setPingDeadlineMs 限制。
+ */
+export declare function setPingCount(count: number): void
+
+/**
+ * 设置单次测量（ICMP 或 TCP 回退）的总耗时上限（毫秒，默认 10000），超过后放弃剩余 Echo，
+ * 采样的 echoCount 为实际发出的次数。传入 0 时恢复默认。
+ */
+export declare function setPingDeadlineMs(deadlineMs: number): void
+
+/**
+ * 设置 ICMP 探测是否携带 DF（禁止分片）标志，默认关闭，下一次探测生效。
+ * 配合 setPingPayloadSize 发送大包，收到“需要分片”回复时采样的 mtuBlackHole 为 true。
+ */
+export declare function setPingDontFragment(enable: boolean): void
+
+/**
+ * 设置 ICMP 探测的载荷大小（字节，默认 32），下一次探测生效。
+ * 例如设置为 1472 可构造 1500 字节的 IP 报文以排查路径 MTU 问题；超过 65507 会被拒绝。
+ */
+export declare function setPingPayloadSize(size: number): void
+
+/**
+ * 开启或关闭目标轮询：开启后每个探测周期只采样目标列表中的下一个目标（采样以 target 区分），
+ * 单周期耗时与目标数量无关；关闭时每周期探测全部目标（默认）。下一个探测周期生效。
+ */
+export declare function setPingTargetRoundRobin(enable: boolean): void
+
+/**
+ * 设置网络质量探测目标列表（IPv4 或域名），每个探测周期对所有目标依次采样。
+ * 传入空列表时回退到默认目标，下一个探测周期生效。
+ */
+export declare function setPingTargets(targets: Array<string>): void
+
+/**
+ * 设置单次 Echo 的超时（毫秒，默认 3000，范围 100-60000），ICMP 与 TCP 回退握手共用，下一次探测生效。
+ * 高延迟链路（如卫星）可适当调大以避免误报丢包，局域网可调小以便目标不可达时尽快结束。
+ */
+export declare function setPingTimeoutMs(timeoutMs: number): void
+
+/**
+ * 设置每个周期探测间隔的随机扰动幅度（±百分比，默认 0 关闭，最大 50），下一个周期生效。
+ * 大量终端探测同一内部目标时开启（如 10 表示间隔在 ±10% 内随机），避免探测时刻同步造成服务端负载尖峰。
+ */
+export declare function setProbeIntervalJitterPercent(percent: number): void
+
+/**
+ * 运行时调整网络质量探测间隔（秒），从下一个探测周期开始生效，不会打断当前等待。
+ * 传入 0 会被拒绝，避免探测线程空转占用 CPU。
+ */
+export declare function setProbeIntervalSecs(secs: number): void
+
+/**
+ * 设置探测模式：0 先 ICMP、全部失败再回退 TCP 握手（默认），1 仅 TCP 握手 RTT（适用于完全屏蔽 ICMP
+ * 的企业网络，避免每周期等满全部 Echo 超时），2 仅 ICMP（不回退）。下一次探测生效，非法值返回错误。
+ */
+export declare function setProbeMode(mode: number): void
+
+/**
+ * 设置探测使用的源 IPv4 地址，ICMP 与 TCP 回退探测均从该地址所在网卡发出，用于多网卡环境下
+ * 分别测量各出口质量。传入 undefined 或空字符串时恢复按系统默认路由选择；地址需为本机网卡地址。
+ */
+export declare function setProbeSourceAddress(address?: string | undefined | null): void
+
+/**
+ * 设置是否仅通过 WiFi 网卡探测，默认关闭，下一次探测生效。开启后 ICMP 与 TCP 探测绑定到当前已连接
+ * WLAN 接口所在网卡的 IPv4 地址（优先于 setProbeSourceAddress），延迟/丢包可与同一网卡的信号质量直接关联；
+ * WiFi 未连接或不是可用网卡时按系统默认路由探测并输出提示日志，采样的 wifiBound 为 false。
+ */
+export declare function setProbeWifiOnly(enable: boolean): void
+
+/**
+ * 设置网络质量劣化阈值（滞回）：抖动 ≥ jitterEnterMs 或丢包 ≥ lossEnterPercent 时上报劣化事件，
+ * 抖动 ≤ jitterExitMs 且丢包 ≤ lossExitPercent 时上报恢复事件。
+ * 传 0 表示使用默认值（100ms/50ms、10%/5%）；退出阈值不小于进入阈值时修正为进入阈值的一半。
+ */
+export declare function setQualityDegradationThresholds(jitterEnterMs: number, jitterExitMs: number, lossEnterPercent: number, lossExitPercent: number): void
+
+/**
+ * 设置质量采样的指数移动平均系数 alpha，作用于 latencyAvgMs、jitterMs 与 packetLossPercent：
+ * 0 关闭平滑（默认），(0, 1] 越小曲线越平滑。原始值始终保留在对应的 *Raw 字段中。
+ * 平滑状态在停止探测时清空。
+ */
+export declare function setQualitySmoothing(alpha: number): void
+
+/**
+ * 静默或恢复上报：静默期间 reportNetworkStatus、reportWlanStatus 与 reportNetQuality 不再回调，
+ * 内部状态照常更新（适用于驱动更新等已知会反复断网的操作）；解除静默时按最新状态各补发一次。
+ * 日志、心跳与其余可选回调不受影响。
+ */
+export declare function setReportingMuted(muted: boolean): void
+
+/**
+ * 设置 ICMP 全部失败时 TCP 握手 RTT 回退探测的端口列表（默认 [443]），按顺序尝试，
+ * 首个握手成功的端口作为本周期结果。传入空列表时恢复默认；端口需在 1-65535 之间。
+ * 每个失败端口最多耗时 探测次数 × 超时，列表不宜过长。
+ */
+export declare function setTcpFallbackPorts(ports: Array<number>): void
+
+/**
+ * 设置 TCP RTT 探测是否保持长连接，默认关闭（每次 Echo 重新握手，测得的是握手 RTT）。
+ * 开启后对 ports 中的端口每个目标只建一次连接，每次 Echo 发送 1 字节无协议数据，按墙钟计时到对端确认为止
+ * （含对端的延迟确认），适用于屏蔽 ICMP 的网络上持续采样。探测字节会进入对端的应用协议，ports 只应包含
+ * 可容忍这类数据的端口（如自建回显服务）；TLS（443）等端口会直接断开连接，不在 ports 中的端口仍按握手测量。
+ * 开启时 ports 不能为空且不能包含 0；连接断开时本周期回退为握手测量。
+ */
+export declare function setTcpPersistentRtt(enable: boolean, ports?: Array<number> | undefined | null): void
+
+/**
+ * 设置 WiFi 信号变化上报的节流间隔（毫秒，默认 500）：同一接口在间隔内最多上报一次 reportWlanStatus，
+ * 合并为最新质量；弱/强信号切换始终立即上报。0 表示每次信号变化都上报，最大 60000。
+ */
+export declare function setWlanStatusDebounceMs(ms: number): void
+
+export interface SubsystemInitStatus {
+  enabled: boolean
+  ok: boolean
+  error?: string
+}
+
+export interface TraceHop {
+  ttl: number
+  address: string
+  rttMs: number
+  status: string
+  reached: boolean
+}
+
+/**
+ * 对目标执行一次 traceroute，返回 Promise<TraceHop[]>：TTL 从 1 递增逐跳发送 ICMP Echo，
+ * 记录每跳应答方的地址与往返时间，到达目标或收到目标不可达等差错时提前结束。
+ * maxHops 为 1-64，timeoutMs 为每跳的等待超时（100-60000）；仅支持 IPv4 目标，遵循 setProbeSourceAddress 的源地址。
+ * 独立于周期探测，无需先调用 doInitialize；目标解析失败时 Promise 被拒绝。
+ */
+export declare function traceroute(target: string, maxHops: number, timeoutMs: number): Promise<Array<TraceHop>>
+
+/**
+ * 运行时更新 WiFi 弱信号阈值，无需重新初始化 WLAN 句柄。
+ * 传 0 表示使用默认值；recover 不大于 drop 时按初始化规则修正为 drop + 5。
+ */
+export declare function updateSignalThresholds(drop: number, recover: number): void
+
+/**
+ * 等待监控线程完成初始化（COM、网络与 WLAN 初始化完成并推送首个网络状态），返回 Promise<ReadyReport>，
+ * 含各子系统初始化结果与当时的网络状态，供 Electron 等应用在展示网络界面前等待就绪。
+ * 可在 doInitialize 之前调用；timeoutMs 默认 30000，超时仍未就绪时 Promise 被拒绝。
+ */
+export declare function waitForReady(timeoutMs?: number | undefined | null): Promise<ReadyReport>
+
+export interface WlanEvent {
+  code: number
+  name: string
+  interfaceGuid: string
+  timestamp: string
+}
+
+export interface WlanNetworkInfo {
+  ssid: string
+  bssid: string
+  quality: number
+  rssi: number
+  channel: number
+  band: string
+  securityEnabled: boolean
+  authAlgorithm: number
+  cipherAlgorithm: number
+  phyType: number
+  interfaceGuid: string
+}
+
+/**
+ * 断开并按原配置文件重连当前已连接的 WLAN 接口，用于排查 WiFi 链路卡死，返回重连接口的网卡描述。
+ * 仅在 WLAN 监控已初始化时可用；重连结果通过 reportWlanStatus / reportWlanEvent 回调上报。
+ * 组策略或权限限制导致系统拒绝时返回明确的权限错误。
+ */
+export declare function wlanReconnect(): string
+
 export interface WlanStatus {
   strong: number
+  isWeak: boolean
+  weakStateChanged: boolean
   quality: number
   rssi: number
+  ssid: string
+  bssid: string
+  interfaceGuid: string
+  interfaceName: string
+  signalValid: boolean
+  securityEnabled: boolean
+  authAlgorithm: number
+  cipherAlgorithm: number
+  phyType: number
+  channel: number
+  band: string
+  txRateKbps: number
+  rxRateKbps: number
+  trend: string
 }
@@ -572,4 +572,47 @@ if (!nativeBinding) {
 }
 
 module.exports = nativeBinding
+module.exports.checkTcpPorts = nativeBinding.checkTcpPorts
+module.exports.doDeinitialize = nativeBinding.doDeinitialize
 module.exports.doInitialize = nativeBinding.doInitialize
+module.exports.enableNetQualityProb = nativeBinding.enableNetQualityProb
+module.exports.formatLogEvent = nativeBinding.formatLogEvent
+module.exports.getCurrentNetworkStatus = nativeBinding.getCurrentNetworkStatus
+module.exports.getCurrentWlanStatus = nativeBinding.getCurrentWlanStatus
+module.exports.getDispatchStats = nativeBinding.getDispatchStats
+module.exports.getLastQualitySample = nativeBinding.getLastQualitySample
+module.exports.getMonitorInfo = nativeBinding.getMonitorInfo
+module.exports.getRecentQualitySamples = nativeBinding.getRecentQualitySamples
+module.exports.pauseQualityProbe = nativeBinding.pauseQualityProbe
+module.exports.probeQualityNow = nativeBinding.probeQualityNow
+module.exports.resumeQualityProbe = nativeBinding.resumeQualityProbe
+module.exports.runDiagnostics = nativeBinding.runDiagnostics
+module.exports.scanWlanNetworks = nativeBinding.scanWlanNetworks
+module.exports.setDnsCacheTtlSecs = nativeBinding.setDnsCacheTtlSecs
+module.exports.setDnsServer = nativeBinding.setDnsServer
+module.exports.setIcmpCreateRetries = nativeBinding.setIcmpCreateRetries
+module.exports.setIcmpEchoConcurrency = nativeBinding.setIcmpEchoConcurrency
+module.exports.setIcmpTimeoutEscalation = nativeBinding.setIcmpTimeoutEscalation
+module.exports.setLogLevel = nativeBinding.setLogLevel
+module.exports.setPingCount = nativeBinding.setPingCount
+module.exports.setPingDeadlineMs = nativeBinding.setPingDeadlineMs
+module.exports.setPingDontFragment = nativeBinding.setPingDontFragment
+module.exports.setPingPayloadSize = nativeBinding.setPingPayloadSize
+module.exports.setPingTargetRoundRobin = nativeBinding.setPingTargetRoundRobin
+module.exports.setPingTargets = nativeBinding.setPingTargets
+module.exports.setPingTimeoutMs = nativeBinding.setPingTimeoutMs
+module.exports.setProbeIntervalJitterPercent = nativeBinding.setProbeIntervalJitterPercent
+module.exports.setProbeIntervalSecs = nativeBinding.setProbeIntervalSecs
+module.exports.setProbeMode = nativeBinding.setProbeMode
+module.exports.setProbeSourceAddress = nativeBinding.setProbeSourceAddress
+module.exports.setProbeWifiOnly = nativeBinding.setProbeWifiOnly
+module.exports.setQualityDegradationThresholds = nativeBinding.setQualityDegradationThresholds
+module.exports.setQualitySmoothing = nativeBinding.setQualitySmoothing
+module.exports.setReportingMuted = nativeBinding.setReportingMuted
+module.exports.setTcpFallbackPorts = nativeBinding.setTcpFallbackPorts
+module.exports.setTcpPersistentRtt = nativeBinding.setTcpPersistentRtt
+module.exports.setWlanStatusDebounceMs = nativeBinding.setWlanStatusDebounceMs
+module.exports.traceroute = nativeBinding.traceroute
+module.exports.updateSignalThresholds = nativeBinding.updateSignalThresholds
+module.exports.waitForReady = nativeBinding.waitForReady
+module.exports.wlanReconnect = nativeBinding.wlanReconnect
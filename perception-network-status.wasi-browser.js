@@ -56,4 +56,47 @@ const {
   },
 })
 export default __napiModule.exports
+export const checkTcpPorts = __napiModule.exports.checkTcpPorts
+export const doDeinitialize = __napiModule.exports.doDeinitialize
 export const doInitialize = __napiModule.exports.doInitialize
+export const enableNetQualityProb = __napiModule.exports.enableNetQualityProb
+export const formatLogEvent = __napiModule.exports.formatLogEvent
+export const getCurrentNetworkStatus = __napiModule.exports.getCurrentNetworkStatus
+export const getCurrentWlanStatus = __napiModule.exports.getCurrentWlanStatus
+export const getDispatchStats = __napiModule.exports.getDispatchStats
+export const getLastQualitySample = __napiModule.exports.getLastQualitySample
+export const getMonitorInfo = __napiModule.exports.getMonitorInfo
+export const getRecentQualitySamples = __napiModule.exports.getRecentQualitySamples
+export const pauseQualityProbe = __napiModule.exports.pauseQualityProbe
+export const probeQualityNow = __napiModule.exports.probeQualityNow
+export const resumeQualityProbe = __napiModule.exports.resumeQualityProbe
+export const runDiagnostics = __napiModule.exports.runDiagnostics
+export const scanWlanNetworks = __napiModule.exports.scanWlanNetworks
+export const setDnsCacheTtlSecs = __napiModule.exports.setDnsCacheTtlSecs
+export const setDnsServer = __napiModule.exports.setDnsServer
+export const setIcmpCreateRetries = __napiModule.exports.setIcmpCreateRetries
+export const setIcmpEchoConcurrency = __napiModule.exports.setIcmpEchoConcurrency
+export const setIcmpTimeoutEscalation = __napiModule.exports.setIcmpTimeoutEscalation
+export const setLogLevel = __napiModule.exports.setLogLevel
+export const setPingCount = __napiModule.exports.setPingCount
+export const setPingDeadlineMs = __napiModule.exports.setPingDeadlineMs
+export const setPingDontFragment = __napiModule.exports.setPingDontFragment
+export const setPingPayloadSize = __napiModule.exports.setPingPayloadSize
+export const setPingTargetRoundRobin = __napiModule.exports.setPingTargetRoundRobin
+export const setPingTargets = __napiModule.exports.setPingTargets
+export const setPingTimeoutMs = __napiModule.exports.setPingTimeoutMs
+export const setProbeIntervalJitterPercent = __napiModule.exports.setProbeIntervalJitterPercent
+export const setProbeIntervalSecs = __napiModule.exports.setProbeIntervalSecs
+export const setProbeMode = __napiModule.exports.setProbeMode
+export const setProbeSourceAddress = __napiModule.exports.setProbeSourceAddress
+export const setProbeWifiOnly = __napiModule.exports.setProbeWifiOnly
+export const setQualityDegradationThresholds = __napiModule.exports.setQualityDegradationThresholds
+export const setQualitySmoothing = __napiModule.exports.setQualitySmoothing
+export const setReportingMuted = __napiModule.exports.setReportingMuted
+export const setTcpFallbackPorts = __napiModule.exports.setTcpFallbackPorts
+export const setTcpPersistentRtt = __napiModule.exports.setTcpPersistentRtt
+export const setWlanStatusDebounceMs = __napiModule.exports.setWlanStatusDebounceMs
+export const traceroute = __napiModule.exports.traceroute
+export const updateSignalThresholds = __napiModule.exports.updateSignalThresholds
+export const waitForReady = __napiModule.exports.waitForReady
+export const wlanReconnect = __napiModule.exports.wlanReconnect
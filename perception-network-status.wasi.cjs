@@ -108,4 +108,47 @@ const { instance: __napiInstance, module: __wasiModule, napiModule: __napiModule
   },
 })
 module.exports = __napiModule.exports
+module.exports.checkTcpPorts = __napiModule.exports.checkTcpPorts
+module.exports.doDeinitialize = __napiModule.exports.doDeinitialize
 module.exports.doInitialize = __napiModule.exports.doInitialize
+module.exports.enableNetQualityProb = __napiModule.exports.enableNetQualityProb
+module.exports.formatLogEvent = __napiModule.exports.formatLogEvent
+module.exports.getCurrentNetworkStatus = __napiModule.exports.getCurrentNetworkStatus
+module.exports.getCurrentWlanStatus = __napiModule.exports.getCurrentWlanStatus
+module.exports.getDispatchStats = __napiModule.exports.getDispatchStats
+module.exports.getLastQualitySample = __napiModule.exports.getLastQualitySample
+module.exports.getMonitorInfo = __napiModule.exports.getMonitorInfo
+module.exports.getRecentQualitySamples = __napiModule.exports.getRecentQualitySamples
+module.exports.pauseQualityProbe = __napiModule.exports.pauseQualityProbe
+module.exports.probeQualityNow = __napiModule.exports.probeQualityNow
+module.exports.resumeQualityProbe = __napiModule.exports.resumeQualityProbe
+module.exports.runDiagnostics = __napiModule.exports.runDiagnostics
+module.exports.scanWlanNetworks = __napiModule.exports.scanWlanNetworks
+module.exports.setDnsCacheTtlSecs = __napiModule.exports.setDnsCacheTtlSecs
+module.exports.setDnsServer = __napiModule.exports.setDnsServer
+module.exports.setIcmpCreateRetries = __napiModule.exports.setIcmpCreateRetries
+module.exports.setIcmpEchoConcurrency = __napiModule.exports.setIcmpEchoConcurrency
+module.exports.setIcmpTimeoutEscalation = __napiModule.exports.setIcmpTimeoutEscalation
+module.exports.setLogLevel = __napiModule.exports.setLogLevel
+module.exports.setPingCount = __napiModule.exports.setPingCount
+module.exports.setPingDeadlineMs = __napiModule.exports.setPingDeadlineMs
+module.exports.setPingDontFragment = __napiModule.exports.setPingDontFragment
+module.exports.setPingPayloadSize = __napiModule.exports.setPingPayloadSize
+module.exports.setPingTargetRoundRobin = __napiModule.exports.setPingTargetRoundRobin
+module.exports.setPingTargets = __napiModule.exports.setPingTargets
+module.exports.setPingTimeoutMs = __napiModule.exports.setPingTimeoutMs
+module.exports.setProbeIntervalJitterPercent = __napiModule.exports.setProbeIntervalJitterPercent
+module.exports.setProbeIntervalSecs = __napiModule.exports.setProbeIntervalSecs
+module.exports.setProbeMode = __napiModule.exports.setProbeMode
+module.exports.setProbeSourceAddress = __napiModule.exports.setProbeSourceAddress
+module.exports.setProbeWifiOnly = __napiModule.exports.setProbeWifiOnly
+module.exports.setQualityDegradationThresholds = __napiModule.exports.setQualityDegradationThresholds
+module.exports.setQualitySmoothing = __napiModule.exports.setQualitySmoothing
+module.exports.setReportingMuted = __napiModule.exports.setReportingMuted
+module.exports.setTcpFallbackPorts = __napiModule.exports.setTcpFallbackPorts
+module.exports.setTcpPersistentRtt = __napiModule.exports.setTcpPersistentRtt
+module.exports.setWlanStatusDebounceMs = __napiModule.exports.setWlanStatusDebounceMs
+module.exports.traceroute = __napiModule.exports.traceroute
+module.exports.updateSignalThresholds = __napiModule.exports.updateSignalThresholds
+module.exports.waitForReady = __napiModule.exports.waitForReady
+module.exports.wlanReconnect = __napiModule.exports.wlanReconnect
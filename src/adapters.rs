@@ -0,0 +1,319 @@
+use std::net::Ipv4Addr;
+
+use windows::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, ERROR_SUCCESS};
+use windows::Win32::NetworkManagement::IpHelper::{
+    GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER, GAA_FLAG_SKIP_MULTICAST, GetAdaptersAddresses,
+    GetBestInterface, IF_TYPE_ETHERNET_CSMACD, IF_TYPE_IEEE80211, IF_TYPE_PPP,
+    IF_TYPE_PROP_VIRTUAL, IF_TYPE_TUNNEL, IF_TYPE_WWANPP, IF_TYPE_WWANPP2, IP_ADAPTER_ADDRESSES_LH,
+    IP_ADAPTER_UNICAST_ADDRESS_LH,
+};
+use windows::Win32::NetworkManagement::Ndis::{
+    IfOperStatusUp, TUNNEL_TYPE, TUNNEL_TYPE_6TO4, TUNNEL_TYPE_ISATAP, TUNNEL_TYPE_TEREDO,
+};
+use windows::Win32::Networking::WinSock::{AF_INET, AF_UNSPEC, SOCKADDR_IN};
+
+use crate::{report_debug_log, report_error_log};
+
+// GetAdaptersAddresses 首次调用的缓冲区大小，官方建议 15KB 以减少重试
+const ADAPTERS_BUFFER_SIZE: u32 = 15 * 1024;
+// 缓冲区不足时的最大重试次数：两次调用之间可能新增适配器
+const ADAPTERS_QUERY_ATTEMPTS: usize = 3;
+
+// 查询默认路由出口网卡时使用的公网地址（仅做路由表查找，不发送任何报文）
+const ROUTE_PROBE_ADDRESS: [u8; 4] = [8, 8, 8, 8];
+
+pub const CONNECTION_TYPE_ETHERNET: &str = "ethernet";
+pub const CONNECTION_TYPE_WIFI: &str = "wifi";
+pub const CONNECTION_TYPE_CELLULAR: &str = "cellular";
+pub const CONNECTION_TYPE_OTHER: &str = "other";
+
+// 描述中包含以下关键字的适配器视为 VPN（小写匹配），覆盖 OpenVPN TAP、WireGuard/Wintun 等常见驱动
+const VPN_DESCRIPTION_KEYWORDS: [&str; 6] = [
+    "tap-windows",
+    "wireguard",
+    "wintun",
+    "openvpn",
+    "anyconnect",
+    "vpn",
+];
+
+// 单个网络适配器的关键属性
+#[derive(Debug, Clone, Default)]
+pub struct AdapterInfo {
+    pub interface_index: u32,
+    pub if_type: u32,
+    pub oper_up: bool,
+    pub tunnel_type: TUNNEL_TYPE,
+    pub description: String,
+    pub friendly_name: String,
+    // 适配器 GUID（如 "{XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX}"），与 WLAN 接口 GUID 一致
+    pub adapter_name: String,
+    // 适配器的单播 IPv4 地址
+    pub ipv4_addresses: Vec<Ipv4Addr>,
+}
+
+// 枚举本机网络适配器，查询失败时返回 None
+pub fn list_adapters() -> Option<Vec<AdapterInfo>> {
+    let flags = GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER;
+    let mut size = ADAPTERS_BUFFER_SIZE;
+    for _ in 0..ADAPTERS_QUERY_ATTEMPTS {
+        // 以 u64 分配保证 IP_ADAPTER_ADDRESSES_LH 的对齐要求
+        let mut buffer = vec![0u64; (size as usize).div_ceil(8)];
+        let result = unsafe {
+            GetAdaptersAddresses(
+                AF_UNSPEC.0 as u32,
+                flags,
+                None,
+                Some(buffer.as_mut_ptr().cast()),
+                &mut size,
+            )
+        };
+        if result == ERROR_BUFFER_OVERFLOW.0 {
+            continue;
+        }
+        if result != ERROR_SUCCESS.0 {
+            report_error_log!("GetAdaptersAddresses 失败: {}", result);
+            return None;
+        }
+
+        let mut adapters = Vec::new();
+        let mut current = buffer.as_ptr() as *const IP_ADAPTER_ADDRESSES_LH;
+        while !current.is_null() {
+            let adapter = unsafe { &*current };
+            adapters.push(AdapterInfo {
+                interface_index: unsafe { adapter.Anonymous1.Anonymous.IfIndex },
+                if_type: adapter.IfType,
+                oper_up: adapter.OperStatus == IfOperStatusUp,
+                tunnel_type: adapter.TunnelType,
+                description: unsafe { adapter.Description.to_string() }.unwrap_or_default(),
+                friendly_name: unsafe { adapter.FriendlyName.to_string() }.unwrap_or_default(),
+                adapter_name: unsafe { adapter.AdapterName.to_string() }.unwrap_or_default(),
+                ipv4_addresses: unicast_ipv4_addresses(adapter.FirstUnicastAddress),
+            });
+            current = adapter.Next;
+        }
+        return Some(adapters);
+    }
+
+    report_error_log!("GetAdaptersAddresses 缓冲区多次不足，放弃查询");
+    None
+}
+
+// 遍历适配器的单播地址链表，提取其中的 IPv4 地址
+fn unicast_ipv4_addresses(first: *const IP_ADAPTER_UNICAST_ADDRESS_LH) -> Vec<Ipv4Addr> {
+    let mut addresses = Vec::new();
+    let mut current = first;
+    while !current.is_null() {
+        let unicast = unsafe { &*current };
+        let sockaddr = unicast.Address.lpSockaddr;
+        if !sockaddr.is_null() && unsafe { (*sockaddr).sa_family } == AF_INET {
+            let sockaddr = unsafe { &*(sockaddr as *const SOCKADDR_IN) };
+            addresses.push(Ipv4Addr::from(
+                unsafe { sockaddr.sin_addr.S_un.S_addr }.to_ne_bytes(),
+            ));
+        }
+        current = unicast.Next;
+    }
+    addresses
+}
+
+// 查找 WLAN 接口（按接口 GUID）对应适配器的 IPv4 源地址，供“仅 WiFi”探测绑定；
+// 适配器未启用或只有链路本地地址时返回 None
+pub fn wifi_source_address(interface_guid: &str) -> Option<Ipv4Addr> {
+    select_wifi_source_address(&list_adapters()?, interface_guid)
+}
+
+fn select_wifi_source_address(adapters: &[AdapterInfo], interface_guid: &str) -> Option<Ipv4Addr> {
+    adapters
+        .iter()
+        .find(|adapter| {
+            adapter.oper_up && adapter.adapter_name.eq_ignore_ascii_case(interface_guid)
+        })?
+        .ipv4_addresses
+        .iter()
+        .copied()
+        .find(|address| !address.is_link_local() && !address.is_unspecified())
+}
+
+// 是否存在处于启用状态的 VPN/隧道适配器，查询失败时按未启用处理
+pub fn is_vpn_active() -> bool {
+    let Some(adapters) = list_adapters() else {
+        return false;
+    };
+    match adapters.iter().find(|adapter| is_vpn_adapter(adapter)) {
+        Some(adapter) => {
+            report_debug_log!(
+                "检测到 VPN 适配器：{}（{}），接口索引={}，类型={}",
+                adapter.friendly_name,
+                adapter.description,
+                adapter.interface_index,
+                adapter.if_type
+            );
+            true
+        }
+        None => false,
+    }
+}
+
+// 识别主连接的介质：取默认路由的出口网卡；出口为 VPN 时改用第一个已启用的物理网卡，
+// 以反映 VPN 底层承载的介质。没有可用路由或查询失败时返回空字符串
+pub fn primary_connection_type() -> &'static str {
+    let mut best_index = 0u32;
+    let result =
+        unsafe { GetBestInterface(u32::from_le_bytes(ROUTE_PROBE_ADDRESS), &mut best_index) };
+    if result != ERROR_SUCCESS.0 {
+        report_debug_log!("GetBestInterface 失败: {}", result);
+        return "";
+    }
+    let Some(adapters) = list_adapters() else {
+        return "";
+    };
+    let Some(best) = adapters
+        .iter()
+        .find(|adapter| adapter.interface_index == best_index)
+    else {
+        return "";
+    };
+    if !is_vpn_adapter(best) {
+        return classify_connection_type(best.if_type);
+    }
+    adapters
+        .iter()
+        .filter(|adapter| adapter.oper_up)
+        .map(|adapter| classify_connection_type(adapter.if_type))
+        .find(|connection_type| *connection_type != CONNECTION_TYPE_OTHER)
+        .unwrap_or(CONNECTION_TYPE_OTHER)
+}
+
+// 按 IfType 将网卡归类为有线、WiFi、蜂窝或其他
+fn classify_connection_type(if_type: u32) -> &'static str {
+    match if_type {
+        IF_TYPE_ETHERNET_CSMACD => CONNECTION_TYPE_ETHERNET,
+        IF_TYPE_IEEE80211 => CONNECTION_TYPE_WIFI,
+        IF_TYPE_WWANPP | IF_TYPE_WWANPP2 => CONNECTION_TYPE_CELLULAR,
+        _ => CONNECTION_TYPE_OTHER,
+    }
+}
+
+// 判断适配器是否为已连接的 VPN：PPP（系统内置 IKEv2/L2TP/PPTP）、隧道（排除 Teredo/6to4/ISATAP
+// 等 IPv6 过渡隧道）、私有虚拟接口（Wintun/WireGuard），或描述命中常见 VPN 驱动关键字
+fn is_vpn_adapter(adapter: &AdapterInfo) -> bool {
+    if !adapter.oper_up {
+        return false;
+    }
+    match adapter.if_type {
+        IF_TYPE_PPP | IF_TYPE_PROP_VIRTUAL => return true,
+        IF_TYPE_TUNNEL => {
+            return ![TUNNEL_TYPE_TEREDO, TUNNEL_TYPE_6TO4, TUNNEL_TYPE_ISATAP]
+                .contains(&adapter.tunnel_type);
+        }
+        _ => {}
+    }
+    let description = adapter.description.to_lowercase();
+    VPN_DESCRIPTION_KEYWORDS
+        .iter()
+        .any(|keyword| description.contains(keyword))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AdapterInfo, CONNECTION_TYPE_CELLULAR, CONNECTION_TYPE_ETHERNET, CONNECTION_TYPE_OTHER,
+        CONNECTION_TYPE_WIFI, classify_connection_type, is_vpn_adapter, select_wifi_source_address,
+    };
+    use std::net::Ipv4Addr;
+    use windows::Win32::NetworkManagement::IpHelper::{
+        IF_TYPE_ETHERNET_CSMACD, IF_TYPE_IEEE80211, IF_TYPE_PPP, IF_TYPE_TUNNEL, IF_TYPE_WWANPP,
+    };
+    use windows::Win32::NetworkManagement::Ndis::{TUNNEL_TYPE_IPHTTPS, TUNNEL_TYPE_TEREDO};
+
+    fn adapter(if_type: u32, description: &str) -> AdapterInfo {
+        AdapterInfo {
+            if_type,
+            oper_up: true,
+            description: description.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn common_vpn_adapters_are_detected() {
+        assert!(is_vpn_adapter(&adapter(
+            IF_TYPE_PPP,
+            "WAN Miniport (IKEv2)"
+        )));
+        assert!(is_vpn_adapter(&adapter(
+            IF_TYPE_ETHERNET_CSMACD,
+            "TAP-Windows Adapter V9"
+        )));
+        let mut ip_https = adapter(IF_TYPE_TUNNEL, "Microsoft IP-HTTPS Platform Interface");
+        ip_https.tunnel_type = TUNNEL_TYPE_IPHTTPS;
+        assert!(is_vpn_adapter(&ip_https));
+    }
+
+    #[test]
+    fn physical_and_transition_adapters_are_not_vpn() {
+        assert!(!is_vpn_adapter(&adapter(
+            IF_TYPE_IEEE80211,
+            "Intel(R) Wi-Fi 6 AX201 160MHz"
+        )));
+        let mut teredo = adapter(IF_TYPE_TUNNEL, "Teredo Tunneling Pseudo-Interface");
+        teredo.tunnel_type = TUNNEL_TYPE_TEREDO;
+        assert!(!is_vpn_adapter(&teredo));
+
+        let mut disconnected = adapter(IF_TYPE_PPP, "WAN Miniport (IKEv2)");
+        disconnected.oper_up = false;
+        assert!(!is_vpn_adapter(&disconnected));
+    }
+
+    #[test]
+    fn connection_types_follow_interface_type() {
+        assert_eq!(
+            classify_connection_type(IF_TYPE_ETHERNET_CSMACD),
+            CONNECTION_TYPE_ETHERNET
+        );
+        assert_eq!(
+            classify_connection_type(IF_TYPE_IEEE80211),
+            CONNECTION_TYPE_WIFI
+        );
+        assert_eq!(
+            classify_connection_type(IF_TYPE_WWANPP),
+            CONNECTION_TYPE_CELLULAR
+        );
+        assert_eq!(
+            classify_connection_type(IF_TYPE_TUNNEL),
+            CONNECTION_TYPE_OTHER
+        );
+    }
+
+    #[test]
+    fn wifi_source_address_matches_interface_guid() {
+        let guid = "{6B1D3C4A-0F2E-4B5D-9A8C-1E2F3A4B5C6D}";
+        let wifi = AdapterInfo {
+            if_type: IF_TYPE_IEEE80211,
+            oper_up: true,
+            adapter_name: guid.to_string(),
+            ipv4_addresses: vec![
+                Ipv4Addr::new(169, 254, 3, 4),
+                Ipv4Addr::new(192, 168, 1, 20),
+            ],
+            ..Default::default()
+        };
+        let ethernet = AdapterInfo {
+            if_type: IF_TYPE_ETHERNET_CSMACD,
+            oper_up: true,
+            adapter_name: "{00000000-0000-0000-0000-000000000001}".to_string(),
+            ipv4_addresses: vec![Ipv4Addr::new(10, 0, 0, 5)],
+            ..Default::default()
+        };
+        let adapters = vec![ethernet, wifi];
+        assert_eq!(
+            select_wifi_source_address(&adapters, &guid.to_lowercase()),
+            Some(Ipv4Addr::new(192, 168, 1, 20))
+        );
+        assert_eq!(
+            select_wifi_source_address(&adapters, "{11111111-1111-1111-1111-111111111111}"),
+            None
+        );
+    }
+}
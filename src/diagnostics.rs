@@ -0,0 +1,89 @@
+use std::thread;
+
+use windows::Win32::NetworkManagement::IpHelper::{
+    GetTcpStatisticsEx, IcmpCloseHandle, IcmpCreateFile, MIB_TCPSTATS_LH,
+};
+use windows::Win32::NetworkManagement::WiFi::WlanCloseHandle;
+use windows::Win32::Networking::NetworkListManager::{INetworkListManager, NetworkListManager};
+use windows::Win32::System::Com::{
+    CLSCTX_ALL, COINIT_MULTITHREADED, CoCreateInstance, CoInitializeEx, CoUninitialize,
+};
+
+use crate::global::{ApiCheckResult, DiagnosticsReport, IP_FAMILY_IPV4};
+use crate::wlan::open_wlan_handle;
+use crate::{report_error_log, report_info_log};
+
+// 依次调用监控依赖的系统 API 各一次并立即释放资源，不启动监控线程、不注册任何回调
+// 在临时线程中执行：COM 初始化只作用于该线程，不影响 Node 主线程的套间模型
+pub fn run_diagnostics() -> DiagnosticsReport {
+    let spawned = thread::Builder::new()
+        .name("network-diagnostics".to_string())
+        .spawn(collect_diagnostics);
+    let report = match spawned.map(|handle| handle.join()) {
+        Ok(Ok(report)) => report,
+        Ok(Err(_)) => {
+            report_error_log!("环境自检线程异常退出");
+            DiagnosticsReport::default()
+        }
+        Err(error) => {
+            report_error_log!("创建环境自检线程失败: {}", error);
+            DiagnosticsReport::default()
+        }
+    };
+    report_info_log!("环境自检结果：{:?}", report);
+    report
+}
+
+fn collect_diagnostics() -> DiagnosticsReport {
+    let wlan_open_handle = check_wlan_open_handle();
+    let icmp_create_file = check_icmp_create_file();
+    let network_list_manager = check_network_list_manager();
+    let tcp_statistics = check_tcp_statistics();
+    DiagnosticsReport {
+        all_ok: wlan_open_handle.ok
+            && icmp_create_file.ok
+            && network_list_manager.ok
+            && tcp_statistics.ok,
+        wlan_open_handle,
+        icmp_create_file,
+        network_list_manager,
+        tcp_statistics,
+    }
+}
+
+fn check_wlan_open_handle() -> ApiCheckResult {
+    let result = open_wlan_handle();
+    if let Ok(handle) = result {
+        let _ = unsafe { WlanCloseHandle(handle, None) };
+    }
+    ApiCheckResult::from_win_result(&result)
+}
+
+fn check_icmp_create_file() -> ApiCheckResult {
+    let result = unsafe { IcmpCreateFile() };
+    if let Ok(handle) = result {
+        let _ = unsafe { IcmpCloseHandle(handle) };
+    }
+    ApiCheckResult::from_win_result(&result)
+}
+
+// 与监控线程一致使用 MTA；COM 初始化失败时直接返回该错误
+fn check_network_list_manager() -> ApiCheckResult {
+    let com_result = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) }.ok();
+    if com_result.is_err() {
+        return ApiCheckResult::from_win_result(&com_result);
+    }
+    let result = unsafe {
+        CoCreateInstance::<_, INetworkListManager>(&NetworkListManager, None, CLSCTX_ALL)
+    }
+    .and_then(|manager| unsafe { manager.GetConnectivity() });
+    let check = ApiCheckResult::from_win_result(&result);
+    unsafe { CoUninitialize() };
+    check
+}
+
+fn check_tcp_statistics() -> ApiCheckResult {
+    let mut stats = MIB_TCPSTATS_LH::default();
+    let result = unsafe { GetTcpStatisticsEx(&mut stats, IP_FAMILY_IPV4) };
+    ApiCheckResult::from_win32(result)
+}
@@ -0,0 +1,297 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use rand::RngExt;
+
+use crate::report_error_log;
+
+// DNS 服务端口
+pub const DNS_PORT: u16 = 53;
+// 单次查询（A 或 AAAA）的等待上限
+const DNS_QUERY_TIMEOUT: Duration = Duration::from_secs(3);
+// UDP 响应的最大长度：不使用 EDNS0 时 DNS over UDP 限制为 512 字节
+const DNS_MAX_RESPONSE_SIZE: usize = 512;
+const DNS_HEADER_SIZE: usize = 12;
+// 查询类型与类别
+const DNS_TYPE_A: u16 = 1;
+const DNS_TYPE_AAAA: u16 = 28;
+const DNS_CLASS_IN: u16 = 1;
+// 头部标志：RD（期望递归）
+const DNS_FLAG_RECURSION_DESIRED: u16 = 0x0100;
+// 头部标志：QR（响应）与 RCODE 掩码
+const DNS_FLAG_RESPONSE: u16 = 0x8000;
+const DNS_RCODE_MASK: u16 = 0x000F;
+// 域名单个标签与总长度的上限
+const DNS_MAX_LABEL_LEN: usize = 63;
+const DNS_MAX_NAME_LEN: usize = 253;
+
+// 向指定 DNS 服务器查询域名：先查 A 记录，没有 IPv4 地址时再查 AAAA，
+// 不经过系统解析器与缓存，用于对比不同 DNS 服务器的解析耗时
+pub fn resolve_with_server(host: &str, server: IpAddr) -> Option<IpAddr> {
+    let server = SocketAddr::new(server, DNS_PORT);
+    query_addresses(host, server, DNS_TYPE_A)
+        .or_else(|| query_addresses(host, server, DNS_TYPE_AAAA))
+        .and_then(|addrs| addrs.first().copied())
+}
+
+// 发送一次查询并等待匹配的响应，返回应答中的全部地址；失败或没有对应记录时返回 None
+fn query_addresses(host: &str, server: SocketAddr, qtype: u16) -> Option<Vec<IpAddr>> {
+    let id = rand::rng().random::<u16>();
+    let Some(query) = build_query(id, host, qtype) else {
+        report_error_log!("无效的 DNS 查询域名：{}", host);
+        return None;
+    };
+
+    let bind_addr: SocketAddr = if server.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(bind_addr)
+        .and_then(|socket| socket.connect(server).map(|_| socket))
+        .inspect_err(|error| report_error_log!("创建 DNS 查询套接字失败: {}", error))
+        .ok()?;
+    if let Err(error) = socket.send(&query) {
+        report_error_log!("发送 DNS 查询失败：server={} error={}", server, error);
+        return None;
+    }
+
+    // 忽略 ID 不匹配的迟到响应，直到超时
+    let deadline = Instant::now() + DNS_QUERY_TIMEOUT;
+    let mut buffer = [0u8; DNS_MAX_RESPONSE_SIZE];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() || socket.set_read_timeout(Some(remaining)).is_err() {
+            report_error_log!("DNS 查询超时：server={} host={}", server, host);
+            return None;
+        }
+        let received = match socket.recv(&mut buffer) {
+            Ok(received) => received,
+            Err(error) => {
+                report_error_log!(
+                    "接收 DNS 响应失败：server={} host={} error={}",
+                    server,
+                    host,
+                    error
+                );
+                return None;
+            }
+        };
+        match parse_response(id, &buffer[..received]) {
+            Ok(addrs) if addrs.is_empty() => return None,
+            Ok(addrs) => return Some(addrs),
+            Err(DnsResponseError::IdMismatch) => continue,
+            Err(DnsResponseError::ServerError(rcode)) => {
+                report_error_log!(
+                    "DNS 服务器返回错误：server={} host={} rcode={}",
+                    server,
+                    host,
+                    rcode
+                );
+                return None;
+            }
+            Err(DnsResponseError::Malformed) => {
+                report_error_log!("DNS 响应格式错误：server={} host={}", server, host);
+                return None;
+            }
+        }
+    }
+}
+
+// 响应解析失败的原因
+#[derive(Debug, PartialEq)]
+enum DnsResponseError {
+    // 不是本次查询的响应
+    IdMismatch,
+    // 服务器返回非 0 的 RCODE（如 2=SERVFAIL、3=NXDOMAIN）
+    ServerError(u16),
+    Malformed,
+}
+
+// 构造标准递归查询报文，域名为空或超长时返回 None
+fn build_query(id: u16, host: &str, qtype: u16) -> Option<Vec<u8>> {
+    let host = host.trim_end_matches('.');
+    if host.is_empty() || host.len() > DNS_MAX_NAME_LEN {
+        return None;
+    }
+
+    let mut query = Vec::with_capacity(DNS_HEADER_SIZE + host.len() + 6);
+    query.extend_from_slice(&id.to_be_bytes());
+    query.extend_from_slice(&DNS_FLAG_RECURSION_DESIRED.to_be_bytes());
+    // QDCOUNT=1，其余计数为 0
+    query.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in host.split('.') {
+        if label.is_empty() || label.len() > DNS_MAX_LABEL_LEN {
+            return None;
+        }
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    query.extend_from_slice(&qtype.to_be_bytes());
+    query.extend_from_slice(&DNS_CLASS_IN.to_be_bytes());
+    Some(query)
+}
+
+// 解析响应报文，返回应答区中的 A/AAAA 地址（按出现顺序）
+fn parse_response(id: u16, response: &[u8]) -> Result<Vec<IpAddr>, DnsResponseError> {
+    if response.len() < DNS_HEADER_SIZE {
+        return Err(DnsResponseError::Malformed);
+    }
+    if read_u16(response, 0) != Some(id) {
+        return Err(DnsResponseError::IdMismatch);
+    }
+    let flags = read_u16(response, 2).ok_or(DnsResponseError::Malformed)?;
+    if flags & DNS_FLAG_RESPONSE == 0 {
+        return Err(DnsResponseError::Malformed);
+    }
+    let rcode = flags & DNS_RCODE_MASK;
+    if rcode != 0 {
+        return Err(DnsResponseError::ServerError(rcode));
+    }
+    let question_count = read_u16(response, 4).ok_or(DnsResponseError::Malformed)?;
+    let answer_count = read_u16(response, 6).ok_or(DnsResponseError::Malformed)?;
+
+    let mut offset = DNS_HEADER_SIZE;
+    for _ in 0..question_count {
+        // 问题区：域名 + QTYPE + QCLASS
+        offset = skip_name(response, offset).ok_or(DnsResponseError::Malformed)? + 4;
+    }
+
+    let mut addrs = Vec::new();
+    for _ in 0..answer_count {
+        offset = skip_name(response, offset).ok_or(DnsResponseError::Malformed)?;
+        let rtype = read_u16(response, offset).ok_or(DnsResponseError::Malformed)?;
+        let rdlength = read_u16(response, offset + 8).ok_or(DnsResponseError::Malformed)? as usize;
+        let rdata_start = offset + 10;
+        let rdata = response
+            .get(rdata_start..rdata_start + rdlength)
+            .ok_or(DnsResponseError::Malformed)?;
+        // CNAME 等其他记录直接跳过，递归服务器会在同一响应中附带最终的地址记录
+        match (rtype, rdlength) {
+            (DNS_TYPE_A, 4) => {
+                let octets: [u8; 4] = rdata.try_into().unwrap();
+                addrs.push(IpAddr::from(octets));
+            }
+            (DNS_TYPE_AAAA, 16) => {
+                let octets: [u8; 16] = rdata.try_into().unwrap();
+                addrs.push(IpAddr::from(octets));
+            }
+            _ => {}
+        }
+        offset = rdata_start + rdlength;
+    }
+    Ok(addrs)
+}
+
+// 跳过报文中的域名（支持压缩指针），返回域名之后的偏移
+fn skip_name(message: &[u8], mut offset: usize) -> Option<usize> {
+    for _ in 0..DNS_MAX_NAME_LEN {
+        let len = *message.get(offset)? as usize;
+        match len {
+            0 => return Some(offset + 1),
+            // 压缩指针占 2 字节，指针之后域名即结束
+            len if len & 0xC0 == 0xC0 => {
+                message.get(offset + 1)?;
+                return Some(offset + 2);
+            }
+            len if len > DNS_MAX_LABEL_LEN => return None,
+            len => offset += len + 1,
+        }
+    }
+    None
+}
+
+fn read_u16(message: &[u8], offset: usize) -> Option<u16> {
+    message
+        .get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DNS_TYPE_A, DNS_TYPE_AAAA, DnsResponseError, build_query, parse_response, skip_name,
+    };
+    use std::net::IpAddr;
+
+    // 构造一个包含问题区与给定应答记录的响应报文，应答域名使用指向问题区的压缩指针
+    fn response(id: u16, rcode: u8, answers: &[(u16, &[u8])]) -> Vec<u8> {
+        let mut message = build_query(id, "example.com", DNS_TYPE_A).unwrap();
+        message[2] = 0x81;
+        message[3] = 0x80 | rcode;
+        message[7] = answers.len() as u8;
+        for (rtype, rdata) in answers {
+            message.extend_from_slice(&[0xC0, 0x0C]);
+            message.extend_from_slice(&rtype.to_be_bytes());
+            message.extend_from_slice(&[0, 1, 0, 0, 0, 60]);
+            message.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+            message.extend_from_slice(rdata);
+        }
+        message
+    }
+
+    #[test]
+    fn query_encodes_labels_and_type() {
+        let query = build_query(0x1234, "www.example.com.", DNS_TYPE_AAAA).unwrap();
+        assert_eq!(&query[..4], &[0x12, 0x34, 0x01, 0x00]);
+        assert_eq!(&query[4..6], &[0, 1]);
+        assert_eq!(&query[12..29], b"\x03www\x07example\x03com\x00");
+        assert_eq!(&query[29..], &[0, 28, 0, 1]);
+
+        assert!(build_query(1, "", DNS_TYPE_A).is_none());
+        assert!(build_query(1, "a..b", DNS_TYPE_A).is_none());
+        assert!(build_query(1, &"a".repeat(64), DNS_TYPE_A).is_none());
+    }
+
+    #[test]
+    fn response_addresses_skip_cname_records() {
+        let cname: &[u8] = &[3, b'c', b'd', b'n', 0xC0, 0x0C];
+        let message = response(
+            7,
+            0,
+            &[
+                (5, cname),
+                (DNS_TYPE_A, &[93, 184, 216, 34]),
+                (
+                    DNS_TYPE_AAAA,
+                    &[0x26, 0x06, 0x28, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+                ),
+            ],
+        );
+        let addrs = parse_response(7, &message).unwrap();
+        assert_eq!(
+            addrs,
+            vec![
+                "93.184.216.34".parse::<IpAddr>().unwrap(),
+                "2606:2800::1".parse::<IpAddr>().unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn mismatched_failed_and_truncated_responses_are_rejected() {
+        let message = response(7, 0, &[(DNS_TYPE_A, &[1, 1, 1, 1])]);
+        assert_eq!(
+            parse_response(8, &message),
+            Err(DnsResponseError::IdMismatch)
+        );
+        assert_eq!(
+            parse_response(7, &response(7, 3, &[])),
+            Err(DnsResponseError::ServerError(3))
+        );
+        assert_eq!(
+            parse_response(7, &message[..message.len() - 2]),
+            Err(DnsResponseError::Malformed)
+        );
+        assert_eq!(parse_response(7, &response(7, 0, &[])), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn names_with_pointers_are_skipped() {
+        let message = [3, b'w', b'w', b'w', 0xC0, 0x0C, 0xFF];
+        assert_eq!(skip_name(&message, 0), Some(6));
+        assert_eq!(skip_name(&[0xC0], 0), None);
+        assert_eq!(skip_name(&[5, b'a'], 0), None);
+    }
+}
@@ -1,30 +1,58 @@
 use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
 use std::fmt;
-use std::sync::atomic::{AtomicBool, AtomicU32};
-use std::sync::{Arc, Mutex, OnceLock};
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
 use std::thread::JoinHandle;
 use std::time::Instant;
 
-use chrono::Local;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, Utc};
 
+use napi::Status;
 use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
 use napi_derive::napi;
 
 use windows::Win32::Networking::NetworkListManager::{
-    INetworkListManager, INetworkListManagerEvents,
+    INetworkCostManager, INetworkListManager, INetworkListManagerEvents,
+    NLM_CONNECTIVITY_IPV4_INTERNET, NLM_CONNECTIVITY_IPV4_LOCALNETWORK,
+    NLM_CONNECTIVITY_IPV4_NOTRAFFIC, NLM_CONNECTIVITY_IPV4_SUBNET, NLM_CONNECTIVITY_IPV6_INTERNET,
+    NLM_CONNECTIVITY_IPV6_LOCALNETWORK, NLM_CONNECTIVITY_IPV6_NOTRAFFIC,
+    NLM_CONNECTIVITY_IPV6_SUBNET,
 };
 use windows::Win32::System::Com::{IConnectionPoint, IConnectionPointContainer};
+use windows::core::{GUID, HRESULT};
+
+use crate::{report_error_log, report_info_log};
 
 pub static SOME_EVENT: OnceLock<Mutex<(String, Instant)>> = OnceLock::new();
 
-pub static GLOBAL_REPORT_NET_STATUS: OnceLock<ThreadsafeFunction<NetworkStatus>> = OnceLock::new();
+// 上报回调：使用 Mutex<Option<...>> 而非 OnceLock，反初始化后再次 doInitialize 可直接替换为新的回调
+pub static GLOBAL_REPORT_NET_STATUS: Mutex<Option<ThreadsafeFunction<NetworkStatus>>> =
+    Mutex::new(None);
+
+pub static GLOBAL_REPORT_WLAN_STATUS: Mutex<Option<ThreadsafeFunction<WlanStatus>>> =
+    Mutex::new(None);
+
+pub static GLOBAL_REPORT_NET_QUALITY: Mutex<Option<ThreadsafeFunction<NetworkQualitySample>>> =
+    Mutex::new(None);
 
-pub static GLOBAL_REPORT_WLAN_STATUS: OnceLock<ThreadsafeFunction<WlanStatus>> = OnceLock::new();
+pub static GLOBAL_LOG: Mutex<Option<ThreadsafeFunction<LogEvent>>> = Mutex::new(None);
 
-pub static GLOBAL_REPORT_NET_QUALITY: OnceLock<ThreadsafeFunction<NetworkQualitySample>> =
-    OnceLock::new();
+// 质量劣化/恢复事件回调为可选项，未注册时只记录日志
+pub static GLOBAL_REPORT_QUALITY_DEGRADED: Mutex<Option<ThreadsafeFunction<QualityDegradedEvent>>> =
+    Mutex::new(None);
 
-pub static GLOBAL_LOG: OnceLock<ThreadsafeFunction<String>> = OnceLock::new();
+// WLAN 原始通知回调为可选项：转发全部 MSM 通知（漫游、认证、射频开关等），用于分析间歇性断线
+pub static GLOBAL_REPORT_WLAN_EVENT: Mutex<Option<ThreadsafeFunction<WlanEvent>>> =
+    Mutex::new(None);
+
+// 心跳回调为可选项，未注册时监控线程不发送心跳
+pub static GLOBAL_REPORT_HEARTBEAT: Mutex<Option<ThreadsafeFunction<Heartbeat>>> = Mutex::new(None);
+
+// 进程退出清理钩子是否已注册：重复初始化时不能再次注册同一钩子
+pub static CLEANUP_HOOK_REGISTERED: AtomicBool = AtomicBool::new(false);
 
 // 用于记录后台监控线程的 ID
 pub static MONITOR_THREAD_ID: AtomicU32 = AtomicU32::new(0);
@@ -34,6 +62,19 @@ pub static THRESHOLD_RECOVER: AtomicU32 = AtomicU32::new(0);
 
 // 监控线程是否已经启动，避免重复创建线程
 pub static MONITOR_STARTED: AtomicBool = AtomicBool::new(false);
+// 监控线程开始运行的时间，线程退出后清除，供 getMonitorInfo 计算运行时长
+pub static MONITOR_STARTED_AT: Mutex<Option<Instant>> = Mutex::new(None);
+// 网络连通性事件订阅是否有效：注册成功后为 true，注销或订阅失效被释放后为 false
+pub static NETWORK_MONITOR_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+// 心跳间隔：监控线程消息循环按此间隔上报存活信号
+pub const HEARTBEAT_INTERVAL_MS: u32 = 30_000;
+// 网络事件订阅校验间隔：定期确认 NLM 连接点仍保留订阅，失效时重新注册
+pub const NETWORK_SINK_CHECK_INTERVAL_MS: u32 = 60_000;
+// 最近一次收到系统网络/WLAN 事件的时间（Unix 毫秒），尚未收到事件时为 0
+pub static LAST_EVENT_AT_MS: AtomicI64 = AtomicI64::new(0);
+// 监控线程句柄：显式反初始化时用于等待线程退出
+pub static MONITOR_THREAD: OnceLock<Mutex<Option<JoinHandle<()>>>> = OnceLock::new();
 
 // 当前网络是否具备互联网连通性。
 //
@@ -43,48 +84,356 @@ pub static MONITOR_STARTED: AtomicBool = AtomicBool::new(false);
 // - 因此把“是否联网”提升为跨线程可见的原子状态，避免线程局部存储带来的状态割裂。
 pub static NETWORK_CONNECTED: AtomicBool = AtomicBool::new(false);
 
-// WLAN 信号强度监控上下文：保存阈值与当前状态，供回调使用
+// 最近一次上报的 VPN 状态，用于在连通状态不变时识别 VPN 连接/断开
+pub static VPN_ACTIVE: AtomicBool = AtomicBool::new(false);
+// 最近一次识别到的主连接介质，取值同 NetworkStatus.connection_type，用于识别 WiFi/有线之间的切换
+pub static CONNECTION_TYPE: Mutex<&str> = Mutex::new("");
+// 最近一次上报的网络状态取值（NETWORK_STATUS_*），用于识别 1/2 之间的切换
+pub static NETWORK_STATUS: AtomicU32 = AtomicU32::new(NETWORK_STATUS_DISCONNECTED);
+// 最近一次的 NLM_CONNECTIVITY 原始位掩码，用于在状态取值不变时识别 IPv4/IPv6 等子标志的变化
+pub static RAW_CONNECTIVITY: AtomicU32 = AtomicU32::new(0);
+
+// NetworkStatus.status 取值：未连接
+pub const NETWORK_STATUS_DISCONNECTED: u32 = 0;
+// NetworkStatus.status 取值：可访问互联网
+pub const NETWORK_STATUS_CONNECTED: u32 = 1;
+// NetworkStatus.status 取值：仅连上本地网络但无法访问互联网（常见于酒店、机场的认证页面）
+pub const NETWORK_STATUS_LOCAL_ONLY: u32 = 2;
+
+// 视为“已连接”（status 为 1）的 NLM_CONNECTIVITY 标志，命中任一即可；默认仅 IPv4/IPv6 互联网，
+// 仅访问内网的应用可加入 LOCALNETWORK 标志，使仅本地网络时同样上报已连接
+pub const DEFAULT_CONNECTED_CONNECTIVITY_FLAGS: u32 =
+    (NLM_CONNECTIVITY_IPV4_INTERNET.0 | NLM_CONNECTIVITY_IPV6_INTERNET.0) as u32;
+// 可配置的全部标志位（NOTRAFFIC、SUBNET、LOCALNETWORK、INTERNET 的 IPv4/IPv6 版本）
+pub const VALID_CONNECTED_CONNECTIVITY_FLAGS: u32 = (NLM_CONNECTIVITY_IPV4_NOTRAFFIC.0
+    | NLM_CONNECTIVITY_IPV6_NOTRAFFIC.0
+    | NLM_CONNECTIVITY_IPV4_SUBNET.0
+    | NLM_CONNECTIVITY_IPV4_LOCALNETWORK.0
+    | NLM_CONNECTIVITY_IPV4_INTERNET.0
+    | NLM_CONNECTIVITY_IPV6_SUBNET.0
+    | NLM_CONNECTIVITY_IPV6_LOCALNETWORK.0
+    | NLM_CONNECTIVITY_IPV6_INTERNET.0) as u32;
+pub static CONNECTED_CONNECTIVITY_FLAGS: AtomicU32 =
+    AtomicU32::new(DEFAULT_CONNECTED_CONNECTIVITY_FLAGS);
+
+// WLAN 信号强度监控上下文：保存句柄与阈值，以及每个 WLAN 接口的当前状态，供回调使用
 pub struct SignalMonitorContext {
     pub wlan_handle: isize,
     pub threshold_drop: u32,
     pub threshold_recover: u32,
+    pub interfaces: Vec<InterfaceSignalState>,
+}
+
+// 单个 WLAN 接口的弱信号状态：按接口 GUID 区分，互不影响
+pub struct InterfaceSignalState {
+    pub interface_guid: GUID,
+    // 网卡描述（如 "Intel(R) Wi-Fi 6 AX201 160MHz"），初始化或首次发现接口时读取一次
+    pub interface_name: String,
     pub is_signal_weak: bool,
     pub last_quality: u32,
+    // 最近 SIGNAL_TREND_WINDOW 次上报的信号质量（旧到新），用于计算短期信号趋势，断开时清空
+    pub recent_qualities: VecDeque<u32>,
+    // 最近一次上报 WlanStatus 的时间，用于信号变化通知的节流
+    pub last_status_at: Option<Instant>,
+    // 节流窗口内有未上报的信号变化，窗口结束后补发最新值
+    pub pending_status: bool,
 }
 
+// 信号趋势的采样窗口：取最近 5 次上报的质量，最新与最早之差超过 ±5 视为上升/下降，否则为稳定
+pub const SIGNAL_TREND_WINDOW: usize = 5;
+pub const SIGNAL_TREND_STABLE_BAND: u32 = 5;
+pub const SIGNAL_TREND_RISING: &str = "rising";
+pub const SIGNAL_TREND_FALLING: &str = "falling";
+pub const SIGNAL_TREND_STABLE: &str = "stable";
+
+// 信号变化上报的节流间隔：同一接口在间隔内最多上报一次 WlanStatus（合并为最新质量），
+// 弱/强信号切换不受节流影响立即上报；0 表示每次信号变化都上报
+pub const DEFAULT_WLAN_STATUS_DEBOUNCE_MS: u32 = 500;
+pub const MAX_WLAN_STATUS_DEBOUNCE_MS: u32 = 60_000;
+pub static WLAN_STATUS_DEBOUNCE_MS: AtomicU32 = AtomicU32::new(DEFAULT_WLAN_STATUS_DEBOUNCE_MS);
+// 监控线程检查节流窗口内待补发信号状态的间隔
+pub const WLAN_STATUS_FLUSH_CHECK_MS: u32 = 250;
+// 网络状态防抖：状态变化需稳定保持该时长（毫秒）才上报，0 表示立即上报（默认，保持原有行为）
+pub const MAX_NETWORK_STATUS_DEBOUNCE_MS: u32 = 60_000;
+pub static NETWORK_STATUS_DEBOUNCE_MS: AtomicU32 = AtomicU32::new(0);
+// 防抖窗口内最新的网络状态及其产生时间，稳定后由监控线程上报
+pub static PENDING_NETWORK_STATUS: Mutex<Option<(NetworkStatus, Instant)>> = Mutex::new(None);
+// 监控线程检查待上报网络状态的间隔
+pub const NETWORK_STATUS_FLUSH_CHECK_MS: u32 = 100;
+
 pub const DEFAULT_PING_TARGET: &str = "www.baidu.com";
+// 网络质量探测目标列表：由初始化参数或运行时设置，为空时回退到 DEFAULT_PING_TARGET
+pub static PING_TARGETS: OnceLock<Mutex<Vec<String>>> = OnceLock::new();
+// 轮询模式：每个探测周期只采样列表中的下一个目标，默认关闭（每周期探测全部目标）
+pub static PING_TARGETS_ROUND_ROBIN: AtomicBool = AtomicBool::new(false);
 pub const DEFAULT_PING_COUNT: usize = 10;
 pub const DEFAULT_PING_TIMEOUT_MS: u32 = 3000;
+// 单次 Echo 超时的合法范围：过短会把正常的高延迟链路（如卫星）误判为丢包
+pub const MIN_PING_TIMEOUT_MS: u32 = 100;
+pub const MAX_PING_TIMEOUT_MS: u32 = 60_000;
+// 单次 Echo（ICMP 与 TCP 回退握手）超时：运行时可调整，下一次探测生效
+pub static PING_TIMEOUT_MS: AtomicU32 = AtomicU32::new(DEFAULT_PING_TIMEOUT_MS);
+// TCP 端口可达性检测的并发建连数上限
+pub const TCP_PORT_CHECK_CONCURRENCY: usize = 8;
+// 单次 TCP 端口可达性检测的端口数上限
+pub const MAX_TCP_PORT_CHECK_PORTS: usize = 1024;
+// traceroute 的最大跳数上限（IP TTL 为 8 位，常见路径不超过 30 跳）
+pub const MAX_TRACEROUTE_HOPS: u32 = 64;
+// 每个目标每周期的 Echo 次数上限，避免误配置导致单个周期过长
+pub const MAX_PING_COUNT: u32 = 100;
+// 每个目标每周期的 Echo 次数：运行时可调整，下一次探测生效
+pub static PING_COUNT: AtomicU32 = AtomicU32::new(DEFAULT_PING_COUNT as u32);
+// IcmpCreateFile 失败后的重试次数（资源紧张时可能短暂失败），全部失败时先 ICMP 模式回退为 TCP 握手探测
+pub const DEFAULT_ICMP_CREATE_RETRIES: u32 = 2;
+pub const MAX_ICMP_CREATE_RETRIES: u32 = 10;
+pub static ICMP_CREATE_RETRIES: AtomicU32 = AtomicU32::new(DEFAULT_ICMP_CREATE_RETRIES);
+// 单次测量（ICMP 或 TCP 回退）的总耗时上限，超过后放弃剩余 Echo，避免黑洞目标拖垮整个探测周期
+pub const DEFAULT_PING_DEADLINE_MS: u32 = 10_000;
+pub static PING_DEADLINE_MS: AtomicU32 = AtomicU32::new(DEFAULT_PING_DEADLINE_MS);
 pub const DEFAULT_PROBE_INTERVAL_SECS: u64 = 10;
+// 网络质量探测间隔（秒）：运行时可调整，探测线程每个周期重新读取
+pub static PROBE_INTERVAL_SECS: AtomicU64 = AtomicU64::new(DEFAULT_PROBE_INTERVAL_SECS);
+// 每个周期探测间隔的随机扰动幅度（±百分比），默认 0 关闭；大量终端探测同一目标时用于错开探测时刻
+pub const MAX_PROBE_INTERVAL_JITTER_PERCENT: u32 = 50;
+pub static PROBE_INTERVAL_JITTER_PERCENT: AtomicU32 = AtomicU32::new(0);
+pub const DEFAULT_PING_PAYLOAD_SIZE: u32 = 32;
+// ICMP 载荷上限：IPv4 报文最大 65535 字节，减去 20 字节 IP 头与 8 字节 ICMP 头
+pub const MAX_PING_PAYLOAD_SIZE: u32 = 65507;
+// ICMP Echo 载荷大小（字节）：运行时可调整，用于发送大包排查路径 MTU 问题
+pub static PING_PAYLOAD_SIZE: AtomicU32 = AtomicU32::new(DEFAULT_PING_PAYLOAD_SIZE);
+// ICMP 探测是否设置 DF（禁止分片）标志，默认关闭以保持原有行为
+pub static PING_DONT_FRAGMENT: AtomicBool = AtomicBool::new(false);
+// ICMP 超时升级：周期内首个 Echo 超时时以加倍超时重发一次再计丢包，默认关闭；
+// 每个目标每周期最多增加 2 倍单次超时的耗时（默认 6000ms），且不超过测量时限
+pub static ICMP_TIMEOUT_ESCALATION: AtomicBool = AtomicBool::new(false);
+// 每个目标每周期同时在途的 ICMP Echo 数上限：默认 1（串行发送，抖动测量更稳定），
+// 大于 1 时并发发送，周期耗时约缩短为原来的 1/并发数
+pub const DEFAULT_ICMP_ECHO_CONCURRENCY: u32 = 1;
+pub const MAX_ICMP_ECHO_CONCURRENCY: u32 = 8;
+pub static ICMP_ECHO_CONCURRENCY: AtomicU32 = AtomicU32::new(DEFAULT_ICMP_ECHO_CONCURRENCY);
+// 各探测目标最近一次有成功回包的时间，停止探测时清空
+pub static LAST_PROBE_SUCCESS_AT: Mutex<Option<HashMap<String, Instant>>> = Mutex::new(None);
+// 本次探测启动时间：目标尚无成功采样时作为 seconds_since_last_success 的计时起点
+pub static QUALITY_PROBE_STARTED_AT: Mutex<Option<Instant>> = Mutex::new(None);
+// 探测模式：0 先 ICMP、全部失败再回退 TCP 握手（默认），1 仅 TCP 握手（ICMP 被完全屏蔽的网络），2 仅 ICMP
+pub const PROBE_MODE_ICMP_FIRST: u32 = 0;
+pub const PROBE_MODE_TCP_ONLY: u32 = 1;
+pub const PROBE_MODE_ICMP_ONLY: u32 = 2;
+pub static PROBE_MODE: AtomicU32 = AtomicU32::new(PROBE_MODE_ICMP_FIRST);
+// 探测源 IPv4 地址（u32，按 Ipv4Addr 大端表示存储），0 表示按系统默认路由选择出口网卡
+pub static PROBE_SOURCE_ADDRESS: AtomicU32 = AtomicU32::new(0);
+// 仅 WiFi 探测：探测绑定到当前已连接 WLAN 接口的 IPv4 地址（优先于 PROBE_SOURCE_ADDRESS），
+// 使延迟/丢包与同一网卡的信号质量可直接关联；WiFi 不可用时按系统默认路由探测
+pub static PROBE_WIFI_ONLY: AtomicBool = AtomicBool::new(false);
+// ICMP 全部失败时 TCP 握手 RTT 回退探测的端口列表，按顺序尝试，为空时回退到 DEFAULT_TCP_FALLBACK_PORT
+pub const DEFAULT_TCP_FALLBACK_PORT: u16 = 443;
+pub static TCP_FALLBACK_PORTS: OnceLock<Mutex<Vec<u16>>> = OnceLock::new();
+// 质量指标平滑系数（f64 位模式存储）：0 表示关闭平滑，默认关闭
+pub static QUALITY_SMOOTHING_ALPHA: AtomicU64 = AtomicU64::new(0);
 pub const IP_FAMILY_IPV4: u32 = 2;
+// DNS 解析失败时 dns_resolve_ms 的取值，用于与正常耗时区分
+pub const DNS_RESOLVE_FAILED: u32 = u32::MAX;
+// DNS 耗时测量使用的 DNS 服务器，None 表示使用系统解析器
+pub static DNS_SERVER_OVERRIDE: Mutex<Option<IpAddr>> = Mutex::new(None);
+// 域名探测目标解析结果的缓存时长（秒）：默认 5 分钟，0 表示每个周期都重新解析
+pub const DEFAULT_DNS_CACHE_TTL_SECS: u32 = 300;
+pub const MAX_DNS_CACHE_TTL_SECS: u32 = 86_400;
+pub static DNS_CACHE_TTL_SECS: AtomicU32 = AtomicU32::new(DEFAULT_DNS_CACHE_TTL_SECS);
+// 域名探测目标的解析缓存：目标 -> (地址, 解析时间)，停止探测或修改 DNS 配置时清空
+pub static RESOLVED_TARGETS: Mutex<Option<HashMap<String, (IpAddr, Instant)>>> = Mutex::new(None);
+// TCP RTT 探测保持长连接：每个目标地址只建一次连接，按连接上的往返更新 RTT，默认关闭（每次 Echo 重新握手）
+pub static TCP_PERSISTENT_RTT: AtomicBool = AtomicBool::new(false);
+// 允许长连接测量的端口：长连接会向对端写入无协议的探测字节，只用于调用方明确标记为可容忍的端口
+// （如自建回显服务），其余端口（包括默认的 443，TLS 会因非法记录直接断开）仍按握手测量
+pub static TCP_PERSISTENT_RTT_PORTS: Mutex<Vec<u16>> = Mutex::new(Vec::new());
+// 长连接模式下各目标地址保持的连接，连接断开、停止探测或关闭该模式时移除
+pub static PERSISTENT_TCP_CONNECTIONS: Mutex<Option<HashMap<SocketAddr, TcpStream>>> =
+    Mutex::new(None);
 
 pub static QUALITY_RUNNING: AtomicBool = AtomicBool::new(false);
+// 探测线程的代数：每次启动探测线程加 1，线程只在代数与自身一致时继续探测与上报；
+// 停止时等待超时被放弃的旧线程在重新启动后也不会与新线程同时上报
+pub static QUALITY_GENERATION: AtomicU64 = AtomicU64::new(0);
+// 网络质量探测是否暂停：暂停期间线程保持存活但不发起探测
+pub static QUALITY_PAUSED: AtomicBool = AtomicBool::new(false);
 pub static QUALITY_THREAD: OnceLock<Mutex<Option<JoinHandle<()>>>> = OnceLock::new();
+// 立即采样请求：连通性变化时置位并通知探测线程，探测线程在间隔等待中被唤醒后立即开始下一周期
+pub static QUALITY_WAKE_PENDING: Mutex<bool> = Mutex::new(false);
+pub static QUALITY_WAKE: Condvar = Condvar::new();
+// 质量劣化阈值（进入/退出），0 表示使用默认值；退出阈值不小于进入阈值时按进入阈值的一半修正
+pub static DEGRADED_JITTER_ENTER_MS: AtomicU32 = AtomicU32::new(0);
+pub static DEGRADED_JITTER_EXIT_MS: AtomicU32 = AtomicU32::new(0);
+pub static DEGRADED_LOSS_ENTER_PERCENT: AtomicU32 = AtomicU32::new(0);
+pub static DEGRADED_LOSS_EXIT_PERCENT: AtomicU32 = AtomicU32::new(0);
+
+// 质量劣化/恢复事件：连接仍在但抖动或丢包超过阈值时 degraded 为 true，回落到退出阈值以下时为 false
+#[napi(object)]
+#[derive(Clone)]
+pub struct QualityDegradedEvent {
+    pub degraded: bool,
+    pub target: String,
+    pub jitter_ms: u32,
+    pub packet_loss_percent: f64,
+    // 触发劣化的指标："jitter"、"loss" 或 "jitter,loss"；恢复事件为空字符串
+    pub reason: String,
+}
+
+// WLAN MSM 原始通知：code 为 WLAN_NOTIFICATION_MSM 取值，name 为去掉前缀的可读名称（如 "roaming_start"）
+#[napi(object)]
+#[derive(Clone)]
+pub struct WlanEvent {
+    pub code: u32,
+    pub name: String,
+    // 触发通知的网卡 GUID（带花括号的大写形式）
+    pub interface_guid: String,
+    pub timestamp: String,
+}
+
+// 最近一次产出的网络质量采样（含平滑与评分），供 Node 侧同步读取；首次探测完成前为 None
+pub static LAST_QUALITY_SAMPLE: Mutex<Option<NetworkQualitySample>> = Mutex::new(None);
+// 最近一次 WiFi 信号状态快照（不受网络连通性过滤），断开连接或 WLAN 监控释放时清空
+pub static LAST_WLAN_STATUS: Mutex<Option<WlanStatus>> = Mutex::new(None);
+// 最近的周期探测采样（旧到新），供 Node 侧绘制趋势图；容量由 doInitialize 的 options 设置，0 表示不保留
+pub const DEFAULT_RECENT_QUALITY_CAPACITY: u32 = 60;
+pub const MAX_RECENT_QUALITY_CAPACITY: u32 = 3600;
+pub static RECENT_QUALITY_CAPACITY: AtomicU32 = AtomicU32::new(DEFAULT_RECENT_QUALITY_CAPACITY);
+pub static RECENT_QUALITY_SAMPLES: Mutex<VecDeque<NetworkQualitySample>> =
+    Mutex::new(VecDeque::new());
 // 网络质量探测是否启用：由初始化参数与运行时开关共同控制
 pub static NET_QUALITY_PROB_ENABLED: AtomicBool = AtomicBool::new(true);
+// 子系统开关：由 doInitialize 的 options 设置，仅在监控线程启动时读取；关闭的子系统不初始化也不清理
+pub static WLAN_SUBSYSTEM_ENABLED: AtomicBool = AtomicBool::new(true);
+// 网络质量探测子系统开关：关闭后 enableNetQualityProb 与 probeQualityNow 均不会发起探测
+pub static QUALITY_SUBSYSTEM_ENABLED: AtomicBool = AtomicBool::new(true);
+
+// 质量评分权重：四项之和为 1，延迟与丢包对体验影响最大
+pub const QUALITY_WEIGHT_LATENCY: f64 = 0.35;
+pub const QUALITY_WEIGHT_JITTER: f64 = 0.15;
+pub const QUALITY_WEIGHT_LOSS: f64 = 0.35;
+pub const QUALITY_WEIGHT_RETRANSMISSION: f64 = 0.15;
+// 单项评分阈值：不超过 GOOD 得满分，达到 BAD 得 0 分，中间线性插值
+pub const QUALITY_LATENCY_GOOD_MS: f64 = 50.0;
+pub const QUALITY_LATENCY_BAD_MS: f64 = 300.0;
+pub const QUALITY_JITTER_GOOD_MS: f64 = 10.0;
+pub const QUALITY_JITTER_BAD_MS: f64 = 100.0;
+pub const QUALITY_LOSS_GOOD_PERCENT: f64 = 0.0;
+pub const QUALITY_LOSS_BAD_PERCENT: f64 = 20.0;
+pub const QUALITY_RETRANSMISSION_GOOD_PERCENT: f64 = 1.0;
+pub const QUALITY_RETRANSMISSION_BAD_PERCENT: f64 = 10.0;
+// 评分分档：不低于 80 为 good，不低于 50 为 fair，其余为 poor
+pub const QUALITY_GRADE_GOOD_MIN_SCORE: u32 = 80;
+pub const QUALITY_GRADE_FAIR_MIN_SCORE: u32 = 50;
+pub const QUALITY_GRADE_GOOD: &str = "good";
+pub const QUALITY_GRADE_FAIR: &str = "fair";
+pub const QUALITY_GRADE_POOR: &str = "poor";
+// 本周期未能完成测量的原因（NetworkQualitySample.probe_error），正常测量时为空字符串
+pub const PROBE_ERROR_RESOLUTION_FAILED: &str = "resolution_failed";
+pub const PROBE_ERROR_IPV6_UNSUPPORTED: &str = "ipv6_unsupported";
+pub const PROBE_ERROR_OFFLINE: &str = "offline";
+pub const PROBE_ERROR_ICMP_UNAVAILABLE: &str = "icmp_unavailable";
+pub const PROBE_ERROR_MEASUREMENT_FAILED: &str = "measurement_failed";
 
 // 网络质量采样结果：用于记录一次探测周期内的主要指标
 #[napi(object)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct NetworkQualitySample {
+    pub target: String,
+    // 本次测量开始时的 Unix 毫秒时间戳，不受回调队列延迟影响，绘图与事件关联应以此为准
+    pub sampled_at_ms: f64,
+    // 目标 DNS 解析耗时：字面量 IP 为 0，解析失败为 u32::MAX（此时其余指标为零且丢包 100%）
+    pub dns_resolve_ms: u32,
+    // 本周期是否复用了解析缓存（setDnsCacheTtlSecs），复用时不发起查询，dns_resolve_ms 为 0
+    pub dns_cached: bool,
+    // 开启平滑（setQualitySmoothing）后为指数移动平均值，否则与 latency_avg_ms_raw 相同
     pub latency_avg_ms: u32,
+    // 本周期原始平均延迟，不受平滑影响
+    pub latency_avg_ms_raw: u32,
     pub latency_min_ms: u32,
     pub latency_max_ms: u32,
+    // 延迟百分位（最近秩法），样本不足时等于 latency_max_ms
+    pub latency_p50_ms: u32,
+    pub latency_p95_ms: u32,
+    pub latency_p99_ms: u32,
+    // 开启平滑后为指数移动平均值，否则与 jitter_ms_raw 相同
     pub jitter_ms: u32,
+    pub jitter_ms_raw: u32,
+    // RTT 样本的总体标准差，与 jitter_ms（相邻差值均值）并存以兼容旧的统计口径
+    pub jitter_stddev_ms: u32,
+    // RFC 3550 到达间隔抖动（J += (|D| - J) / 16），便于与 VoIP 质量工具的结果对比
+    pub jitter_rfc3550_ms: f64,
+    // 开启平滑后为指数移动平均值，否则与 packet_loss_percent_raw 相同
     pub packet_loss_percent: f64,
+    pub packet_loss_percent_raw: f64,
+    // 返回 ICMP 差错（TTL 超时、目标不可达等）的 Echo 占比，属于可达性问题，不计入 packet_loss_percent
+    pub icmp_error_percent: f64,
+    // 最后一个 ICMP Echo 的结果（IP_STATUS 去掉前缀的小写名称，如 "success"、"req_timed_out"、
+    // "ttl_expired_transit"），便于在工单中直接定位问题；未发起 ICMP 探测时为空字符串
+    pub last_ping_status: String,
+    // 本周期实际发出的 Echo（或 TCP 握手）次数，触发测量时限时小于配置的次数，丢包率按实际次数计算
+    pub echo_count: u32,
+    // 本条采样的测量耗时（毫秒，含 DNS 解析、Echo 与 TCP 回退），断网采样为 0；
+    // 多目标时各目标耗时之和接近探测间隔说明需要调大间隔或减少 pingCount
+    pub cycle_duration_ms: u32,
+    // 本周期能否读取系统 TCP 统计（GetTcpStatisticsEx），false 时 TCP 重传率、发送段等字段为 0 表示数据缺失而非无重传
+    pub tcp_stats_available: bool,
     pub tcp_retransmission_percent: f64,
     pub tcp_segments_sent: i64,
     pub tcp_segments_retransmitted: i64,
+    // 周期内主动建连失败次数（增量）
+    pub tcp_connection_failures: i64,
+    // 采样时刻已建立的 TCP 连接数（快照）
+    pub tcp_current_established: i64,
+    // 开启 DF 探测后收到“需要分片”回复：路径 MTU 小于探测报文，疑似 MTU 黑洞（如 VPN 隧道）
+    pub mtu_black_hole: bool,
+    // 到探测目标的最佳路由（出口接口或下一跳）与上一周期不同：WiFi/有线切换、VPN 连接/断开等
+    // 连通状态不变的切换也能由此发现；首次采样或路由查询失败时为 false
+    pub route_changed: bool,
+    // 本周期乱序应答（回显序号与发送序号不符）与重复应答（单次 Echo 收到多个应答）的次数，
+    // 均提示路径异常；载荷小于 4 字节时只统计重复应答
+    pub out_of_order_count: u32,
+    // 本周期 ICMP 全部失败并回退为 TCP 握手探测（疑似网络过滤 ICMP），ICMP 恢复成功的周期为 false；
+    // 仅 TCP 模式与仅有 IPv6 地址的目标不发 ICMP，始终为 false
+    pub icmp_blocked: bool,
+    // 仅 WiFi 探测模式下本条采样已绑定到 WiFi 网卡；模式关闭或 WiFi 不可用（按默认路由探测）时为 false
+    pub wifi_bound: bool,
+    // 本周期未发起测量的原因（"resolution_failed" / "ipv6_unsupported" / "offline" / "icmp_unavailable" /
+    // "measurement_failed"），此时其余指标为零、丢包按 100% 上报；每个周期都会上报采样，
+    // JS 侧可据此解释数据缺口。正常测量时为空字符串
+    pub probe_error: String,
+    // 默认网关平均延迟，用于区分本地链路与上游网络问题；每周期固定 2 个 500ms 超时的 Echo，
+    // 无法确定网关、仅 TCP 模式或网关持续无应答而退避跳过的周期为 0
+    pub gateway_latency_ms: u32,
+    // 默认网关丢包率，未探测网关的周期为 0
+    pub gateway_loss_percent: f64,
+    // 活动接口（默认路由所在接口）在本周期内的平均接收/发送速率（字节/秒），无法确定接口时为 0
+    pub rx_bytes_per_sec: f64,
+    pub tx_bytes_per_sec: f64,
+    // 活动接口在本周期内新增的接收/发送差错包与丢弃包数，首个周期、接口切换或计数器回绕时为 0
+    pub rx_errors: i64,
+    pub tx_errors: i64,
+    pub rx_discards: i64,
+    pub tx_discards: i64,
+    // 综合质量评分 0-100（权重见 QUALITY_WEIGHT_*），全部丢包时为 0
+    pub quality_score: u32,
+    // 评分分档："good" / "fair" / "poor"
+    pub quality_grade: String,
+    // 本条采样之前因回调队列已满而被丢弃的采样数，正常情况下为 0
+    pub dropped_samples: u32,
+    // 距该目标最近一次有成功回包的采样的秒数，本次成功时为 0；尚无成功采样时从探测启动开始计时
+    pub seconds_since_last_success: u32,
 }
 
 // 监控相关的全局状态，统一保存在 global.rs 里
+// 仅保存监控线程持有的 COM 对象；连通状态以 NETWORK_CONNECTED / NETWORK_STATUS 原子量为唯一来源，
+// 由 handle_network_status_message 在监控线程中更新
 pub struct MonitorState {
     pub network_list_manager: Option<INetworkListManager>,
     pub connection_point_container: Option<IConnectionPointContainer>,
     pub connection_point: Option<IConnectionPoint>,
     pub event_sink: Option<INetworkListManagerEvents>,
     pub cookie: u32,
-    pub signal_context: Option<Arc<Mutex<SignalMonitorContext>>>,
+    // 费用管理器，用于在连通性变化时刷新按流量计费状态
+    pub cost_manager: Option<INetworkCostManager>,
 }
 
 thread_local! {
@@ -94,10 +443,17 @@ thread_local! {
         connection_point: None,
         event_sink: None,
         cookie: 0,
-        signal_context: None,
+        cost_manager: None,
     }) };
 }
 
+// 当前生效的 WLAN 信号监控上下文。
+//
+// 说明：
+// - 阈值需要支持从 Node 主线程在运行时修改，而 MONITOR_STATE 是监控线程的 thread_local，其他线程无法访问。
+// - 因此与 NETWORK_CONNECTED 一样，把上下文提升为跨线程共享的全局状态。
+pub static SIGNAL_CONTEXT: Mutex<Option<Arc<Mutex<SignalMonitorContext>>>> = Mutex::new(None);
+
 pub fn with_monitor_state<F, R>(action: F) -> R
 where
     F: FnOnce(&mut MonitorState) -> R,
@@ -106,53 +462,586 @@ where
 }
 
 #[napi(object)]
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct NetworkStatus {
+    // 0 未连接，1 可访问互联网，2 仅本地网络（疑似认证门户），只判断 != 0 的旧逻辑仍视为“已连上网络”
     pub status: u32,
+    // 当前连接是否按流量计费（如手机热点），费用信息不可用时为 false
+    pub is_metered: bool,
+    // IPv4 / IPv6 是否分别可访问互联网；status 为 1 表示两者至少其一可用
+    pub ipv4_internet: bool,
+    pub ipv6_internet: bool,
+    // 系统返回的 NLM_CONNECTIVITY 原始位掩码，供需要自行判断的调用方使用，常用位：
+    // 0x0001 IPv4 无流量、0x0002 IPv6 无流量、0x0010 IPv4 子网、0x0020 IPv4 本地网络、0x0040 IPv4 互联网、
+    // 0x0100 IPv6 子网、0x0200 IPv6 本地网络、0x0400 IPv6 互联网；0 表示断开
+    pub raw_connectivity: u32,
+    // 是否存在已连接的 VPN/隧道适配器（PPP、隧道、Wintun/WireGuard、OpenVPN TAP 等），非常见驱动可能漏报
+    pub vpn_active: bool,
+    // 主连接（默认路由所在网卡）的介质："ethernet" / "wifi" / "cellular" / "other"，无可用路由时为空字符串
+    pub connection_type: String,
 }
 
 #[napi(object)]
 #[derive(Clone)]
 pub struct WlanStatus {
     pub strong: i32,
+    // 是否处于弱信号区间（与 strong == 0 等价），按 THRESHOLD_DROP/THRESHOLD_RECOVER 滞回判定
+    pub is_weak: bool,
+    // 本条状态是否由弱/强切换触发：进入弱信号或恢复时为 true，可据此提示用户靠近路由器
+    pub weak_state_changed: bool,
     pub quality: u32,
     pub rssi: i32,
+    // 当前关联的网络名称，未连接时为空字符串
+    pub ssid: String,
+    // 当前关联的接入点 MAC，格式 aa:bb:cc:dd:ee:ff；两次上报间 BSSID 变化即发生了漫游
+    pub bssid: String,
+    // 上报来源的 WLAN 接口 GUID，格式 {XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX}
+    pub interface_guid: String,
+    // 上报来源的 WLAN 网卡描述，便于日志与界面展示，读取失败时为空字符串
+    pub interface_name: String,
+    // 信号数据是否有效：接口仍处于连接状态但 WlanQueryInterface 失败时为 false，
+    // 此时 quality/rssi 及连接详情均为 0/空，strong 保留上一次的弱/强判定
+    pub signal_valid: bool,
+    // 当前连接是否启用加密，为 false 表示开放网络（未连接时同样为 false）
+    pub security_enabled: bool,
+    // 认证算法（DOT11_AUTH_ALGORITHM 原始值），未连接时为 0
+    pub auth_algorithm: u32,
+    // 加密算法（DOT11_CIPHER_ALGORITHM 原始值），未连接时为 0
+    pub cipher_algorithm: u32,
+    // 物理层类型（DOT11_PHY_TYPE 原始值）：7=802.11n，8=802.11ac，10=802.11ax，11=802.11be，未连接时为 0
+    pub phy_type: u32,
+    // 当前信道号，未连接或查询失败时为 0
+    pub channel: u32,
+    // 由信道推断的频段："2.4GHz" / "5GHz" / "6GHz"，无法判断时为空字符串
+    pub band: String,
+    // 协商的发送/接收速率（Kbps）：质量不变而发送速率下降通常意味着同频干扰，未连接时为 0
+    pub tx_rate_kbps: u32,
+    pub rx_rate_kbps: u32,
+    // 短期信号趋势："rising" / "falling" / "stable"（窗口与阈值见 SIGNAL_TREND_*），可用于提示用户朝信号变好的方向移动；
+    // 信号未知时为空字符串
+    pub trend: String,
+}
+
+// 监控线程心跳：Node 侧超过若干个心跳间隔未收到即可判定监控已停滞
+#[napi(object)]
+#[derive(Clone)]
+pub struct Heartbeat {
+    // 监控线程已运行的秒数
+    pub uptime_secs: u32,
+    // 最近一次收到系统网络/WLAN 事件的 Unix 毫秒时间戳，尚未收到事件时为 0
+    pub last_event_at_ms: i64,
+    pub timestamp: String,
+}
+
+// 监控线程运行信息，用于确认线程是否在运行并在调试器中定位该线程
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct MonitorInfo {
+    // 监控线程的系统线程 ID，未运行时为 0
+    pub thread_id: u32,
+    pub started: bool,
+    // 监控线程已运行的秒数，未运行时为 0
+    pub uptime_secs: u32,
+    // 各子系统实际是否在运行（按初始化与注册结果，而非初始化参数）：网络事件订阅有效、
+    // WLAN 句柄与通知已注册、质量探测线程在运行；可据此隐藏当前机器不可用的功能
+    pub network_monitor_active: bool,
+    pub wlan_monitor_active: bool,
+    pub quality_probe_active: bool,
+}
+
+// scanWlanNetworks 的单个扫描结果：每个接入点（BSSID）一条
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct WlanNetworkInfo {
+    // 隐藏网络为空字符串
+    pub ssid: String,
+    // 格式 aa:bb:cc:dd:ee:ff
+    pub bssid: String,
+    // 链路质量 0-100
+    pub quality: u32,
+    // 接收信号强度（dBm）
+    pub rssi: i32,
+    // 由中心频率换算的信道号与频段（"2.4GHz" / "5GHz" / "6GHz"），无法换算时为 0 与空字符串
+    pub channel: u32,
+    pub band: String,
+    pub security_enabled: bool,
+    // 默认认证/加密算法（DOT11_AUTH_ALGORITHM / DOT11_CIPHER_ALGORITHM 原始值），无法确定时为 0
+    pub auth_algorithm: u32,
+    pub cipher_algorithm: u32,
+    // DOT11_PHY_TYPE 原始值
+    pub phy_type: u32,
+    // 扫描到该接入点的 WLAN 接口 GUID
+    pub interface_guid: String,
+}
+
+// WLAN 扫描完成（或失败）通知已到达的接口，scanWlanNetworks 据此结束等待
+pub static WLAN_SCAN_COMPLETED: Mutex<Vec<GUID>> = Mutex::new(Vec::new());
+pub static WLAN_SCAN_SIGNAL: Condvar = Condvar::new();
+
+// 单个子系统的初始化结果：失败时 error 为系统返回的错误描述
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct SubsystemInitStatus {
+    // 子系统是否启用，通过 doInitialize 的 options 关闭时为 false（此时 ok 也为 false）
+    pub enabled: bool,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+impl SubsystemInitStatus {
+    pub fn from_result<E: std::fmt::Display>(result: Result<(), E>) -> Self {
+        match result {
+            Ok(()) => Self {
+                enabled: true,
+                ok: true,
+                error: None,
+            },
+            Err(error) => Self::failed(error.to_string()),
+        }
+    }
+
+    pub fn failed(error: impl Into<String>) -> Self {
+        Self {
+            enabled: true,
+            ok: false,
+            error: Some(error.into()),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+}
+
+// 单个系统 API 的自检结果：失败时 error_code 为 HRESULT（Win32 错误码按 HRESULT_FROM_WIN32 转换）
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct ApiCheckResult {
+    pub ok: bool,
+    // 成功时为 0
+    pub error_code: i32,
+    pub error: Option<String>,
+}
+
+impl ApiCheckResult {
+    pub fn from_win_result<T>(result: &windows::core::Result<T>) -> Self {
+        match result {
+            Ok(_) => Self {
+                ok: true,
+                error_code: 0,
+                error: None,
+            },
+            Err(error) => Self {
+                ok: false,
+                error_code: error.code().0,
+                error: Some(error.message()),
+            },
+        }
+    }
+
+    pub fn from_win32(code: u32) -> Self {
+        Self::from_win_result(&HRESULT::from_win32(code).ok())
+    }
+}
+
+// runDiagnostics 的结果：逐项记录监控依赖的系统 API 是否可用
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct DiagnosticsReport {
+    // 全部检查项均成功
+    pub all_ok: bool,
+    // WlanOpenHandle：WLAN 信号监控（未运行 WLAN 服务的服务器环境会失败）
+    pub wlan_open_handle: ApiCheckResult,
+    // IcmpCreateFile：ICMP 延迟与丢包探测
+    pub icmp_create_file: ApiCheckResult,
+    // CoCreateInstance(NetworkListManager) 与 GetConnectivity：网络连通性监控
+    pub network_list_manager: ApiCheckResult,
+    // GetTcpStatisticsEx：TCP 重传率统计
+    pub tcp_statistics: ApiCheckResult,
+}
+
+// doInitialize 的可选配置：未传入的字段使用默认值
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct InitOptions {
+    // 是否启用 WLAN 信号监控，默认 true；无 WLAN 服务的服务器环境可关闭以避免 WlanOpenHandle 报错
+    pub enable_wlan: Option<bool>,
+    // 是否启用网络质量探测子系统，默认 true；关闭后即使 netQualityProbEnable 为 true 也不会探测
+    pub enable_quality_probe: Option<bool>,
+    // 最近采样环形缓冲区容量，默认 60，最大 3600，0 表示不保留
+    pub recent_sample_capacity: Option<u32>,
+    // 网络状态防抖窗口（毫秒，最大 60000）：状态变化需稳定保持该时长才通过 reportNetworkStatus 上报，
+    // 默认 0 表示立即上报；窗口内来回抖动时只上报最终稳定的状态
+    pub network_status_debounce_ms: Option<u32>,
+    // 日志时间格式（chrono strftime 语法），默认 "%Y-%m-%d %H:%M:%S.%3f"；
+    // UTC ISO 8601 可配合 logTimestampUtc 使用 "%Y-%m-%dT%H:%M:%S%.3fZ"
+    pub log_timestamp_format: Option<String>,
+    // 日志时间是否使用 UTC，默认 false（本地时区）
+    pub log_timestamp_utc: Option<bool>,
+    // 视为“已连接”的 NLM_CONNECTIVITY 标志位掩码，默认 0x440（IPv4/IPv6 互联网）；
+    // 仅内网应用可传 0x660（加入 IPv4/IPv6 本地网络），无效或为 0 时使用默认值
+    pub connected_connectivity_flags: Option<u32>,
+}
+
+// checkTcpPorts 的单个端口结果，顺序与传入的端口列表一致
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct PortResult {
+    pub port: u16,
+    pub reachable: bool,
+    // TCP 握手耗时，不可达时为 0
+    pub connect_ms: u32,
+}
+
+// traceroute 的单跳结果，按 TTL 递增排列
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct TraceHop {
+    // 本跳使用的 TTL（从 1 开始）
+    pub ttl: u32,
+    // 应答方地址，本跳超时无应答时为空字符串
+    pub address: String,
+    // 应答往返时间，无应答时为 0
+    pub rtt_ms: u32,
+    // 应答状态（ip_status_name 的取值，如 "ttl_expired_transit" / "success" / "req_timed_out"）
+    pub status: String,
+    // 是否已到达目标（收到目标本身的 Echo 应答）
+    pub reached: bool,
+}
+
+// 回调投递统计（进程加载以来累计），用于排查“趋势图有缺口”等数据缺失问题
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct DispatchStats {
+    // 成功投递的质量采样数
+    pub samples_emitted: i64,
+    // 因回调队列已满等原因丢弃的质量采样数
+    pub samples_dropped: i64,
+    // 丢弃的日志数
+    pub logs_dropped: i64,
+    pub network_status_dropped: i64,
+    pub wlan_status_dropped: i64,
+    // 节流窗口内被合并的 WiFi 信号变化通知数（属于预期行为，非丢失）
+    pub wlan_status_coalesced: i64,
+    pub wlan_events_dropped: i64,
+    pub heartbeats_dropped: i64,
+    pub quality_degraded_dropped: i64,
+}
+
+// 读取当前的回调投递统计
+pub fn dispatch_stats() -> DispatchStats {
+    let load = |counter: &AtomicU64| counter.load(Ordering::SeqCst).min(i64::MAX as u64) as i64;
+    DispatchStats {
+        samples_emitted: load(&EMITTED_NET_QUALITY_EVENTS),
+        samples_dropped: load(&DROPPED_NET_QUALITY_EVENTS),
+        logs_dropped: load(&DROPPED_LOG_EVENTS),
+        network_status_dropped: load(&DROPPED_NET_STATUS_EVENTS),
+        wlan_status_dropped: load(&DROPPED_WLAN_STATUS_EVENTS),
+        wlan_status_coalesced: load(&COALESCED_WLAN_STATUS_EVENTS),
+        wlan_events_dropped: load(&DROPPED_WLAN_EVENTS),
+        heartbeats_dropped: load(&DROPPED_HEARTBEAT_EVENTS),
+        quality_degraded_dropped: load(&DROPPED_QUALITY_DEGRADED_EVENTS),
+    }
+}
+
+// 监控线程初始化阶段的结果，由 doInitialize 返回给调用方，便于按子系统降级功能
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct InitResult {
+    pub com: SubsystemInitStatus,
+    pub network: SubsystemInitStatus,
+    pub wlan: SubsystemInitStatus,
+    // 监控线程已在运行（重复调用 doInitialize），本结果为上一次的初始化结果，仅替换了回调
+    pub already_initialized: bool,
+}
+
+// doInitialize 失败时 JS 错误对象的 code，取值保持稳定，供调用方分支判断而不依赖错误文本
+// 重复初始化按成功返回（InitResult.already_initialized），新回调替换旧回调，不单独设错误码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitErrorCode {
+    // 回调的线程安全函数无法解除对事件循环的引用
+    CallbackRegistrationFailed,
+    // 无法注册 Node 环境退出时的清理钩子
+    CleanupHookFailed,
+}
+
+impl AsRef<str> for InitErrorCode {
+    fn as_ref(&self) -> &str {
+        match self {
+            InitErrorCode::CallbackRegistrationFailed => "CALLBACK_REGISTRATION_FAILED",
+            InitErrorCode::CleanupHookFailed => "CLEANUP_HOOK_FAILED",
+        }
+    }
+}
+
+// 最近一次监控线程初始化结果：重复调用 doInitialize（线程已在运行）时直接返回；
+// 新线程启动时清空，初始化完成后写入并通过 MONITOR_READY_SIGNAL 唤醒 waitForReady
+pub static LAST_INIT_RESULT: Mutex<Option<InitResult>> = Mutex::new(None);
+pub static MONITOR_READY_SIGNAL: Condvar = Condvar::new();
+// waitForReady 未指定超时时的默认等待上限
+pub const DEFAULT_READY_WAIT_TIMEOUT_MS: u32 = 30_000;
+
+// waitForReady 的结果：监控线程完成网络监控初始化并推送首个状态后的快照
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct ReadyReport {
+    pub init_result: InitResult,
+    // 初始化完成时的网络状态（取值同 NetworkStatus.status）
+    pub network_status: u32,
+}
+
+// 记录收到系统事件的时间，供心跳上报
+pub fn mark_event_received() {
+    LAST_EVENT_AT_MS.store(Utc::now().timestamp_millis(), Ordering::SeqCst);
+}
+
+// 各回调因队列已满（或正在关闭）而丢弃的事件累计数
+pub static DROPPED_NET_STATUS_EVENTS: AtomicU64 = AtomicU64::new(0);
+pub static DROPPED_WLAN_STATUS_EVENTS: AtomicU64 = AtomicU64::new(0);
+pub static DROPPED_NET_QUALITY_EVENTS: AtomicU64 = AtomicU64::new(0);
+pub static DROPPED_HEARTBEAT_EVENTS: AtomicU64 = AtomicU64::new(0);
+pub static DROPPED_QUALITY_DEGRADED_EVENTS: AtomicU64 = AtomicU64::new(0);
+pub static DROPPED_WLAN_EVENTS: AtomicU64 = AtomicU64::new(0);
+// 日志回调投递失败的累计数（不输出警告，避免日志投递失败再产生日志）
+pub static DROPPED_LOG_EVENTS: AtomicU64 = AtomicU64::new(0);
+// 成功投递到 Node 的质量采样累计数
+pub static EMITTED_NET_QUALITY_EVENTS: AtomicU64 = AtomicU64::new(0);
+// 因节流被合并、未单独上报的 WiFi 信号变化通知累计数
+pub static COALESCED_WLAN_STATUS_EVENTS: AtomicU64 = AtomicU64::new(0);
+// 自上一次成功投递以来被丢弃的质量采样数，随下一条成功投递的采样一并上报
+static PENDING_NET_QUALITY_DROPS: AtomicU32 = AtomicU32::new(0);
+
+// 检查线程安全函数的投递结果：失败时累计丢弃数，并按 1、2、4、8… 次节流输出警告
+fn track_call_status(status: Status, channel: &str, dropped: &AtomicU64) -> bool {
+    if status == Status::Ok {
+        return true;
+    }
+    let count = dropped.fetch_add(1, Ordering::SeqCst) + 1;
+    if count.is_power_of_two() {
+        report_error_log!(
+            "{} 回调投递失败：{:?}，累计丢弃 {} 条",
+            channel,
+            status,
+            count
+        );
+    }
+    false
+}
+
+// 静默上报：为 true 时网络状态、WiFi 状态与质量采样只更新内部状态与快照，不投递到 Node，
+// 用于驱动更新等已知会反复断网的操作期间
+pub static REPORTING_MUTED: AtomicBool = AtomicBool::new(false);
+// 最近一次产出的网络状态（含静默期间），解除静默时用于重新同步
+pub static LAST_NETWORK_STATUS: Mutex<Option<NetworkStatus>> = Mutex::new(None);
+
+// 设置静默状态；解除静默时按最新快照各补发一次网络状态、WiFi 状态与质量采样，使界面重新同步
+pub fn set_reporting_muted(muted: bool) {
+    let was_muted = REPORTING_MUTED.swap(muted, Ordering::SeqCst);
+    if was_muted == muted {
+        return;
+    }
+    if muted {
+        report_info_log!("已静默网络状态、WiFi 状态与质量采样上报");
+        return;
+    }
+
+    report_info_log!("解除上报静默，补发最新状态");
+    let network_status = LAST_NETWORK_STATUS.lock().unwrap().clone();
+    if let Some(network_status) = network_status {
+        report_network_status(network_status);
+    }
+    // 与 WLAN 回调一致：未联网时不上报 WiFi 状态
+    let wlan_status = LAST_WLAN_STATUS.lock().unwrap().clone();
+    if let Some(wlan_status) = wlan_status
+        && NETWORK_CONNECTED.load(Ordering::SeqCst)
+    {
+        report_wlan_status(wlan_status);
+    }
+    let quality_sample = LAST_QUALITY_SAMPLE.lock().unwrap().clone();
+    if let Some(quality_sample) = quality_sample {
+        report_net_quality(quality_sample);
+    }
 }
 
 pub fn report_network_status(info: NetworkStatus) {
-    if let Some(tsfn) = GLOBAL_REPORT_NET_STATUS.get() {
-        tsfn.call(Ok(info), ThreadsafeFunctionCallMode::NonBlocking);
-    } else {
-        println!("Warning: No report wnd listener registered yet!");
+    LAST_NETWORK_STATUS.lock().unwrap().replace(info.clone());
+    if REPORTING_MUTED.load(Ordering::SeqCst) {
+        return;
     }
+    let status = match GLOBAL_REPORT_NET_STATUS.lock().unwrap().as_ref() {
+        Some(tsfn) => tsfn.call(Ok(info), ThreadsafeFunctionCallMode::NonBlocking),
+        None => {
+            println!("Warning: No report wnd listener registered yet!");
+            return;
+        }
+    };
+    track_call_status(status, "reportNetworkStatus", &DROPPED_NET_STATUS_EVENTS);
 }
 
 pub fn report_wlan_status(info: WlanStatus) {
-    if let Some(tsfn) = GLOBAL_REPORT_WLAN_STATUS.get() {
-        tsfn.call(Ok(info), ThreadsafeFunctionCallMode::NonBlocking);
+    if REPORTING_MUTED.load(Ordering::SeqCst) {
+        return;
+    }
+    let status = match GLOBAL_REPORT_WLAN_STATUS.lock().unwrap().as_ref() {
+        Some(tsfn) => tsfn.call(Ok(info), ThreadsafeFunctionCallMode::NonBlocking),
+        None => {
+            println!("Warning: No report wlan status listener registered yet!");
+            return;
+        }
+    };
+    track_call_status(status, "reportWlanStatus", &DROPPED_WLAN_STATUS_EVENTS);
+}
+
+// 质量采样按“最新优先”处理：队列已满时丢弃当前采样而不阻塞，也不重发旧采样；
+// 丢弃数量记录在下一条成功投递的采样的 dropped_samples 中，避免 UI 误以为数据连续
+pub fn report_net_quality(mut info: NetworkQualitySample) {
+    if REPORTING_MUTED.load(Ordering::SeqCst) {
+        return;
+    }
+    let pending_drops = PENDING_NET_QUALITY_DROPS.load(Ordering::SeqCst);
+    info.dropped_samples = pending_drops;
+    let status = match GLOBAL_REPORT_NET_QUALITY.lock().unwrap().as_ref() {
+        Some(tsfn) => tsfn.call(Ok(info), ThreadsafeFunctionCallMode::NonBlocking),
+        None => {
+            println!("Warning: No report net quality listener registered yet!");
+            return;
+        }
+    };
+    if track_call_status(status, "reportNetQuality", &DROPPED_NET_QUALITY_EVENTS) {
+        EMITTED_NET_QUALITY_EVENTS.fetch_add(1, Ordering::SeqCst);
+        PENDING_NET_QUALITY_DROPS.fetch_sub(pending_drops, Ordering::SeqCst);
     } else {
-        println!("Warning: No report wlan status listener registered yet!");
+        PENDING_NET_QUALITY_DROPS.fetch_add(1, Ordering::SeqCst);
     }
 }
 
-pub fn report_net_quality(info: NetworkQualitySample) {
-    if let Some(tsfn) = GLOBAL_REPORT_NET_QUALITY.get() {
-        tsfn.call(Ok(info), ThreadsafeFunctionCallMode::NonBlocking);
+pub fn report_quality_degraded(info: QualityDegradedEvent) {
+    let status = match GLOBAL_REPORT_QUALITY_DEGRADED.lock().unwrap().as_ref() {
+        Some(tsfn) => tsfn.call(Ok(info), ThreadsafeFunctionCallMode::NonBlocking),
+        None => return,
+    };
+    track_call_status(
+        status,
+        "reportQualityDegraded",
+        &DROPPED_QUALITY_DEGRADED_EVENTS,
+    );
+}
+
+pub fn report_wlan_event(info: WlanEvent) {
+    let status = match GLOBAL_REPORT_WLAN_EVENT.lock().unwrap().as_ref() {
+        Some(tsfn) => tsfn.call(Ok(info), ThreadsafeFunctionCallMode::NonBlocking),
+        None => return,
+    };
+    track_call_status(status, "reportWlanEvent", &DROPPED_WLAN_EVENTS);
+}
+
+pub fn report_heartbeat(info: Heartbeat) {
+    let status = match GLOBAL_REPORT_HEARTBEAT.lock().unwrap().as_ref() {
+        Some(tsfn) => tsfn.call(Ok(info), ThreadsafeFunctionCallMode::NonBlocking),
+        None => return,
+    };
+    track_call_status(status, "reportHeartbeat", &DROPPED_HEARTBEAT_EVENTS);
+}
+
+pub const LOG_LEVEL_NAME_ERROR: &str = "error";
+pub const LOG_LEVEL_NAME_INFO: &str = "info";
+pub const LOG_LEVEL_NAME_DEBUG: &str = "debug";
+
+// 日志级别：数值越大输出越详细，低于阈值的日志在宏内直接短路，不会构造格式化参数
+pub const LOG_LEVEL_OFF: u32 = 0;
+pub const LOG_LEVEL_ERROR: u32 = 1;
+pub const LOG_LEVEL_INFO: u32 = 2;
+pub const LOG_LEVEL_DEBUG: u32 = 3;
+// 当前日志级别，默认输出 error 与 info；开发模式下额外输出调试日志，保持原有行为
+pub static LOG_LEVEL: AtomicU32 = AtomicU32::new(if cfg!(debug_assertions) {
+    LOG_LEVEL_DEBUG
+} else {
+    LOG_LEVEL_INFO
+});
+
+// 默认日志时间格式（本地时区），与旧版本输出保持一致
+pub const DEFAULT_LOG_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S.%3f";
+// 日志时间格式（chrono strftime 语法），None 表示使用 DEFAULT_LOG_TIMESTAMP_FORMAT
+pub static LOG_TIMESTAMP_FORMAT: Mutex<Option<String>> = Mutex::new(None);
+// 日志时间是否使用 UTC，默认本地时区
+pub static LOG_TIMESTAMP_UTC: AtomicBool = AtomicBool::new(false);
+
+// 校验 strftime 格式串：包含无法识别的格式符时 chrono 在格式化阶段才会报错，需提前拒绝
+pub fn is_valid_timestamp_format(format: &str) -> bool {
+    !format.is_empty() && !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+// 按指定时区与格式输出时间
+fn format_timestamp(now: DateTime<Utc>, format: &str, utc: bool) -> String {
+    if utc {
+        now.format(format).to_string()
     } else {
-        println!("Warning: No report net quality listener registered yet!");
+        now.with_timezone(&Local).format(format).to_string()
     }
 }
 
-fn report_log(msg: String) {
+#[doc(hidden)]
+pub fn log_enabled(level: u32) -> bool {
+    level != LOG_LEVEL_OFF && level <= LOG_LEVEL.load(Ordering::Relaxed)
+}
+
+// 结构化日志事件：Node 侧可直接按 level 过滤，无需解析字符串
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct LogEvent {
+    pub level: String,
+    pub timestamp: String,
+    pub module: String,
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+// 将日志事件格式化为原先的单行字符串，保持与旧版日志格式一致
+pub fn format_log_event(event: &LogEvent) -> String {
+    let tag = if event.level == LOG_LEVEL_NAME_ERROR {
+        "selection_error"
+    } else {
+        event.level.as_str()
+    };
+    format!(
+        "[{}]:{} - {}:{}:{} {} - {}",
+        tag, event.timestamp, event.file, event.line, event.column, event.module, event.message
+    )
+}
+
+fn report_log(event: LogEvent) {
     if cfg!(debug_assertions) {
-        println!("{}", msg);
-    } else if let Some(tsfn) = GLOBAL_LOG.get() {
-        tsfn.call(Ok(msg), ThreadsafeFunctionCallMode::NonBlocking);
+        println!("{}", format_log_event(&event));
+    } else if let Some(tsfn) = GLOBAL_LOG.lock().unwrap().as_ref() {
+        if tsfn.call(Ok(event), ThreadsafeFunctionCallMode::NonBlocking) != Status::Ok {
+            DROPPED_LOG_EVENTS.fetch_add(1, Ordering::Relaxed);
+        }
     } else {
         println!("Warning: No report log listener registered yet!");
     }
 }
 
+fn build_log_event(
+    level: &str,
+    msg: fmt::Arguments,
+    module_path: &'static str,
+    file: &'static str,
+    line: u32,
+    column: u32,
+) -> LogEvent {
+    LogEvent {
+        level: level.to_string(),
+        timestamp: get_current_time(),
+        module: module_path.to_string(),
+        file: file.to_string(),
+        line,
+        column,
+        message: msg.to_string(),
+    }
+}
+
 #[doc(hidden)]
 pub(crate) fn report_error(
     msg: fmt::Arguments,
@@ -161,12 +1050,14 @@ pub(crate) fn report_error(
     line: u32,
     column: u32,
 ) {
-    let curr_time = get_current_time();
-    let log_msg = format!(
-        "[selection_error]:{} - {}:{}:{} {} - {}",
-        curr_time, file, line, column, module_path, msg
-    );
-    report_log(log_msg);
+    report_log(build_log_event(
+        LOG_LEVEL_NAME_ERROR,
+        msg,
+        module_path,
+        file,
+        line,
+        column,
+    ));
 }
 
 #[doc(hidden)]
@@ -177,25 +1068,47 @@ pub(crate) fn report_info(
     line: u32,
     column: u32,
 ) {
-    let curr_time = get_current_time();
-    let log_msg = format!(
-        "[info]:{} - {}:{}:{} {} - {}",
-        curr_time, file, line, column, module_path, msg
-    );
-    report_log(log_msg);
+    report_log(build_log_event(
+        LOG_LEVEL_NAME_INFO,
+        msg,
+        module_path,
+        file,
+        line,
+        column,
+    ));
+}
+
+#[doc(hidden)]
+pub(crate) fn report_debug(
+    msg: fmt::Arguments,
+    module_path: &'static str,
+    file: &'static str,
+    line: u32,
+    column: u32,
+) {
+    report_log(build_log_event(
+        LOG_LEVEL_NAME_DEBUG,
+        msg,
+        module_path,
+        file,
+        line,
+        column,
+    ));
 }
 
 #[macro_export]
 macro_rules! report_error_log {
     // format_args! 是编译器内置宏，它不分配内存，只打包参数
     ($($arg:tt)*) => {
-        $crate::global::report_error(
-            format_args!($($arg)*),
-            module_path!(),
-            file!(),
-            line!(),
-            column!(),
-        )
+        if $crate::global::log_enabled($crate::global::LOG_LEVEL_ERROR) {
+            $crate::global::report_error(
+                format_args!($($arg)*),
+                module_path!(),
+                file!(),
+                line!(),
+                column!(),
+            )
+        }
     }
 }
 
@@ -203,25 +1116,84 @@ macro_rules! report_error_log {
 macro_rules! report_info_log {
     // format_args! 是编译器内置宏，它不分配内存，只打包参数
     ($($arg:tt)*) => {
-        $crate::global::report_info(
-            format_args!($($arg)*),
-            module_path!(),
-            file!(),
-            line!(),
-            column!(),
-        )
+        if $crate::global::log_enabled($crate::global::LOG_LEVEL_INFO) {
+            $crate::global::report_info(
+                format_args!($($arg)*),
+                module_path!(),
+                file!(),
+                line!(),
+                column!(),
+            )
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! report_debug_log {
+    // format_args! 是编译器内置宏，它不分配内存，只打包参数
+    ($($arg:tt)*) => {
+        if $crate::global::log_enabled($crate::global::LOG_LEVEL_DEBUG) {
+            $crate::global::report_debug(
+                format_args!($($arg)*),
+                module_path!(),
+                file!(),
+                line!(),
+                column!(),
+            )
+        }
     }
 }
 
+// 日志与事件的时间戳，格式与时区由 doInitialize 的 logTimestampFormat / logTimestampUtc 决定
 pub fn get_current_time() -> String {
-    Local::now().format("%Y-%m-%d %H:%M:%S.%3f").to_string()
+    let format = LOG_TIMESTAMP_FORMAT.lock().unwrap();
+    format_timestamp(
+        Utc::now(),
+        format.as_deref().unwrap_or(DEFAULT_LOG_TIMESTAMP_FORMAT),
+        LOG_TIMESTAMP_UTC.load(Ordering::Relaxed),
+    )
+}
+
+// 当前 Unix 毫秒时间戳，与日志时间使用同一时钟；以 f64 返回便于直接作为 JS number 使用
+pub fn get_current_unix_millis() -> f64 {
+    Local::now().timestamp_millis() as f64
 }
 
 #[cfg(test)]
 mod tests {
-    use super::NETWORK_CONNECTED;
+    use super::{
+        ApiCheckResult, DEFAULT_LOG_TIMESTAMP_FORMAT, InitErrorCode, LOG_LEVEL, LOG_LEVEL_DEBUG,
+        LOG_LEVEL_ERROR, LOG_LEVEL_INFO, LOG_LEVEL_NAME_ERROR, LOG_LEVEL_NAME_INFO, LOG_LEVEL_OFF,
+        LogEvent, NETWORK_CONNECTED, format_log_event, format_timestamp, is_valid_timestamp_format,
+        log_enabled,
+    };
+    use chrono::{DateTime, Utc};
     use std::sync::atomic::Ordering;
 
+    fn sample_log_event(level: &str) -> LogEvent {
+        LogEvent {
+            level: level.to_string(),
+            timestamp: "2024-01-01 00:00:00.000".to_string(),
+            module: "perception_network_status::wlan".to_string(),
+            file: "src/wlan.rs".to_string(),
+            line: 12,
+            column: 5,
+            message: "hello".to_string(),
+        }
+    }
+
+    #[test]
+    fn log_event_formats_like_legacy_strings() {
+        assert_eq!(
+            format_log_event(&sample_log_event(LOG_LEVEL_NAME_INFO)),
+            "[info]:2024-01-01 00:00:00.000 - src/wlan.rs:12:5 perception_network_status::wlan - hello"
+        );
+        assert_eq!(
+            format_log_event(&sample_log_event(LOG_LEVEL_NAME_ERROR)),
+            "[selection_error]:2024-01-01 00:00:00.000 - src/wlan.rs:12:5 perception_network_status::wlan - hello"
+        );
+    }
+
     #[test]
     fn network_connected_is_visible_across_threads() {
         NETWORK_CONNECTED.store(false, Ordering::SeqCst);
@@ -234,4 +1206,62 @@ mod tests {
         assert!(NETWORK_CONNECTED.load(Ordering::SeqCst));
         NETWORK_CONNECTED.store(false, Ordering::SeqCst);
     }
+
+    #[test]
+    fn log_level_filters_less_important_levels() {
+        let previous = LOG_LEVEL.swap(LOG_LEVEL_ERROR, Ordering::SeqCst);
+        assert!(log_enabled(LOG_LEVEL_ERROR));
+        assert!(!log_enabled(LOG_LEVEL_INFO));
+
+        LOG_LEVEL.store(LOG_LEVEL_OFF, Ordering::SeqCst);
+        assert!(!log_enabled(LOG_LEVEL_ERROR));
+
+        LOG_LEVEL.store(LOG_LEVEL_DEBUG, Ordering::SeqCst);
+        assert!(log_enabled(LOG_LEVEL_DEBUG));
+        LOG_LEVEL.store(previous, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn timestamp_format_honors_zone_and_rejects_invalid_specifiers() {
+        let now = DateTime::parse_from_rfc3339("2024-01-02T03:04:05.678Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            format_timestamp(now, "%Y-%m-%dT%H:%M:%S%.3fZ", true),
+            "2024-01-02T03:04:05.678Z"
+        );
+        assert_eq!(
+            format_timestamp(now, DEFAULT_LOG_TIMESTAMP_FORMAT, true),
+            "2024-01-02 03:04:05.678"
+        );
+        assert!(is_valid_timestamp_format(DEFAULT_LOG_TIMESTAMP_FORMAT));
+        assert!(!is_valid_timestamp_format("%Y-%Q"));
+        assert!(!is_valid_timestamp_format(""));
+    }
+
+    #[test]
+    fn api_check_maps_win32_codes_to_hresult() {
+        let ok = ApiCheckResult::from_win32(0);
+        assert!(ok.ok);
+        assert_eq!(ok.error_code, 0);
+        assert!(ok.error.is_none());
+
+        // ERROR_ACCESS_DENIED (5) 转换为 HRESULT 0x80070005
+        let denied = ApiCheckResult::from_win32(5);
+        assert!(!denied.ok);
+        assert_eq!(denied.error_code as u32, 0x8007_0005);
+        assert!(denied.error.is_some());
+    }
+
+    #[test]
+    fn init_error_codes_are_stable() {
+        assert_eq!(
+            InitErrorCode::CallbackRegistrationFailed.as_ref(),
+            "CALLBACK_REGISTRATION_FAILED"
+        );
+        assert_eq!(
+            InitErrorCode::CleanupHookFailed.as_ref(),
+            "CLEANUP_HOOK_FAILED"
+        );
+    }
 }
@@ -1,28 +1,70 @@
 #![deny(clippy::all)]
+mod adapters;
+mod diagnostics;
+mod dns;
 mod global;
+mod messages;
 mod monitor;
 mod network;
 mod network_quality;
+pub mod snapshot;
 mod wlan;
 
+use napi::bindgen_prelude::{AsyncTask, JsValuesTupleIntoVec};
 use napi::threadsafe_function::ThreadsafeFunction;
 use napi::{Env, Status};
 use napi_derive::napi;
 
+use std::net::{IpAddr, Ipv4Addr};
 use std::ptr::null_mut;
 use std::sync::Mutex;
 use std::sync::atomic::Ordering;
+use std::sync::mpsc::RecvTimeoutError;
 use std::time::{Duration, Instant};
 
+use windows::Win32::Foundation::ERROR_ACCESS_DENIED;
+use windows::core::HRESULT;
+
 use crate::global::{
-    GLOBAL_LOG, GLOBAL_REPORT_NET_QUALITY, GLOBAL_REPORT_NET_STATUS, GLOBAL_REPORT_WLAN_STATUS,
-    NET_QUALITY_PROB_ENABLED, NetworkQualitySample, NetworkStatus, SOME_EVENT, THRESHOLD_DROP,
-    THRESHOLD_RECOVER, WlanStatus,
+    CLEANUP_HOOK_REGISTERED, CONNECTED_CONNECTIVITY_FLAGS, DEFAULT_CONNECTED_CONNECTIVITY_FLAGS,
+    DEFAULT_PING_DEADLINE_MS, DEFAULT_READY_WAIT_TIMEOUT_MS, DEFAULT_RECENT_QUALITY_CAPACITY,
+    DEGRADED_JITTER_ENTER_MS, DEGRADED_JITTER_EXIT_MS, DEGRADED_LOSS_ENTER_PERCENT,
+    DEGRADED_LOSS_EXIT_PERCENT, DNS_CACHE_TTL_SECS, DNS_SERVER_OVERRIDE, DiagnosticsReport,
+    DispatchStats, GLOBAL_LOG, GLOBAL_REPORT_HEARTBEAT, GLOBAL_REPORT_NET_QUALITY,
+    GLOBAL_REPORT_NET_STATUS, GLOBAL_REPORT_QUALITY_DEGRADED, GLOBAL_REPORT_WLAN_EVENT,
+    GLOBAL_REPORT_WLAN_STATUS, Heartbeat, ICMP_CREATE_RETRIES, ICMP_ECHO_CONCURRENCY,
+    ICMP_TIMEOUT_ESCALATION, InitErrorCode, InitOptions, InitResult, LAST_INIT_RESULT, LOG_LEVEL,
+    LOG_LEVEL_DEBUG, LOG_TIMESTAMP_FORMAT, LOG_TIMESTAMP_UTC, LogEvent, MAX_DNS_CACHE_TTL_SECS,
+    MAX_ICMP_CREATE_RETRIES, MAX_ICMP_ECHO_CONCURRENCY, MAX_NETWORK_STATUS_DEBOUNCE_MS,
+    MAX_PING_COUNT, MAX_PING_PAYLOAD_SIZE, MAX_PING_TIMEOUT_MS, MAX_PROBE_INTERVAL_JITTER_PERCENT,
+    MAX_RECENT_QUALITY_CAPACITY, MAX_TCP_PORT_CHECK_PORTS, MAX_TRACEROUTE_HOPS,
+    MAX_WLAN_STATUS_DEBOUNCE_MS, MIN_PING_TIMEOUT_MS, MONITOR_STARTED, MonitorInfo,
+    NET_QUALITY_PROB_ENABLED, NETWORK_STATUS_DEBOUNCE_MS, NetworkQualitySample, NetworkStatus,
+    PING_COUNT, PING_DEADLINE_MS, PING_DONT_FRAGMENT, PING_PAYLOAD_SIZE, PING_TARGETS_ROUND_ROBIN,
+    PING_TIMEOUT_MS, PROBE_INTERVAL_JITTER_PERCENT, PROBE_INTERVAL_SECS, PROBE_MODE,
+    PROBE_SOURCE_ADDRESS, PROBE_WIFI_ONLY, QUALITY_SMOOTHING_ALPHA, QUALITY_SUBSYSTEM_ENABLED,
+    QualityDegradedEvent, RECENT_QUALITY_CAPACITY, RECENT_QUALITY_SAMPLES, SOME_EVENT,
+    SubsystemInitStatus, TCP_PERSISTENT_RTT, TCP_PERSISTENT_RTT_PORTS, THRESHOLD_DROP,
+    THRESHOLD_RECOVER, VALID_CONNECTED_CONNECTIVITY_FLAGS, WLAN_STATUS_DEBOUNCE_MS,
+    WLAN_SUBSYSTEM_ENABLED, WlanEvent, WlanStatus, is_valid_timestamp_format,
+};
+use crate::monitor::{
+    WaitForReadyTask, cleanup_monitor_thread, start_monitor_thread, stop_monitor_thread,
 };
-use crate::monitor::{cleanup_monitor_thread, start_monitor_thread};
-use crate::network_quality::{start_quality_probe, stop_quality_probe};
+use crate::network_quality::{
+    CheckTcpPortsTask, ProbeQualityTask, TracerouteTask, clear_resolved_targets,
+    close_persistent_tcp_connections, set_ping_target_list, start_quality_probe,
+    stop_quality_probe,
+};
+use crate::wlan::ScanWlanNetworksTask;
 
-// Node 侧初始化入口：注册回调、启动监控线程，并推送一次空消息用于握手
+/// Node 侧初始化入口：注册回调并启动监控线程，等待线程完成 COM、网络与 WLAN 初始化后
+/// 返回各子系统的结果（最长等待 5 秒），调用方可据此关闭初始化失败的功能。
+/// 监控线程已在运行时（重复调用）返回上一次的初始化结果。
+/// options 可关闭 WLAN 监控或网络质量探测子系统，仅在监控线程启动时生效。
+/// 失败时抛出的错误带稳定的 code（CALLBACK_REGISTRATION_FAILED / CLEANUP_HOOK_FAILED）。
+/// 重复初始化不视为错误：新回调直接替换旧回调（不存在监听冲突），返回 alreadyInitialized: true，
+/// 因此没有 ALREADY_INITIALIZED / LISTENER_CONFLICT 错误码，页面重载后再次初始化无需捕获异常。
 #[napi]
 #[allow(clippy::too_many_arguments)]
 pub fn do_initialize(
@@ -31,50 +73,45 @@ pub fn do_initialize(
     threshold_drop: u32,
     threshold_recover: u32,
     mut report_net_quality: ThreadsafeFunction<NetworkQualitySample>,
-    mut log: ThreadsafeFunction<String>,
+    mut log: ThreadsafeFunction<LogEvent>,
     net_quality_prob_enable: bool,
+    ping_target: Option<String>,
+    report_heartbeat: Option<ThreadsafeFunction<Heartbeat>>,
+    report_quality_degraded: Option<ThreadsafeFunction<QualityDegradedEvent>>,
+    report_wlan_event: Option<ThreadsafeFunction<WlanEvent>>,
+    options: Option<InitOptions>,
     env: Env,
-) -> napi::Result<()> {
+) -> napi::Result<InitResult, InitErrorCode> {
     // 仅在初始化阶段持有线程安全函数，随后交由全局缓存管理
+    let registration_failed = |error| init_error(InitErrorCode::CallbackRegistrationFailed, error);
     #[allow(deprecated)]
-    report_network_status.unref(&env)?;
+    report_network_status
+        .unref(&env)
+        .map_err(registration_failed)?;
     #[allow(deprecated)]
-    report_wlan_status.unref(&env)?;
+    report_wlan_status
+        .unref(&env)
+        .map_err(registration_failed)?;
     #[allow(deprecated)]
-    report_net_quality.unref(&env)?;
+    report_net_quality
+        .unref(&env)
+        .map_err(registration_failed)?;
     #[allow(deprecated)]
-    log.unref(&env)?;
+    log.unref(&env).map_err(registration_failed)?;
+    let report_heartbeat = unref_optional(report_heartbeat, &env).map_err(registration_failed)?;
+    let report_quality_degraded =
+        unref_optional(report_quality_degraded, &env).map_err(registration_failed)?;
+    let report_wlan_event = unref_optional(report_wlan_event, &env).map_err(registration_failed)?;
 
-    GLOBAL_REPORT_NET_STATUS
-        .set(report_network_status)
-        .map_err(|_| {
-            napi::Error::new(
-                Status::GenericFailure,
-                "Global report listener already registered",
-            )
-        })?;
-    GLOBAL_REPORT_WLAN_STATUS
-        .set(report_wlan_status)
-        .map_err(|_| {
-            napi::Error::new(
-                Status::GenericFailure,
-                "Global report wlan status listener already registered",
-            )
-        })?;
-    GLOBAL_REPORT_NET_QUALITY
-        .set(report_net_quality)
-        .map_err(|_| {
-            napi::Error::new(
-                Status::GenericFailure,
-                "Global report net quality listener already registered",
-            )
-        })?;
-    GLOBAL_LOG.set(log).map_err(|_| {
-        napi::Error::new(
-            Status::GenericFailure,
-            "Global log listener already registered",
-        )
-    })?;
+    // 重复初始化时直接替换旧回调，旧的线程安全函数随之释放
+    *GLOBAL_REPORT_NET_STATUS.lock().unwrap() = Some(report_network_status);
+    *GLOBAL_REPORT_WLAN_STATUS.lock().unwrap() = Some(report_wlan_status);
+    *GLOBAL_REPORT_NET_QUALITY.lock().unwrap() = Some(report_net_quality);
+    *GLOBAL_LOG.lock().unwrap() = Some(log);
+    // 心跳回调可选，重复初始化未传入时清除旧回调
+    *GLOBAL_REPORT_HEARTBEAT.lock().unwrap() = report_heartbeat;
+    *GLOBAL_REPORT_QUALITY_DEGRADED.lock().unwrap() = report_quality_degraded;
+    *GLOBAL_REPORT_WLAN_EVENT.lock().unwrap() = report_wlan_event;
 
     // 初始化事件节流缓存，避免高频日志冲击主线程
     SOME_EVENT.get_or_init(|| {
@@ -88,6 +125,66 @@ pub fn do_initialize(
     THRESHOLD_DROP.store(threshold_drop, Ordering::SeqCst);
     THRESHOLD_RECOVER.store(threshold_recover, Ordering::SeqCst);
     NET_QUALITY_PROB_ENABLED.store(net_quality_prob_enable, Ordering::SeqCst);
+    let options = options.unwrap_or_default();
+    // 子系统开关只在本次调用实际启动监控线程时写入，见 start_monitor_thread
+    let wlan_enabled = options.enable_wlan.unwrap_or(true);
+    let quality_enabled = options.enable_quality_probe.unwrap_or(true);
+    let recent_capacity = options
+        .recent_sample_capacity
+        .unwrap_or(DEFAULT_RECENT_QUALITY_CAPACITY);
+    if recent_capacity > MAX_RECENT_QUALITY_CAPACITY {
+        report_error_log!(
+            "最近采样缓冲区容量 {} 超过上限，按 {} 处理",
+            recent_capacity,
+            MAX_RECENT_QUALITY_CAPACITY
+        );
+    }
+    RECENT_QUALITY_CAPACITY.store(
+        recent_capacity.min(MAX_RECENT_QUALITY_CAPACITY),
+        Ordering::SeqCst,
+    );
+
+    LOG_TIMESTAMP_UTC.store(options.log_timestamp_utc.unwrap_or(false), Ordering::SeqCst);
+    let timestamp_format = options.log_timestamp_format.filter(|format| {
+        let valid = is_valid_timestamp_format(format);
+        if !valid {
+            report_error_log!("日志时间格式无效，使用默认格式：{:?}", format);
+        }
+        valid
+    });
+    *LOG_TIMESTAMP_FORMAT.lock().unwrap() = timestamp_format;
+
+    let network_debounce_ms = options.network_status_debounce_ms.unwrap_or(0);
+    if network_debounce_ms > MAX_NETWORK_STATUS_DEBOUNCE_MS {
+        report_error_log!(
+            "网络状态防抖窗口 {}ms 超过上限，按 {}ms 处理",
+            network_debounce_ms,
+            MAX_NETWORK_STATUS_DEBOUNCE_MS
+        );
+    }
+    NETWORK_STATUS_DEBOUNCE_MS.store(
+        network_debounce_ms.min(MAX_NETWORK_STATUS_DEBOUNCE_MS),
+        Ordering::SeqCst,
+    );
+
+    let connected_flags = options
+        .connected_connectivity_flags
+        .map(|flags| flags & VALID_CONNECTED_CONNECTIVITY_FLAGS)
+        .filter(|flags| *flags != 0);
+    if connected_flags.is_none() && options.connected_connectivity_flags.is_some() {
+        report_error_log!(
+            "“已连接”标志位掩码无效：{:?}，使用默认值 {:#x}",
+            options.connected_connectivity_flags,
+            DEFAULT_CONNECTED_CONNECTIVITY_FLAGS
+        );
+    }
+    CONNECTED_CONNECTIVITY_FLAGS.store(
+        connected_flags.unwrap_or(DEFAULT_CONNECTED_CONNECTIVITY_FLAGS),
+        Ordering::SeqCst,
+    );
+
+    // 探测目标为空时回退到默认值，保证旧调用方不受影响
+    set_ping_target_list(ping_target.into_iter().collect());
 
     if cfg!(debug_assertions) {
         report_info_log!("[Debug] 当前正处于开发模式运行，开启详细日志...");
@@ -95,13 +192,90 @@ pub fn do_initialize(
         report_info_log!("[Release] 生产模式运行");
     }
 
-    // 绑定清理钩子，确保 Node 退出时请求监控线程停止
-    env.add_env_cleanup_hook(null_mut(), |arg| unsafe { cleanup_monitor_thread(arg) })?;
+    // 绑定清理钩子，确保 Node 退出时请求监控线程停止；同一钩子只能注册一次
+    if !CLEANUP_HOOK_REGISTERED.swap(true, Ordering::SeqCst)
+        && let Err(error) =
+            env.add_env_cleanup_hook(null_mut(), |arg| unsafe { cleanup_monitor_thread(arg) })
+    {
+        CLEANUP_HOOK_REGISTERED.store(false, Ordering::SeqCst);
+        return Err(init_error(InitErrorCode::CleanupHookFailed, error));
+    }
 
     // 启动后台监控线程：网络事件与 WLAN 事件在该线程中处理
-    start_monitor_thread();
+    let Some(init_receiver) = start_monitor_thread(wlan_enabled, quality_enabled) else {
+        if wlan_enabled != WLAN_SUBSYSTEM_ENABLED.load(Ordering::SeqCst)
+            || quality_enabled != QUALITY_SUBSYSTEM_ENABLED.load(Ordering::SeqCst)
+        {
+            report_info_log!(
+                "监控线程已在运行，子系统开关仅在监控线程启动时生效，忽略 enableWlan={} enableQualityProbe={}",
+                wlan_enabled,
+                quality_enabled
+            );
+        }
+        let previous = LAST_INIT_RESULT.lock().unwrap().clone().unwrap_or_default();
+        return Ok(InitResult {
+            already_initialized: true,
+            ..previous
+        });
+    };
+    match init_receiver.recv_timeout(INIT_WAIT_TIMEOUT) {
+        Ok(init_result) => Ok(init_result),
+        Err(RecvTimeoutError::Disconnected) => {
+            report_error_log!("监控线程在初始化完成前异常退出");
+            let crashed =
+                SubsystemInitStatus::failed("monitor thread exited during initialization");
+            Ok(InitResult {
+                com: crashed.clone(),
+                network: crashed.clone(),
+                wlan: crashed,
+                already_initialized: false,
+            })
+        }
+        Err(RecvTimeoutError::Timeout) => {
+            report_error_log!(
+                "等待监控线程初始化超时（{}s），初始化结果未知",
+                INIT_WAIT_TIMEOUT.as_secs()
+            );
+            let timed_out = SubsystemInitStatus::failed("initialization timed out");
+            Ok(InitResult {
+                com: timed_out.clone(),
+                network: timed_out.clone(),
+                wlan: timed_out,
+                already_initialized: false,
+            })
+        }
+    }
+}
 
-    Ok(())
+// 等待监控线程初始化阶段的上限，避免系统服务异常时长时间阻塞 Node 主线程
+const INIT_WAIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 保留底层错误文本，错误 code 换成 doInitialize 的稳定错误码
+fn init_error(code: InitErrorCode, error: napi::Error) -> napi::Error<InitErrorCode> {
+    report_error_log!("初始化失败（{}）：{}", code.as_ref(), error.reason);
+    napi::Error::new(code, error.reason)
+}
+
+// 可选回调同样不能阻止 Node 进程退出
+fn unref_optional<T: JsValuesTupleIntoVec + 'static>(
+    tsfn: Option<ThreadsafeFunction<T>>,
+    env: &Env,
+) -> napi::Result<Option<ThreadsafeFunction<T>>> {
+    match tsfn {
+        Some(mut tsfn) => {
+            #[allow(deprecated)]
+            tsfn.unref(env)?;
+            Ok(Some(tsfn))
+        }
+        None => Ok(None),
+    }
+}
+
+/// 停止全部监控：退出后台监控线程并等待其完成清理（含网络质量探测与 WLAN 监听），
+/// 不依赖 Node 进程退出。之后可再次调用 doInitialize，新回调会替换旧回调。
+#[napi]
+pub fn do_deinitialize() {
+    stop_monitor_thread();
 }
 
 #[napi]
@@ -112,6 +286,662 @@ pub fn enable_net_quality_prob(enable: bool) {
         start_quality_probe();
     } else {
         report_info_log!("停止网络质量探测");
-        stop_quality_probe();
+        // 不在 Node 主线程上等待：仍在探测中的线程已按代数失效，结束后自行退出
+        stop_quality_probe(Duration::ZERO);
+    }
+}
+
+/// 设置网络质量探测目标列表（IPv4 或域名），每个探测周期对所有目标依次采样。
+/// 传入空列表时回退到默认目标，下一个探测周期生效。
+#[napi]
+pub fn set_ping_targets(targets: Vec<String>) {
+    set_ping_target_list(targets);
+}
+
+/// 开启或关闭目标轮询：开启后每个探测周期只采样目标列表中的下一个目标（采样以 target 区分），
+/// 单周期耗时与目标数量无关；关闭时每周期探测全部目标（默认）。下一个探测周期生效。
+#[napi]
+pub fn set_ping_target_round_robin(enable: bool) {
+    PING_TARGETS_ROUND_ROBIN.store(enable, Ordering::SeqCst);
+    report_info_log!("目标轮询模式：{}", enable);
+}
+
+/// 同步查询当前网络连通状态，返回值与 reportNetworkStatus 回调一致：
+/// 1 可访问互联网，2 仅本地网络（疑似认证门户），0 未连接。
+/// 可在 doInitialize 之前调用，监控线程尚未产生事件时返回 0。
+#[napi]
+pub fn get_current_network_status() -> u32 {
+    snapshot::current_network_status()
+}
+
+/// 同步读取最近一次 WiFi 信号状态（不受网络连通性过滤），用于界面冷启动时立即展示。
+/// 尚未查询到信号、WiFi 已断开或 WLAN 监控未运行时返回 undefined。
+#[napi]
+pub fn get_current_wlan_status() -> Option<WlanStatus> {
+    snapshot::current_wlan_status()
+}
+
+/// 运行时更新 WiFi 弱信号阈值，无需重新初始化 WLAN 句柄。
+/// 传 0 表示使用默认值；recover 不大于 drop 时按初始化规则修正为 drop + 5。
+#[napi]
+pub fn update_signal_thresholds(drop: u32, recover: u32) {
+    wlan::update_signal_thresholds(drop, recover);
+}
+
+/// 设置 WiFi 信号变化上报的节流间隔（毫秒，默认 500）：同一接口在间隔内最多上报一次 reportWlanStatus，
+/// 合并为最新质量；弱/强信号切换始终立即上报。0 表示每次信号变化都上报，最大 60000。
+#[napi]
+pub fn set_wlan_status_debounce_ms(ms: u32) -> napi::Result<()> {
+    if ms > MAX_WLAN_STATUS_DEBOUNCE_MS {
+        report_error_log!("无效的 WiFi 信号上报节流间隔：{}ms", ms);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            format!(
+                "wlan status debounce must be at most {}ms, got {}",
+                MAX_WLAN_STATUS_DEBOUNCE_MS, ms
+            ),
+        ));
+    }
+    WLAN_STATUS_DEBOUNCE_MS.store(ms, Ordering::SeqCst);
+    report_info_log!("更新 WiFi 信号上报节流间隔：{}ms", ms);
+    Ok(())
+}
+
+/// 断开并按原配置文件重连当前已连接的 WLAN 接口，用于排查 WiFi 链路卡死，返回重连接口的网卡描述。
+/// 仅在 WLAN 监控已初始化时可用；重连结果通过 reportWlanStatus / reportWlanEvent 回调上报。
+/// 组策略或权限限制导致系统拒绝时返回明确的权限错误。
+#[napi]
+pub fn wlan_reconnect() -> napi::Result<String> {
+    if !MONITOR_STARTED.load(Ordering::SeqCst)
+        || !WLAN_SUBSYSTEM_ENABLED.load(Ordering::SeqCst)
+        || !wlan::is_wlan_monitor_active()
+    {
+        report_error_log!("WLAN 监控未初始化，无法执行重连");
+        return Err(napi::Error::new(
+            Status::GenericFailure,
+            "WLAN monitoring must be initialized before wlanReconnect",
+        ));
+    }
+    match wlan::reconnect_wlan() {
+        Ok(interface_name) => Ok(interface_name),
+        Err(err) if err.code() == HRESULT::from_win32(ERROR_ACCESS_DENIED.0) => {
+            report_error_log!("WLAN 重连被系统拒绝：权限不足");
+            Err(napi::Error::new(
+                Status::GenericFailure,
+                "Permission denied: the system refused to disconnect/reconnect the WLAN interface",
+            ))
+        }
+        Err(err) => {
+            report_error_log!("WLAN 重连失败：{}", err);
+            Err(napi::Error::new(
+                Status::GenericFailure,
+                format!("WLAN reconnect failed: {}", err.message()),
+            ))
+        }
+    }
+}
+
+/// 扫描附近的 WiFi 网络，返回 Promise<WlanNetworkInfo[]>（每个接入点一条，按质量降序），
+/// 包含 SSID、BSSID、信号质量、RSSI、信道、频段与安全配置，用于现场勘测选择更空闲的信道。
+/// 扫描为异步过程，最长等待约 4 秒；超时的接口返回系统缓存的最近扫描结果。仅在 WLAN 监控已初始化时可用。
+#[napi]
+pub fn scan_wlan_networks() -> napi::Result<AsyncTask<ScanWlanNetworksTask>> {
+    if !MONITOR_STARTED.load(Ordering::SeqCst)
+        || !WLAN_SUBSYSTEM_ENABLED.load(Ordering::SeqCst)
+        || !wlan::is_wlan_monitor_active()
+    {
+        report_error_log!("WLAN 监控未初始化，无法扫描 WiFi 网络");
+        return Err(napi::Error::new(
+            Status::GenericFailure,
+            "WLAN monitoring must be initialized before scanWlanNetworks",
+        ));
+    }
+    Ok(AsyncTask::new(ScanWlanNetworksTask))
+}
+
+/// 暂停网络质量探测（例如应用切到后台或处于按流量计费网络），探测线程保持存活。
+/// 重复调用无副作用。
+#[napi]
+pub fn pause_quality_probe() {
+    network_quality::pause_quality_probe();
+}
+
+/// 恢复网络质量探测，并重新建立 TCP 重传统计基线。重复调用无副作用。
+#[napi]
+pub fn resume_quality_probe() {
+    network_quality::resume_quality_probe();
+}
+
+/// 立即对首个探测目标执行一次网络质量探测，返回 Promise<NetworkQualitySample>。
+/// 探测在线程池中执行（耗时取决于回包与超时，最长约数十秒），不会阻塞主线程，也不会触发
+/// reportNetQuality 回调。与周期探测共享 TCP 重传统计基线，本次取走的增量不会在下个周期重复计算。
+/// 需在 doInitialize 之后调用，否则返回错误。
+#[napi]
+pub fn probe_quality_now() -> napi::Result<AsyncTask<ProbeQualityTask>> {
+    if !MONITOR_STARTED.load(Ordering::SeqCst) {
+        report_error_log!("尚未初始化，无法执行按需网络质量探测");
+        return Err(napi::Error::new(
+            Status::GenericFailure,
+            "doInitialize must be called before probeQualityNow",
+        ));
+    }
+    if !QUALITY_SUBSYSTEM_ENABLED.load(Ordering::SeqCst) {
+        report_error_log!("网络质量探测已通过初始化参数关闭，无法执行按需探测");
+        return Err(napi::Error::new(
+            Status::GenericFailure,
+            "Quality probing was disabled by doInitialize options",
+        ));
+    }
+    Ok(AsyncTask::new(ProbeQualityTask))
+}
+
+/// 等待监控线程完成初始化（COM、网络与 WLAN 初始化完成并推送首个网络状态），返回 Promise<ReadyReport>，
+/// 含各子系统初始化结果与当时的网络状态，供 Electron 等应用在展示网络界面前等待就绪。
+/// 可在 doInitialize 之前调用；timeoutMs 默认 30000，超时仍未就绪时 Promise 被拒绝。
+#[napi]
+pub fn wait_for_ready(timeout_ms: Option<u32>) -> AsyncTask<WaitForReadyTask> {
+    let timeout_ms = timeout_ms.unwrap_or(DEFAULT_READY_WAIT_TIMEOUT_MS);
+    AsyncTask::new(WaitForReadyTask {
+        timeout: Duration::from_millis(timeout_ms as u64),
+    })
+}
+
+/// 检测目标主机一组 TCP 端口的可达性（如 53、443、3478），返回 Promise<PortResult[]>，
+/// 顺序与 ports 一致。目标只解析一次，最多 8 个端口并发建连，遵循 setProbeSourceAddress 的源地址；
+/// timeoutMs 为单个端口的握手超时（100-60000），ports 不能包含 0，最多 1024 个。
+/// 不依赖 doInitialize，也不会触发 reportNetQuality 回调；目标解析失败时 Promise 被拒绝。
+#[napi]
+pub fn check_tcp_ports(
+    host: String,
+    ports: Vec<u16>,
+    timeout_ms: u32,
+) -> napi::Result<AsyncTask<CheckTcpPortsTask>> {
+    let host = host.trim().to_string();
+    if host.is_empty() {
+        report_error_log!("TCP 端口检测的目标为空");
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            "Host must not be empty",
+        ));
+    }
+    if ports.contains(&0) || ports.len() > MAX_TCP_PORT_CHECK_PORTS {
+        report_error_log!("TCP 端口检测的端口列表无效：{:?}", ports);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            format!(
+                "Ports must be between 1 and 65535, at most {} ports",
+                MAX_TCP_PORT_CHECK_PORTS
+            ),
+        ));
+    }
+    if !(MIN_PING_TIMEOUT_MS..=MAX_PING_TIMEOUT_MS).contains(&timeout_ms) {
+        report_error_log!("TCP 端口检测超时无效：{}ms", timeout_ms);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            format!(
+                "Timeout must be between {} and {} ms",
+                MIN_PING_TIMEOUT_MS, MAX_PING_TIMEOUT_MS
+            ),
+        ));
+    }
+    Ok(AsyncTask::new(CheckTcpPortsTask {
+        host,
+        ports,
+        timeout: Duration::from_millis(timeout_ms as u64),
+    }))
+}
+
+/// 对目标执行一次 traceroute，返回 Promise<TraceHop[]>：TTL 从 1 递增逐跳发送 ICMP Echo，
+/// 记录每跳应答方的地址与往返时间，到达目标或收到目标不可达等差错时提前结束。
+/// maxHops 为 1-64，timeoutMs 为每跳的等待超时（100-60000）；仅支持 IPv4 目标，遵循 setProbeSourceAddress 的源地址。
+/// 独立于周期探测，无需先调用 doInitialize；目标解析失败时 Promise 被拒绝。
+#[napi]
+pub fn traceroute(
+    target: String,
+    max_hops: u32,
+    timeout_ms: u32,
+) -> napi::Result<AsyncTask<TracerouteTask>> {
+    let target = target.trim().to_string();
+    if target.is_empty() {
+        report_error_log!("traceroute 的目标为空");
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            "Target must not be empty",
+        ));
+    }
+    if !(1..=MAX_TRACEROUTE_HOPS).contains(&max_hops) {
+        report_error_log!("traceroute 的最大跳数无效：{}", max_hops);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            format!("Max hops must be between 1 and {}", MAX_TRACEROUTE_HOPS),
+        ));
+    }
+    if !(MIN_PING_TIMEOUT_MS..=MAX_PING_TIMEOUT_MS).contains(&timeout_ms) {
+        report_error_log!("traceroute 的超时无效：{}", timeout_ms);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            format!(
+                "Timeout must be between {} and {} ms",
+                MIN_PING_TIMEOUT_MS, MAX_PING_TIMEOUT_MS
+            ),
+        ));
+    }
+    Ok(AsyncTask::new(TracerouteTask {
+        target,
+        max_hops,
+        timeout: Duration::from_millis(timeout_ms as u64),
+    }))
+}
+
+/// 环境自检：依次调用 WlanOpenHandle、IcmpCreateFile、CoCreateInstance(NetworkListManager)
+/// 与 GetTcpStatisticsEx 各一次并立即释放，逐项返回成功与否及错误码（HRESULT），
+/// 用于在新的 Windows 版本或受限环境中部署前确认依赖可用。不启动监控线程，无需先调用 doInitialize。
+#[napi]
+pub fn run_diagnostics() -> DiagnosticsReport {
+    diagnostics::run_diagnostics()
+}
+
+/// 静默或恢复上报：静默期间 reportNetworkStatus、reportWlanStatus 与 reportNetQuality 不再回调，
+/// 内部状态照常更新（适用于驱动更新等已知会反复断网的操作）；解除静默时按最新状态各补发一次。
+/// 日志、心跳与其余可选回调不受影响。
+#[napi]
+pub fn set_reporting_muted(muted: bool) {
+    global::set_reporting_muted(muted);
+}
+
+/// 读取回调投递统计（进程加载以来累计）：成功/丢弃的质量采样数、丢弃的日志数、各回调丢弃数，
+/// 以及节流合并的 WiFi 信号通知数，用于排查趋势图出现缺口等数据缺失问题。
+#[napi]
+pub fn get_dispatch_stats() -> DispatchStats {
+    global::dispatch_stats()
+}
+
+/// 读取监控线程运行信息：系统线程 ID（可在调试器中定位）、是否已启动与运行秒数，
+/// 用于确认监控线程是否仍在运行。未运行时 threadId 与 uptimeSecs 为 0。
+/// networkMonitorActive / wlanMonitorActive / qualityProbeActive 为各子系统的实际运行状态
+/// （如服务器系统上 WLAN 初始化失败时为 false），可据此隐藏当前机器不可用的功能。
+#[napi]
+pub fn get_monitor_info() -> MonitorInfo {
+    monitor::monitor_info()
+}
+
+/// 同步读取最近一次网络质量采样（含平滑与评分），用于界面冷启动时立即展示。
+/// 首次探测完成前返回 undefined。
+#[napi]
+pub fn get_last_quality_sample() -> Option<NetworkQualitySample> {
+    snapshot::last_quality_sample()
+}
+
+/// 读取最近的周期探测采样快照（旧到新），容量由 doInitialize 的 options.recentSampleCapacity
+/// 决定（默认 60）。不包含 probeQualityNow 的按需采样；停止探测时清空。
+#[napi]
+pub fn get_recent_quality_samples() -> Vec<NetworkQualitySample> {
+    RECENT_QUALITY_SAMPLES
+        .lock()
+        .unwrap()
+        .iter()
+        .cloned()
+        .collect()
+}
+
+/// 运行时调整网络质量探测间隔（秒），从下一个探测周期开始生效，不会打断当前等待。
+/// 传入 0 会被拒绝，避免探测线程空转占用 CPU。
+#[napi]
+pub fn set_probe_interval_secs(secs: u32) -> napi::Result<()> {
+    if secs == 0 {
+        report_error_log!("探测间隔不能为 0，保持当前设置");
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            "Probe interval must be greater than 0",
+        ));
+    }
+    PROBE_INTERVAL_SECS.store(secs as u64, Ordering::SeqCst);
+    report_info_log!("更新网络质量探测间隔：{}s", secs);
+    Ok(())
+}
+
+/// 设置每个周期探测间隔的随机扰动幅度（±百分比，默认 0 关闭，最大 50），下一个周期生效。
+/// 大量终端探测同一内部目标时开启（如 10 表示间隔在 ±10% 内随机），避免探测时刻同步造成服务端负载尖峰。
+#[napi]
+pub fn set_probe_interval_jitter_percent(percent: u32) -> napi::Result<()> {
+    if percent > MAX_PROBE_INTERVAL_JITTER_PERCENT {
+        report_error_log!("探测间隔扰动幅度无效：{}%，保持当前设置", percent);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            format!(
+                "Probe interval jitter must be between 0 and {} percent",
+                MAX_PROBE_INTERVAL_JITTER_PERCENT
+            ),
+        ));
+    }
+    PROBE_INTERVAL_JITTER_PERCENT.store(percent, Ordering::SeqCst);
+    report_info_log!("更新探测间隔扰动幅度：±{}%", percent);
+    Ok(())
+}
+
+/// 设置每个目标每周期的 Echo 次数（默认 10，范围 1-100），下一次探测生效。
+/// 次数越多指标越稳定，但单个周期耗时越长，最长受 setPingDeadlineMs 限制。
+#[napi]
+pub fn set_ping_count(count: u32) -> napi::Result<()> {
+    if count == 0 || count > MAX_PING_COUNT {
+        report_error_log!("探测次数无效：{}，保持当前设置", count);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            format!("Ping count must be between 1 and {}", MAX_PING_COUNT),
+        ));
+    }
+    PING_COUNT.store(count, Ordering::SeqCst);
+    report_info_log!("更新探测次数：{}", count);
+    Ok(())
+}
+
+/// 设置 IcmpCreateFile 失败后的重试次数（默认 2，最大 10，第 n 次重试前等待 n×50ms），下一次探测生效。
+/// 句柄资源紧张时创建可能短暂失败，重试仍失败时先 ICMP 模式改用 TCP 握手探测，保证本周期仍有采样。
+#[napi]
+pub fn set_icmp_create_retries(retries: u32) -> napi::Result<()> {
+    if retries > MAX_ICMP_CREATE_RETRIES {
+        report_error_log!("IcmpCreateFile 重试次数无效：{}，保持当前设置", retries);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            format!(
+                "ICMP create retries must be between 0 and {}",
+                MAX_ICMP_CREATE_RETRIES
+            ),
+        ));
+    }
+    ICMP_CREATE_RETRIES.store(retries, Ordering::SeqCst);
+    report_info_log!("更新 IcmpCreateFile 重试次数：{}", retries);
+    Ok(())
+}
+
+/// 设置每个目标每周期同时在途的 ICMP Echo 数（默认 1 即串行发送，最大 8），下一次探测生效。
+/// 并发发送更贴近突发流量的丢包特征并大幅缩短周期耗时，但相邻 Echo 不再等间隔，抖动数值会与串行模式不同。
+#[napi]
+pub fn set_icmp_echo_concurrency(concurrency: u32) -> napi::Result<()> {
+    if !(1..=MAX_ICMP_ECHO_CONCURRENCY).contains(&concurrency) {
+        report_error_log!("ICMP Echo 并发数无效：{}，保持当前设置", concurrency);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            format!(
+                "ICMP echo concurrency must be between 1 and {}",
+                MAX_ICMP_ECHO_CONCURRENCY
+            ),
+        ));
+    }
+    ICMP_ECHO_CONCURRENCY.store(concurrency, Ordering::SeqCst);
+    report_info_log!("更新 ICMP Echo 并发数：{}", concurrency);
+    Ok(())
+}
+
+/// 设置单次 Echo 的超时（毫秒，默认 3000，范围 100-60000），ICMP 与 TCP 回退握手共用，下一次探测生效。
+/// 高延迟链路（如卫星）可适当调大以避免误报丢包，局域网可调小以便目标不可达时尽快结束。
+#[napi]
+pub fn set_ping_timeout_ms(timeout_ms: u32) -> napi::Result<()> {
+    if !(MIN_PING_TIMEOUT_MS..=MAX_PING_TIMEOUT_MS).contains(&timeout_ms) {
+        report_error_log!("探测超时无效：{}ms，保持当前设置", timeout_ms);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            format!(
+                "Ping timeout must be between {} and {} ms",
+                MIN_PING_TIMEOUT_MS, MAX_PING_TIMEOUT_MS
+            ),
+        ));
+    }
+    PING_TIMEOUT_MS.store(timeout_ms, Ordering::SeqCst);
+    report_info_log!("更新探测超时：{}ms", timeout_ms);
+    Ok(())
+}
+
+/// 设置单次测量（ICMP 或 TCP 回退）的总耗时上限（毫秒，默认 10000），超过后放弃剩余 Echo，
+/// 采样的 echoCount 为实际发出的次数。传入 0 时恢复默认。
+#[napi]
+pub fn set_ping_deadline_ms(deadline_ms: u32) {
+    let deadline_ms = if deadline_ms == 0 {
+        DEFAULT_PING_DEADLINE_MS
+    } else {
+        deadline_ms
+    };
+    PING_DEADLINE_MS.store(deadline_ms, Ordering::SeqCst);
+    report_info_log!("更新探测测量时限：{}ms", deadline_ms);
+}
+
+/// 设置 ICMP 探测的载荷大小（字节，默认 32），下一次探测生效。
+/// 例如设置为 1472 可构造 1500 字节的 IP 报文以排查路径 MTU 问题；超过 65507 会被拒绝。
+#[napi]
+pub fn set_ping_payload_size(size: u32) -> napi::Result<()> {
+    if !network_quality::is_valid_ping_payload_size(size) {
+        report_error_log!("探测载荷大小无效：{}，保持当前设置", size);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            format!(
+                "Ping payload size must not exceed {}",
+                MAX_PING_PAYLOAD_SIZE
+            ),
+        ));
+    }
+    PING_PAYLOAD_SIZE.store(size, Ordering::SeqCst);
+    report_info_log!("更新探测载荷大小：{} 字节", size);
+    Ok(())
+}
+
+/// 设置 ICMP 探测是否携带 DF（禁止分片）标志，默认关闭，下一次探测生效。
+/// 配合 setPingPayloadSize 发送大包，收到“需要分片”回复时采样的 mtuBlackHole 为 true。
+#[napi]
+pub fn set_ping_dont_fragment(enable: bool) {
+    PING_DONT_FRAGMENT.store(enable, Ordering::SeqCst);
+    report_info_log!("ICMP 探测 DF 标志：{}", enable);
+}
+
+/// 设置 TCP RTT 探测是否保持长连接，默认关闭（每次 Echo 重新握手，测得的是握手 RTT）。
+/// 开启后对 ports 中的端口每个目标只建一次连接，每次 Echo 发送 1 字节无协议数据，按墙钟计时到对端确认为止
+/// （含对端的延迟确认），适用于屏蔽 ICMP 的网络上持续采样。探测字节会进入对端的应用协议，ports 只应包含
+/// 可容忍这类数据的端口（如自建回显服务）；TLS（443）等端口会直接断开连接，不在 ports 中的端口仍按握手测量。
+/// 开启时 ports 不能为空且不能包含 0；连接断开时本周期回退为握手测量。
+#[napi]
+pub fn set_tcp_persistent_rtt(enable: bool, ports: Option<Vec<u16>>) -> napi::Result<()> {
+    let ports = ports.unwrap_or_default();
+    if enable && (ports.is_empty() || ports.contains(&0)) {
+        report_error_log!("TCP 长连接的端口列表无效：{:?}，保持当前设置", ports);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            "Persistent RTT ports must be a non-empty list of ports between 1 and 65535",
+        ));
+    }
+    *TCP_PERSISTENT_RTT_PORTS.lock().unwrap() = if enable { ports.clone() } else { Vec::new() };
+    TCP_PERSISTENT_RTT.store(enable, Ordering::SeqCst);
+    // 端口列表变化后已有连接可能不再允许，统一关闭，下次探测按新设置重新建立
+    close_persistent_tcp_connections();
+    report_info_log!("TCP RTT 探测长连接：{}，端口：{:?}", enable, ports);
+    Ok(())
+}
+
+/// 设置是否开启 ICMP 超时升级，默认关闭，下一次探测生效。开启后每个周期的首个 Echo 超时时以加倍超时
+/// 重发一次再计为丢包，减少突发链路上偶发慢应答造成的误报丢包；只在真正超时时触发，最坏情况下每个目标
+/// 每周期增加 2 倍 setPingTimeoutMs 的耗时（默认 6000ms），且不超过 setPingDeadlineMs 的测量时限。
+#[napi]
+pub fn set_icmp_timeout_escalation(enable: bool) {
+    ICMP_TIMEOUT_ESCALATION.store(enable, Ordering::SeqCst);
+    report_info_log!("ICMP 超时升级：{}", enable);
+}
+
+/// 设置探测模式：0 先 ICMP、全部失败再回退 TCP 握手（默认），1 仅 TCP 握手 RTT（适用于完全屏蔽 ICMP
+/// 的企业网络，避免每周期等满全部 Echo 超时），2 仅 ICMP（不回退）。下一次探测生效，非法值返回错误。
+#[napi]
+pub fn set_probe_mode(mode: u32) -> napi::Result<()> {
+    if network_quality::probe_mode_from_u32(mode).is_none() {
+        report_error_log!("无效的探测模式：{}", mode);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            format!("probe mode must be 0, 1 or 2, got {}", mode),
+        ));
+    }
+    PROBE_MODE.store(mode, Ordering::SeqCst);
+    report_info_log!("更新探测模式：{}", mode);
+    Ok(())
+}
+
+/// 设置 DNS 耗时测量使用的 DNS 服务器（IPv4 或 IPv6），设置后 dnsResolveMs 为直接向该服务器
+/// 查询 A/AAAA 记录的耗时，不经过系统解析器与缓存，可用于对比企业 DNS 与公共 DNS。
+/// 传入 undefined 或空字符串时恢复使用系统解析器。
+#[napi]
+pub fn set_dns_server(server: Option<String>) -> napi::Result<()> {
+    let server = server.unwrap_or_default();
+    let server = server.trim();
+    if server.is_empty() {
+        DNS_SERVER_OVERRIDE.lock().unwrap().take();
+        clear_resolved_targets();
+        report_info_log!("DNS 耗时测量恢复使用系统解析器");
+        return Ok(());
+    }
+
+    let Ok(address) = server.parse::<IpAddr>() else {
+        report_error_log!("无效的 DNS 服务器地址：{}", server);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            format!("dns server must be an IPv4 or IPv6 address, got {}", server),
+        ));
+    };
+    DNS_SERVER_OVERRIDE.lock().unwrap().replace(address);
+    clear_resolved_targets();
+    report_info_log!("DNS 耗时测量使用服务器：{}", address);
+    Ok(())
+}
+
+/// 设置域名探测目标解析结果的缓存时长（秒，默认 300，最大 86400），0 表示每个周期都重新解析。
+/// 缓存期间不发起 DNS 查询，采样的 dnsCached 为 true 且 dnsResolveMs 为 0；目标探测全部失败时
+/// 立即丢弃其缓存，下个周期重新解析以适应 CDN 地址变化。修改后清空现有缓存。
+#[napi]
+pub fn set_dns_cache_ttl_secs(ttl_secs: u32) -> napi::Result<()> {
+    if ttl_secs > MAX_DNS_CACHE_TTL_SECS {
+        report_error_log!("DNS 缓存时长无效：{}s，保持当前设置", ttl_secs);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            format!(
+                "DNS cache TTL must be at most {} seconds",
+                MAX_DNS_CACHE_TTL_SECS
+            ),
+        ));
+    }
+    DNS_CACHE_TTL_SECS.store(ttl_secs, Ordering::SeqCst);
+    clear_resolved_targets();
+    report_info_log!("更新 DNS 缓存时长：{}s", ttl_secs);
+    Ok(())
+}
+
+/// 设置探测使用的源 IPv4 地址，ICMP 与 TCP 回退探测均从该地址所在网卡发出，用于多网卡环境下
+/// 分别测量各出口质量。传入 undefined 或空字符串时恢复按系统默认路由选择；地址需为本机网卡地址。
+#[napi]
+pub fn set_probe_source_address(address: Option<String>) -> napi::Result<()> {
+    let address = address.unwrap_or_default();
+    let address = address.trim();
+    if address.is_empty() {
+        PROBE_SOURCE_ADDRESS.store(0, Ordering::SeqCst);
+        report_info_log!("探测源地址恢复为系统默认路由");
+        return Ok(());
     }
+
+    let Ok(source) = address.parse::<Ipv4Addr>() else {
+        report_error_log!("无效的探测源地址：{}", address);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            format!(
+                "probe source address must be an IPv4 address, got {}",
+                address
+            ),
+        ));
+    };
+    if source.is_unspecified() {
+        PROBE_SOURCE_ADDRESS.store(0, Ordering::SeqCst);
+        report_info_log!("探测源地址恢复为系统默认路由");
+        return Ok(());
+    }
+    PROBE_SOURCE_ADDRESS.store(u32::from(source), Ordering::SeqCst);
+    report_info_log!("更新探测源地址：{}", source);
+    Ok(())
+}
+
+/// 设置是否仅通过 WiFi 网卡探测，默认关闭，下一次探测生效。开启后 ICMP 与 TCP 探测绑定到当前已连接
+/// WLAN 接口所在网卡的 IPv4 地址（优先于 setProbeSourceAddress），延迟/丢包可与同一网卡的信号质量直接关联；
+/// WiFi 未连接或不是可用网卡时按系统默认路由探测并输出提示日志，采样的 wifiBound 为 false。
+#[napi]
+pub fn set_probe_wifi_only(enable: bool) {
+    PROBE_WIFI_ONLY.store(enable, Ordering::SeqCst);
+    report_info_log!("仅 WiFi 探测：{}", enable);
+}
+
+/// 设置 ICMP 全部失败时 TCP 握手 RTT 回退探测的端口列表（默认 [443]），按顺序尝试，
+/// 首个握手成功的端口作为本周期结果。传入空列表时恢复默认；端口需在 1-65535 之间。
+/// 每个失败端口最多耗时 探测次数 × 超时，列表不宜过长。
+#[napi]
+pub fn set_tcp_fallback_ports(ports: Vec<u32>) -> napi::Result<()> {
+    let mut valid_ports = Vec::with_capacity(ports.len());
+    for port in ports {
+        match u16::try_from(port) {
+            Ok(port) if port != 0 => valid_ports.push(port),
+            _ => {
+                report_error_log!("TCP 回退端口无效：{}，保持当前设置", port);
+                return Err(napi::Error::new(
+                    Status::InvalidArg,
+                    format!("Invalid TCP fallback port: {}", port),
+                ));
+            }
+        }
+    }
+    report_info_log!("更新 TCP 回退端口：{:?}", valid_ports);
+    network_quality::set_tcp_fallback_port_list(valid_ports);
+    Ok(())
+}
+
+/// 设置网络质量劣化阈值（滞回）：抖动 ≥ jitterEnterMs 或丢包 ≥ lossEnterPercent 时上报劣化事件，
+/// 抖动 ≤ jitterExitMs 且丢包 ≤ lossExitPercent 时上报恢复事件。
+/// 传 0 表示使用默认值（100ms/50ms、10%/5%）；退出阈值不小于进入阈值时修正为进入阈值的一半。
+#[napi]
+pub fn set_quality_degradation_thresholds(
+    jitter_enter_ms: u32,
+    jitter_exit_ms: u32,
+    loss_enter_percent: u32,
+    loss_exit_percent: u32,
+) {
+    DEGRADED_JITTER_ENTER_MS.store(jitter_enter_ms, Ordering::SeqCst);
+    DEGRADED_JITTER_EXIT_MS.store(jitter_exit_ms, Ordering::SeqCst);
+    DEGRADED_LOSS_ENTER_PERCENT.store(loss_enter_percent, Ordering::SeqCst);
+    DEGRADED_LOSS_EXIT_PERCENT.store(loss_exit_percent, Ordering::SeqCst);
+    report_info_log!(
+        "更新质量劣化阈值：jitter={}/{}ms，丢包={}/{}%",
+        jitter_enter_ms,
+        jitter_exit_ms,
+        loss_enter_percent,
+        loss_exit_percent
+    );
+}
+
+/// 设置质量采样的指数移动平均系数 alpha，作用于 latencyAvgMs、jitterMs 与 packetLossPercent：
+/// 0 关闭平滑（默认），(0, 1] 越小曲线越平滑。原始值始终保留在对应的 *Raw 字段中。
+/// 平滑状态在停止探测时清空。
+#[napi]
+pub fn set_quality_smoothing(alpha: f64) -> napi::Result<()> {
+    if !network_quality::is_valid_smoothing_alpha(alpha) {
+        report_error_log!("平滑系数无效：{}，保持当前设置", alpha);
+        return Err(napi::Error::new(
+            Status::InvalidArg,
+            "Smoothing alpha must be between 0 and 1",
+        ));
+    }
+    QUALITY_SMOOTHING_ALPHA.store(alpha.to_bits(), Ordering::SeqCst);
+    report_info_log!("更新质量采样平滑系数：{}", alpha);
+    Ok(())
+}
+
+/// 将结构化日志事件格式化为单行字符串，格式与旧版字符串日志一致：
+/// `[level]:timestamp - file:line:column module - message`
+#[napi]
+pub fn format_log_event(event: LogEvent) -> String {
+    global::format_log_event(&event)
+}
+
+/// 设置日志级别：0 关闭，1 仅错误，2 错误与信息（发布版默认），3 包含调试日志（开发版默认）。
+/// 大于 3 的值按 3 处理。低于级别的日志不会进入线程安全函数队列。
+#[napi]
+pub fn set_log_level(level: u32) {
+    LOG_LEVEL.store(level.min(LOG_LEVEL_DEBUG), Ordering::SeqCst);
 }
@@ -1,61 +1,206 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
 use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
+use std::time::{Duration, Instant};
 
+use napi::{Env, Status, Task};
+use napi_derive::napi;
 use windows::Win32::Foundation::{LPARAM, WPARAM};
 use windows::Win32::System::Com::{COINIT_MULTITHREADED, CoInitializeEx, CoUninitialize};
 use windows::Win32::System::Threading::GetCurrentThreadId;
 use windows::Win32::UI::WindowsAndMessaging::{
-    DispatchMessageW, GetMessageW, MSG, PostThreadMessageW, TranslateMessage, WM_QUIT,
+    DispatchMessageW, GetMessageW, KillTimer, MSG, PM_NOREMOVE, PeekMessageW, PostThreadMessageW,
+    SetTimer, TranslateMessage, WM_QUIT, WM_TIMER, WM_USER,
 };
 
-use crate::global::{MONITOR_STARTED, MONITOR_THREAD_ID, NET_QUALITY_PROB_ENABLED};
+use crate::global::{
+    HEARTBEAT_INTERVAL_MS, Heartbeat, InitResult, LAST_EVENT_AT_MS, LAST_INIT_RESULT,
+    MONITOR_READY_SIGNAL, MONITOR_STARTED, MONITOR_STARTED_AT, MONITOR_THREAD, MONITOR_THREAD_ID,
+    MonitorInfo, NET_QUALITY_PROB_ENABLED, NETWORK_MONITOR_ACTIVE, NETWORK_SINK_CHECK_INTERVAL_MS,
+    NETWORK_STATUS, NETWORK_STATUS_FLUSH_CHECK_MS, QUALITY_RUNNING, QUALITY_SUBSYSTEM_ENABLED,
+    ReadyReport, SubsystemInitStatus, WLAN_STATUS_FLUSH_CHECK_MS, WLAN_SUBSYSTEM_ENABLED,
+    get_current_time, report_heartbeat,
+};
+use crate::messages::WM_NETWORK_STATUS_CHANGE;
 use crate::{network, network_quality, wlan};
 use crate::{report_error_log, report_info_log};
 
 // 启动后台监控线程：负责初始化 COM、网络/WLAN 监听与消息循环
-pub fn start_monitor_thread() {
+// 返回的通道在初始化阶段结束后收到各子系统的结果；线程已在运行时返回 None
+pub fn start_monitor_thread(
+    wlan_enabled: bool,
+    quality_enabled: bool,
+) -> Option<Receiver<InitResult>> {
     let already_started = MONITOR_STARTED.swap(true, Ordering::SeqCst);
     if already_started {
         report_info_log!("后台监控线程已启动，跳过重复创建");
-        return;
+        return None;
     }
+    // 子系统开关只在启动新线程时写入，保证与线程实际初始化的子系统一致
+    WLAN_SUBSYSTEM_ENABLED.store(wlan_enabled, Ordering::SeqCst);
+    QUALITY_SUBSYSTEM_ENABLED.store(quality_enabled, Ordering::SeqCst);
+    // 丢弃上一个线程的初始化结果，waitForReady 等待本次初始化完成
+    LAST_INIT_RESULT.lock().unwrap().take();
 
-    thread::spawn(|| {
-        let thread_id = unsafe { GetCurrentThreadId() };
-        MONITOR_THREAD_ID.store(thread_id, Ordering::SeqCst);
-
-        let com_result = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) };
-        if com_result.is_err() {
-            report_error_log!("初始化 COM 失败: {:?}", com_result);
+    let (init_sender, init_receiver) = mpsc::channel();
+    let spawned = thread::Builder::new()
+        .name("network-monitor".to_string())
+        .spawn(move || {
+            // 捕获线程体内的 panic：否则 MONITOR_STARTED 会一直为 true，之后既无法重新启动也没有线程在运行
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| run_monitor_thread(init_sender)));
+            if let Err(payload) = outcome {
+                report_error_log!("后台监控线程异常退出: {}", panic_message(payload.as_ref()));
+                cleanup_after_panic();
+            }
+            MONITOR_THREAD_ID.store(0, Ordering::SeqCst);
+            MONITOR_STARTED_AT.lock().unwrap().take();
+            MONITOR_STARTED.store(false, Ordering::SeqCst);
+        });
+    let handle = match spawned {
+        Ok(handle) => handle,
+        Err(error) => {
+            report_error_log!("创建后台监控线程失败: {}", error);
+            MONITOR_STARTED.store(false, Ordering::SeqCst);
+            let failed = SubsystemInitStatus::failed("failed to spawn monitor thread");
+            let (failed_sender, failed_receiver) = mpsc::channel();
+            let _ = failed_sender.send(InitResult {
+                com: failed.clone(),
+                network: failed.clone(),
+                wlan: failed,
+                already_initialized: false,
+            });
+            return Some(failed_receiver);
         }
+    };
 
-        if let Err(error) = network::initialize_network_monitor() {
-            report_error_log!("初始化网络监控失败: {}", error);
-        }
+    MONITOR_THREAD
+        .get_or_init(|| Mutex::new(None))
+        .lock()
+        .unwrap()
+        .replace(handle);
+    Some(init_receiver)
+}
+
+// 监控线程主体：初始化 COM、网络与 WLAN 监控，运行消息循环，退出时按初始化结果清理
+fn run_monitor_thread(init_sender: Sender<InitResult>) {
+    let started_at = Instant::now();
+    MONITOR_STARTED_AT.lock().unwrap().replace(started_at);
+    // 先调用一次 PeekMessageW 强制创建线程消息队列，确保公开线程 ID 后 PostThreadMessageW 一定能投递成功
+    let mut msg = MSG::default();
+    let _ = unsafe { PeekMessageW(&mut msg, None, WM_USER, WM_USER, PM_NOREMOVE) };
+    let thread_id = unsafe { GetCurrentThreadId() };
+    MONITOR_THREAD_ID.store(thread_id, Ordering::SeqCst);
+
+    let com_result = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) };
+    if com_result.is_err() {
+        report_error_log!("初始化 COM 失败: {:?}", com_result);
+    }
+
+    let network_result = network::initialize_network_monitor();
+    if let Err(error) = &network_result {
+        report_error_log!("初始化网络监控失败: {}", error);
+    }
 
-        if let Err(error) = wlan::initialize_wlan_monitor() {
+    // 子系统开关在线程启动时读取一次，清理阶段按同一结果决定是否清理
+    let wlan_enabled = WLAN_SUBSYSTEM_ENABLED.load(Ordering::SeqCst);
+    let wlan_status = if wlan_enabled {
+        let wlan_result = wlan::initialize_wlan_monitor();
+        if let Err(error) = &wlan_result {
             report_error_log!("初始化 WLAN 监控失败: {}", error);
         }
+        SubsystemInitStatus::from_result(wlan_result)
+    } else {
+        report_info_log!("WLAN 监控已通过初始化参数关闭，跳过初始化");
+        SubsystemInitStatus::disabled()
+    };
 
-        // 根据初始化与运行时开关决定是否启动网络质量探测
-        if NET_QUALITY_PROB_ENABLED.load(Ordering::SeqCst) {
-            network_quality::start_quality_probe();
-        } else {
-            report_info_log!("网络质量探测默认关闭，等待显式启用");
-        }
+    let init_result = InitResult {
+        com: SubsystemInitStatus::from_result(com_result.ok()),
+        network: SubsystemInitStatus::from_result(network_result),
+        wlan: wlan_status,
+        already_initialized: false,
+    };
+    *LAST_INIT_RESULT.lock().unwrap() = Some(init_result.clone());
+    MONITOR_READY_SIGNAL.notify_all();
+    // 调用方可能已等待超时并丢弃接收端，发送失败无需处理
+    let _ = init_sender.send(init_result);
+
+    network_quality::validate_ping_targets();
+
+    // 根据初始化与运行时开关决定是否启动网络质量探测
+    if !QUALITY_SUBSYSTEM_ENABLED.load(Ordering::SeqCst) {
+        report_info_log!("网络质量探测已通过初始化参数关闭");
+    } else if NET_QUALITY_PROB_ENABLED.load(Ordering::SeqCst) {
+        network_quality::start_quality_probe();
+    } else {
+        report_info_log!("网络质量探测默认关闭，等待显式启用");
+    }
 
-        run_message_loop();
+    run_message_loop(started_at);
 
-        network_quality::stop_quality_probe();
+    network_quality::stop_quality_probe(network_quality::QUALITY_STOP_JOIN_TIMEOUT);
 
+    if wlan_enabled {
         wlan::cleanup_wlan_monitor();
-        network::cleanup_network_monitor();
+    }
+    network::cleanup_network_monitor();
 
-        unsafe { CoUninitialize() };
+    unsafe { CoUninitialize() };
+}
 
-        MONITOR_THREAD_ID.store(0, Ordering::SeqCst);
-        MONITOR_STARTED.store(false, Ordering::SeqCst);
+// 监控线程 panic 后尽力释放已初始化的资源，清理过程本身的 panic 同样吞掉，只记录日志
+fn cleanup_after_panic() {
+    let cleanup = panic::catch_unwind(|| {
+        network_quality::stop_quality_probe(network_quality::QUALITY_STOP_JOIN_TIMEOUT);
+        wlan::cleanup_wlan_monitor();
+        network::cleanup_network_monitor();
     });
+    if cleanup.is_err() {
+        report_error_log!("后台监控线程异常退出后清理资源失败");
+    }
+}
+
+// 提取 panic 负载中的文本信息
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+// 显式停止监控线程：请求退出消息循环并等待线程完成清理，之后可再次启动
+pub fn stop_monitor_thread() {
+    let handle = MONITOR_THREAD
+        .get()
+        .and_then(|lock| lock.lock().unwrap().take());
+    let Some(handle) = handle else {
+        report_info_log!("后台监控线程未启动，无需停止");
+        return;
+    };
+
+    // 线程刚创建时可能尚未公开线程 ID，稍作等待再投递退出消息
+    while MONITOR_THREAD_ID.load(Ordering::SeqCst) == 0 && !handle.is_finished() {
+        thread::sleep(Duration::from_millis(10));
+    }
+    let thread_id = MONITOR_THREAD_ID.load(Ordering::SeqCst);
+    if thread_id != 0
+        && let Err(error) = unsafe { PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) }
+    {
+        report_error_log!("投递监控线程退出消息失败: {}", error);
+    }
+
+    if handle.join().is_err() {
+        report_error_log!("等待后台监控线程退出失败");
+    }
+    MONITOR_THREAD_ID.store(0, Ordering::SeqCst);
+    MONITOR_STARTED.store(false, Ordering::SeqCst);
+    report_info_log!("后台监控线程已停止");
 }
 
 // NAPI 清理钩子：请求监控线程退出消息循环
@@ -68,8 +213,27 @@ pub unsafe extern "C" fn cleanup_monitor_thread(_arg: *mut std::ffi::c_void) {
     let _ = unsafe { PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) };
 }
 
-// 监控线程消息循环：消费后台消息并驱动状态更新
-fn run_message_loop() {
+// 监控线程消息循环：消费后台消息并驱动状态更新，同时按固定间隔发送心跳、校验网络事件订阅、
+// 补发被节流合并的 WiFi 信号状态
+fn run_message_loop(started_at: Instant) {
+    // 无窗口的线程定时器：WM_TIMER 投递到本线程消息队列，wParam 为系统分配的定时器 ID
+    let heartbeat_timer = unsafe { SetTimer(None, 0, HEARTBEAT_INTERVAL_MS, None) };
+    if heartbeat_timer == 0 {
+        report_error_log!("创建心跳定时器失败，监控线程将不发送心跳");
+    }
+    let sink_check_timer = unsafe { SetTimer(None, 0, NETWORK_SINK_CHECK_INTERVAL_MS, None) };
+    if sink_check_timer == 0 {
+        report_error_log!("创建网络事件订阅校验定时器失败，订阅失效后将无法自动恢复");
+    }
+    let wlan_flush_timer = unsafe { SetTimer(None, 0, WLAN_STATUS_FLUSH_CHECK_MS, None) };
+    if wlan_flush_timer == 0 {
+        report_error_log!("创建 WiFi 信号补发定时器失败，节流窗口内的最新信号可能延后上报");
+    }
+    let network_flush_timer = unsafe { SetTimer(None, 0, NETWORK_STATUS_FLUSH_CHECK_MS, None) };
+    if network_flush_timer == 0 {
+        report_error_log!("创建网络状态防抖定时器失败，开启防抖后网络状态将无法上报");
+    }
+
     loop {
         let mut msg = MSG::default();
         let result = unsafe { GetMessageW(&mut msg, None, 0, 0) };
@@ -83,9 +247,134 @@ fn run_message_loop() {
             break;
         }
 
+        if msg.message == WM_NETWORK_STATUS_CHANGE {
+            network::handle_network_status_message(msg.wParam.0 as u32);
+            continue;
+        }
+
+        if msg.message == WM_TIMER && heartbeat_timer != 0 && msg.wParam.0 == heartbeat_timer {
+            send_heartbeat(started_at);
+            continue;
+        }
+
+        if msg.message == WM_TIMER && sink_check_timer != 0 && msg.wParam.0 == sink_check_timer {
+            network::revalidate_network_monitor();
+            continue;
+        }
+
+        if msg.message == WM_TIMER && wlan_flush_timer != 0 && msg.wParam.0 == wlan_flush_timer {
+            wlan::flush_pending_wlan_status();
+            continue;
+        }
+
+        if msg.message == WM_TIMER
+            && network_flush_timer != 0
+            && msg.wParam.0 == network_flush_timer
+        {
+            network::flush_pending_network_status();
+            continue;
+        }
+
         unsafe {
             let _ = TranslateMessage(&msg);
             DispatchMessageW(&msg);
         }
     }
+
+    for timer in [
+        heartbeat_timer,
+        sink_check_timer,
+        wlan_flush_timer,
+        network_flush_timer,
+    ] {
+        if timer != 0 {
+            let _ = unsafe { KillTimer(None, timer) };
+        }
+    }
+}
+
+// 上报一次监控线程心跳：附带运行时长与最近一次系统事件时间
+fn send_heartbeat(started_at: Instant) {
+    report_heartbeat(Heartbeat {
+        uptime_secs: started_at.elapsed().as_secs() as u32,
+        last_event_at_ms: LAST_EVENT_AT_MS.load(Ordering::SeqCst),
+        timestamp: get_current_time(),
+    });
+}
+
+// 读取监控线程运行信息：线程 ID、是否已启动、运行时长与各子系统的实际运行状态
+pub fn monitor_info() -> MonitorInfo {
+    let uptime_secs = MONITOR_STARTED_AT
+        .lock()
+        .unwrap()
+        .map(|started_at| started_at.elapsed().as_secs() as u32)
+        .unwrap_or(0);
+    MonitorInfo {
+        thread_id: MONITOR_THREAD_ID.load(Ordering::SeqCst),
+        started: MONITOR_STARTED.load(Ordering::SeqCst),
+        uptime_secs,
+        network_monitor_active: NETWORK_MONITOR_ACTIVE.load(Ordering::SeqCst),
+        wlan_monitor_active: wlan::is_wlan_monitor_active(),
+        quality_probe_active: QUALITY_RUNNING.load(Ordering::SeqCst),
+    }
+}
+
+// 等待监控线程完成初始化（网络监控已注册并推送首个状态），超时或线程未运行时返回 None
+fn wait_for_monitor_ready(timeout: Duration) -> Option<ReadyReport> {
+    let init_result = LAST_INIT_RESULT.lock().unwrap();
+    let (init_result, _) = MONITOR_READY_SIGNAL
+        .wait_timeout_while(init_result, timeout, |result| {
+            result.is_none() || !MONITOR_STARTED.load(Ordering::SeqCst)
+        })
+        .unwrap();
+    let init_result = init_result
+        .clone()
+        .filter(|_| MONITOR_STARTED.load(Ordering::SeqCst))?;
+    Some(ReadyReport {
+        init_result,
+        network_status: NETWORK_STATUS.load(Ordering::SeqCst),
+    })
+}
+
+// waitForReady 任务：在 libuv 线程池中等待，避免阻塞 Node 主线程
+pub struct WaitForReadyTask {
+    pub timeout: Duration,
+}
+
+#[napi]
+impl Task for WaitForReadyTask {
+    type Output = ReadyReport;
+    type JsValue = ReadyReport;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        wait_for_monitor_ready(self.timeout).ok_or_else(|| {
+            napi::Error::new(
+                Status::GenericFailure,
+                format!(
+                    "Monitor did not become ready within {} ms",
+                    self.timeout.as_millis()
+                ),
+            )
+        })
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::panic_message;
+    use std::any::Any;
+
+    #[test]
+    fn panic_payload_text_is_extracted() {
+        let static_str: Box<dyn Any + Send> = Box::new("COM init failed");
+        assert_eq!(panic_message(static_str.as_ref()), "COM init failed");
+        let owned: Box<dyn Any + Send> = Box::new(format!("code {}", 5));
+        assert_eq!(panic_message(owned.as_ref()), "code 5");
+        let other: Box<dyn Any + Send> = Box::new(42u32);
+        assert_eq!(panic_message(other.as_ref()), "unknown panic");
+    }
 }
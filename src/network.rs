@@ -1,53 +1,136 @@
 use windows::Win32::Networking::NetworkListManager::{
-    INetworkListManager, INetworkListManagerEvents, INetworkListManagerEvents_Impl,
-    NLM_CONNECTIVITY, NLM_CONNECTIVITY_IPV4_INTERNET, NLM_CONNECTIVITY_IPV6_INTERNET,
-    NetworkListManager,
+    INetworkCostManager, INetworkListManager, INetworkListManagerEvents,
+    INetworkListManagerEvents_Impl, NLM_CONNECTION_COST_FIXED, NLM_CONNECTION_COST_VARIABLE,
+    NLM_CONNECTIVITY, NLM_CONNECTIVITY_DISCONNECTED, NLM_CONNECTIVITY_IPV4_INTERNET,
+    NLM_CONNECTIVITY_IPV4_LOCALNETWORK, NLM_CONNECTIVITY_IPV6_INTERNET,
+    NLM_CONNECTIVITY_IPV6_LOCALNETWORK, NetworkListManager,
+};
+use windows::Win32::System::Com::{
+    CLSCTX_ALL, CONNECTDATA, CoCreateInstance, IConnectionPointContainer,
 };
-use windows::Win32::System::Com::{CLSCTX_ALL, CoCreateInstance, IConnectionPointContainer};
 use windows::core::{Interface, Result as WinResult, implement};
 
-use crate::global::{NETWORK_CONNECTED, NetworkStatus, report_network_status, with_monitor_state};
+use crate::global::{
+    CONNECTED_CONNECTIVITY_FLAGS, CONNECTION_TYPE, LAST_NETWORK_STATUS, NETWORK_CONNECTED,
+    NETWORK_MONITOR_ACTIVE, NETWORK_STATUS, NETWORK_STATUS_CONNECTED, NETWORK_STATUS_DEBOUNCE_MS,
+    NETWORK_STATUS_DISCONNECTED, NETWORK_STATUS_LOCAL_ONLY, NetworkStatus, PENDING_NETWORK_STATUS,
+    RAW_CONNECTIVITY, VPN_ACTIVE, mark_event_received, report_network_status, with_monitor_state,
+};
+use crate::messages::send_network_status_message;
+use crate::{adapters, network_quality};
 use crate::{report_error_log, report_info_log};
-use std::sync::atomic::Ordering;
+use std::mem::ManuallyDrop;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+// 费用查询失败只记录一次日志，避免每次连通性变化都重复输出
+static COST_QUERY_WARNED: AtomicBool = AtomicBool::new(false);
 
-// NetworkListManager 事件接收器：将系统连通性变化转发到消息队列
+// NetworkListManager 事件接收器：将系统连通性变化转发到监控线程消息队列，
+// 状态比较、防抖与上报统一在监控线程中处理
 #[implement(INetworkListManagerEvents)]
 struct NetworkListManagerEvents;
 
 impl INetworkListManagerEvents_Impl for NetworkListManagerEvents_Impl {
     fn ConnectivityChanged(&self, new_connectivity: NLM_CONNECTIVITY) -> WinResult<()> {
-        log_connectivity(new_connectivity);
-        let status = connectivity_to_status(new_connectivity);
-        let is_connected = status != 0;
-
-        let was_connected = NETWORK_CONNECTED.swap(is_connected, Ordering::SeqCst);
-        report_info_log!(
-            "当前网络状态：{}, 之前状态：{}",
-            is_connected,
-            was_connected
-        );
-        if was_connected != is_connected {
-            report_network_status(NetworkStatus { status });
-        }
+        mark_event_received();
+        send_network_status_message(new_connectivity.0 as u32);
         Ok(())
     }
 }
 
+// 处理监控线程收到的连通性变化消息，connectivity 为 NLM_CONNECTIVITY 原始位掩码
+pub fn handle_network_status_message(connectivity: u32) {
+    let new_connectivity = NLM_CONNECTIVITY(connectivity as i32);
+    log_connectivity(new_connectivity);
+    let status = connectivity_to_status(new_connectivity);
+    let is_connected = status == NETWORK_STATUS_CONNECTED;
+
+    NETWORK_CONNECTED.store(is_connected, Ordering::SeqCst);
+    let previous_status = NETWORK_STATUS.swap(status, Ordering::SeqCst);
+    // 仅 IPv4 与双栈同为“已连接”，需比较原始位掩码才能识别子标志变化
+    let previous_connectivity = RAW_CONNECTIVITY.swap(connectivity, Ordering::SeqCst);
+    // VPN 连接/断开时连通状态可能不变，需单独识别
+    let vpn_active = adapters::is_vpn_active();
+    let previous_vpn_active = VPN_ACTIVE.swap(vpn_active, Ordering::SeqCst);
+    // WiFi 与有线之间切换时连通状态同样可能不变
+    let connection_type = adapters::primary_connection_type();
+    let previous_connection_type =
+        std::mem::replace(&mut *CONNECTION_TYPE.lock().unwrap(), connection_type);
+    report_info_log!(
+        "当前网络状态：{}, 之前状态：{}, VPN：{}, 连接类型：{}",
+        status,
+        previous_status,
+        vpn_active,
+        connection_type
+    );
+    if previous_status != status
+        || previous_connectivity != connectivity
+        || previous_vpn_active != vpn_active
+        || previous_connection_type != connection_type
+    {
+        let cost_manager = with_monitor_state(|state| state.cost_manager.clone());
+        let is_metered = query_is_metered(cost_manager.as_ref());
+        let network_status =
+            build_network_status(new_connectivity, is_metered, vpn_active, connection_type);
+        // 开启防抖时只记录最新状态，稳定后由定时器上报；NETWORK_CONNECTED 等内部状态仍实时更新
+        if NETWORK_STATUS_DEBOUNCE_MS.load(Ordering::SeqCst) == 0 {
+            report_network_status(network_status);
+        } else {
+            PENDING_NETWORK_STATUS
+                .lock()
+                .unwrap()
+                .replace((network_status, Instant::now()));
+        }
+    }
+    if previous_status != status {
+        // 状态变化后立即采样一次，使恢复/断网尽快反映到质量指标上
+        network_quality::request_immediate_sample(is_connected);
+    }
+}
+
 // 初始化网络连通性监控：注册 COM 事件并推送一次当前状态
 pub fn initialize_network_monitor() -> WinResult<()> {
+    register_network_monitor(None)
+}
+
+// 注册 COM 事件；重新注册时传入之前的状态，状态在订阅失效期间发生变化则补报一次
+fn register_network_monitor(previous_status: Option<u32>) -> WinResult<()> {
     let network_list_manager: INetworkListManager =
         unsafe { CoCreateInstance(&NetworkListManager, None, CLSCTX_ALL)? };
     let connection_point_container: IConnectionPointContainer = network_list_manager.cast()?;
     let connection_point =
         unsafe { connection_point_container.FindConnectionPoint(&INetworkListManagerEvents::IID)? };
 
+    let cost_manager = match network_list_manager.cast::<INetworkCostManager>() {
+        Ok(cost_manager) => Some(cost_manager),
+        Err(error) => {
+            report_error_log!("获取网络费用管理器失败，按不计费处理: {}", error);
+            None
+        }
+    };
+    let is_metered = query_is_metered(cost_manager.as_ref());
+
     let event_sink: INetworkListManagerEvents = NetworkListManagerEvents.into();
     let cookie = unsafe { connection_point.Advise(&event_sink)? };
 
-    let connectivity = unsafe { network_list_manager.GetConnectivity() };
-    let status = connectivity.map(connectivity_to_status).unwrap_or(0);
-    report_info_log!("初始化网络监控，当前状态：{}", status);
-    NETWORK_CONNECTED.store(status != 0, Ordering::SeqCst);
+    let connectivity =
+        unsafe { network_list_manager.GetConnectivity() }.unwrap_or(NLM_CONNECTIVITY_DISCONNECTED);
+    let status = connectivity_to_status(connectivity);
+    let vpn_active = adapters::is_vpn_active();
+    let connection_type = adapters::primary_connection_type();
+    report_info_log!(
+        "初始化网络监控，当前状态：{}，按流量计费：{}，VPN：{}，连接类型：{}",
+        status,
+        is_metered,
+        vpn_active,
+        connection_type
+    );
+    VPN_ACTIVE.store(vpn_active, Ordering::SeqCst);
+    *CONNECTION_TYPE.lock().unwrap() = connection_type;
+    NETWORK_CONNECTED.store(status == NETWORK_STATUS_CONNECTED, Ordering::SeqCst);
+    NETWORK_STATUS.store(status, Ordering::SeqCst);
+    RAW_CONNECTIVITY.store(connectivity.0 as u32, Ordering::SeqCst);
 
     with_monitor_state(|state| {
         state.network_list_manager = Some(network_list_manager);
@@ -55,18 +138,117 @@ pub fn initialize_network_monitor() -> WinResult<()> {
         state.connection_point = Some(connection_point);
         state.event_sink = Some(event_sink);
         state.cookie = cookie;
+        state.cost_manager = cost_manager;
     });
+    NETWORK_MONITOR_ACTIVE.store(true, Ordering::SeqCst);
 
-    if status == 0 {
-        report_network_status(NetworkStatus { status });
+    if status != NETWORK_STATUS_CONNECTED || previous_status.is_some_and(|p| p != status) {
+        report_network_status(build_network_status(
+            connectivity,
+            is_metered,
+            vpn_active,
+            connection_type,
+        ));
     }
 
     Ok(())
 }
 
+// 校验网络事件订阅是否仍然有效，失效时重新注册：NLM 服务重启后连接点会丢弃我们的订阅，
+// ConnectivityChanged 不再触发；首次初始化失败时同样在此重试
+pub fn revalidate_network_monitor() {
+    if is_network_sink_advised() {
+        return;
+    }
+
+    report_info_log!("网络事件订阅已失效，尝试重新注册");
+    let previous_status = NETWORK_STATUS.load(Ordering::SeqCst);
+    release_network_monitor_state();
+    match register_network_monitor(Some(previous_status)) {
+        Ok(()) => report_info_log!("重新注册网络事件成功"),
+        Err(error) => report_error_log!("重新注册网络事件失败，将在下次校验时重试: {}", error),
+    }
+}
+
+// 枚举连接点上的订阅，确认我们的 cookie 仍在其中；连接点不可用（如 RPC 断开）时视为失效
+fn is_network_sink_advised() -> bool {
+    let subscription = with_monitor_state(|state| {
+        state
+            .connection_point
+            .clone()
+            .map(|connection_point| (connection_point, state.cookie))
+    });
+    let Some((connection_point, cookie)) = subscription else {
+        return false;
+    };
+    let connections = match unsafe { connection_point.EnumConnections() } {
+        Ok(connections) => connections,
+        Err(error) => {
+            report_error_log!("枚举网络事件订阅失败: {}", error);
+            return false;
+        }
+    };
+
+    loop {
+        let mut data = [CONNECTDATA::default()];
+        let mut fetched = 0u32;
+        let result = unsafe { connections.Next(&mut data, &mut fetched) };
+        if result.is_err() || fetched == 0 {
+            return false;
+        }
+        let [mut data] = data;
+        // Next 返回的 pUnk 已 AddRef，需要手动释放
+        unsafe { ManuallyDrop::drop(&mut data.pUnk) };
+        if data.dwCookie == cookie {
+            return true;
+        }
+    }
+}
+
 // 清理网络监控：注销事件并释放 COM 资源
 pub fn cleanup_network_monitor() {
+    PENDING_NETWORK_STATUS.lock().unwrap().take();
     NETWORK_CONNECTED.store(false, Ordering::SeqCst);
+    NETWORK_STATUS.store(NETWORK_STATUS_DISCONNECTED, Ordering::SeqCst);
+    VPN_ACTIVE.store(false, Ordering::SeqCst);
+    *CONNECTION_TYPE.lock().unwrap() = "";
+    release_network_monitor_state();
+}
+
+// 防抖窗口结束后上报最终稳定的网络状态，由监控线程定时调用
+pub fn flush_pending_network_status() {
+    let debounce = Duration::from_millis(NETWORK_STATUS_DEBOUNCE_MS.load(Ordering::SeqCst) as u64);
+    let last_reported = LAST_NETWORK_STATUS.lock().unwrap().clone();
+    let settled = settled_network_status(
+        &mut PENDING_NETWORK_STATUS.lock().unwrap(),
+        last_reported.as_ref(),
+        debounce,
+        Instant::now(),
+    );
+    if let Some(network_status) = settled {
+        report_info_log!("网络状态已稳定，上报：{:?}", network_status);
+        report_network_status(network_status);
+    }
+}
+
+// 待上报状态保持满 debounce 后取出；与上一次上报的状态相同（窗口内抖动后又回到原状态）时丢弃不报
+fn settled_network_status(
+    pending: &mut Option<(NetworkStatus, Instant)>,
+    last_reported: Option<&NetworkStatus>,
+    debounce: Duration,
+    now: Instant,
+) -> Option<NetworkStatus> {
+    let (_, queued_at) = pending.as_ref()?;
+    if now.saturating_duration_since(*queued_at) < debounce {
+        return None;
+    }
+    let (network_status, _) = pending.take()?;
+    (last_reported != Some(&network_status)).then_some(network_status)
+}
+
+// 注销事件订阅并释放 COM 对象，订阅已失效时 Unadvise 可能失败，仅记录日志
+fn release_network_monitor_state() {
+    NETWORK_MONITOR_ACTIVE.store(false, Ordering::SeqCst);
     with_monitor_state(|state| {
         if let Some(connection_point) = &state.connection_point
             && state.cookie != 0
@@ -80,14 +262,70 @@ pub fn cleanup_network_monitor() {
         state.connection_point = None;
         state.event_sink = None;
         state.cookie = 0;
+        state.cost_manager = None;
     });
 }
 
-// 将 Windows 连通性标志映射为业务状态 0/1
+// 将 Windows 连通性标志映射为业务状态，“已连接”所需的标志由 CONNECTED_CONNECTIVITY_FLAGS 配置
 fn connectivity_to_status(connectivity: NLM_CONNECTIVITY) -> u32 {
-    let has_internet = (connectivity.0 & NLM_CONNECTIVITY_IPV4_INTERNET.0) != 0
-        || (connectivity.0 & NLM_CONNECTIVITY_IPV6_INTERNET.0) != 0;
-    if has_internet { 1 } else { 0 }
+    classify_connectivity(
+        connectivity,
+        CONNECTED_CONNECTIVITY_FLAGS.load(Ordering::SeqCst),
+    )
+}
+
+// 命中 connected_flags 任一标志为 1，否则有本地网络为 2，其余为 0
+fn classify_connectivity(connectivity: NLM_CONNECTIVITY, connected_flags: u32) -> u32 {
+    let is_connected = (connectivity.0 as u32 & connected_flags) != 0;
+    let has_local_network = (connectivity.0 & NLM_CONNECTIVITY_IPV4_LOCALNETWORK.0) != 0
+        || (connectivity.0 & NLM_CONNECTIVITY_IPV6_LOCALNETWORK.0) != 0;
+    if is_connected {
+        NETWORK_STATUS_CONNECTED
+    } else if has_local_network {
+        NETWORK_STATUS_LOCAL_ONLY
+    } else {
+        NETWORK_STATUS_DISCONNECTED
+    }
+}
+
+// 组装上报给 Node 的网络状态，IPv4/IPv6 互联网连通性分别上报，便于发现“IPv6 已通告但不可用”
+fn build_network_status(
+    connectivity: NLM_CONNECTIVITY,
+    is_metered: bool,
+    vpn_active: bool,
+    connection_type: &str,
+) -> NetworkStatus {
+    NetworkStatus {
+        status: connectivity_to_status(connectivity),
+        is_metered,
+        ipv4_internet: (connectivity.0 & NLM_CONNECTIVITY_IPV4_INTERNET.0) != 0,
+        ipv6_internet: (connectivity.0 & NLM_CONNECTIVITY_IPV6_INTERNET.0) != 0,
+        raw_connectivity: connectivity.0 as u32,
+        vpn_active,
+        connection_type: connection_type.to_string(),
+    }
+}
+
+// 查询当前连接是否按流量计费（固定流量套餐或按量计费），费用管理器不可用时视为不计费
+fn query_is_metered(cost_manager: Option<&INetworkCostManager>) -> bool {
+    let Some(cost_manager) = cost_manager else {
+        return false;
+    };
+
+    let mut cost = 0u32;
+    match unsafe { cost_manager.GetCost(&mut cost, std::ptr::null()) } {
+        Ok(()) => {
+            let metered_flags =
+                (NLM_CONNECTION_COST_FIXED.0 | NLM_CONNECTION_COST_VARIABLE.0) as u32;
+            cost & metered_flags != 0
+        }
+        Err(error) => {
+            if !COST_QUERY_WARNED.swap(true, Ordering::SeqCst) {
+                report_error_log!("查询网络费用失败，按不计费处理: {}", error);
+            }
+            false
+        }
+    }
 }
 
 // 输出连通性变化的详细日志，便于排查状态切换
@@ -99,3 +337,130 @@ fn log_connectivity(connectivity: NLM_CONNECTIVITY) {
         status
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        build_network_status, classify_connectivity, connectivity_to_status, settled_network_status,
+    };
+    use crate::global::{
+        DEFAULT_CONNECTED_CONNECTIVITY_FLAGS, NETWORK_STATUS_CONNECTED,
+        NETWORK_STATUS_DISCONNECTED, NETWORK_STATUS_LOCAL_ONLY,
+    };
+    use std::time::{Duration, Instant};
+    use windows::Win32::Networking::NetworkListManager::{
+        NLM_CONNECTIVITY, NLM_CONNECTIVITY_DISCONNECTED, NLM_CONNECTIVITY_IPV4_INTERNET,
+        NLM_CONNECTIVITY_IPV4_LOCALNETWORK, NLM_CONNECTIVITY_IPV4_NOTRAFFIC,
+        NLM_CONNECTIVITY_IPV6_INTERNET, NLM_CONNECTIVITY_IPV6_LOCALNETWORK,
+    };
+
+    #[test]
+    fn connectivity_maps_to_status() {
+        assert_eq!(
+            connectivity_to_status(NLM_CONNECTIVITY_IPV4_INTERNET),
+            NETWORK_STATUS_CONNECTED
+        );
+        assert_eq!(
+            connectivity_to_status(NLM_CONNECTIVITY(
+                NLM_CONNECTIVITY_IPV4_LOCALNETWORK.0 | NLM_CONNECTIVITY_IPV6_INTERNET.0
+            )),
+            NETWORK_STATUS_CONNECTED
+        );
+        assert_eq!(
+            connectivity_to_status(NLM_CONNECTIVITY_IPV4_LOCALNETWORK),
+            NETWORK_STATUS_LOCAL_ONLY
+        );
+        assert_eq!(
+            connectivity_to_status(NLM_CONNECTIVITY_IPV4_NOTRAFFIC),
+            NETWORK_STATUS_DISCONNECTED
+        );
+        assert_eq!(
+            connectivity_to_status(NLM_CONNECTIVITY_DISCONNECTED),
+            NETWORK_STATUS_DISCONNECTED
+        );
+    }
+
+    #[test]
+    fn local_network_counts_as_connected_when_configured() {
+        let intranet_flags = DEFAULT_CONNECTED_CONNECTIVITY_FLAGS
+            | (NLM_CONNECTIVITY_IPV4_LOCALNETWORK.0 | NLM_CONNECTIVITY_IPV6_LOCALNETWORK.0) as u32;
+        assert_eq!(
+            classify_connectivity(NLM_CONNECTIVITY_IPV4_LOCALNETWORK, intranet_flags),
+            NETWORK_STATUS_CONNECTED
+        );
+        assert_eq!(
+            classify_connectivity(NLM_CONNECTIVITY_IPV6_INTERNET, intranet_flags),
+            NETWORK_STATUS_CONNECTED
+        );
+        // 只认 IPv4 互联网时，仅 IPv6 互联网按本地网络处理
+        assert_eq!(
+            classify_connectivity(
+                NLM_CONNECTIVITY(
+                    NLM_CONNECTIVITY_IPV6_INTERNET.0 | NLM_CONNECTIVITY_IPV6_LOCALNETWORK.0
+                ),
+                NLM_CONNECTIVITY_IPV4_INTERNET.0 as u32
+            ),
+            NETWORK_STATUS_LOCAL_ONLY
+        );
+        assert_eq!(
+            classify_connectivity(NLM_CONNECTIVITY_IPV4_NOTRAFFIC, intranet_flags),
+            NETWORK_STATUS_DISCONNECTED
+        );
+    }
+
+    #[test]
+    fn internet_flags_are_reported_per_family() {
+        let ipv4_only = build_network_status(
+            NLM_CONNECTIVITY(
+                NLM_CONNECTIVITY_IPV4_INTERNET.0 | NLM_CONNECTIVITY_IPV6_LOCALNETWORK.0,
+            ),
+            false,
+            false,
+            "ethernet",
+        );
+        assert_eq!(ipv4_only.status, NETWORK_STATUS_CONNECTED);
+        assert_eq!(ipv4_only.connection_type, "ethernet");
+        assert!(ipv4_only.ipv4_internet);
+        assert!(!ipv4_only.ipv6_internet);
+
+        let ipv6_only = build_network_status(NLM_CONNECTIVITY_IPV6_INTERNET, false, false, "wifi");
+        assert_eq!(ipv6_only.status, NETWORK_STATUS_CONNECTED);
+        assert!(!ipv6_only.ipv4_internet);
+        assert!(ipv6_only.ipv6_internet);
+
+        let local_only = build_network_status(NLM_CONNECTIVITY_IPV6_LOCALNETWORK, false, false, "");
+        assert_eq!(local_only.status, NETWORK_STATUS_LOCAL_ONLY);
+        assert!(!local_only.ipv4_internet && !local_only.ipv6_internet);
+        assert_eq!(
+            local_only.raw_connectivity,
+            NLM_CONNECTIVITY_IPV6_LOCALNETWORK.0 as u32
+        );
+    }
+
+    #[test]
+    fn flapping_status_is_reported_once_settled() {
+        let debounce = Duration::from_millis(2000);
+        let queued_at = Instant::now();
+        let connected = build_network_status(NLM_CONNECTIVITY_IPV4_INTERNET, false, false, "wifi");
+        let disconnected = build_network_status(NLM_CONNECTIVITY_DISCONNECTED, false, false, "");
+
+        let mut pending = Some((disconnected.clone(), queued_at));
+        let early = queued_at + Duration::from_millis(500);
+        assert!(settled_network_status(&mut pending, Some(&connected), debounce, early).is_none());
+        assert!(pending.is_some());
+
+        let settled = queued_at + debounce;
+        assert_eq!(
+            settled_network_status(&mut pending, Some(&connected), debounce, settled),
+            Some(disconnected)
+        );
+        assert!(pending.is_none());
+
+        // 窗口内抖动后回到已上报的状态：丢弃不报
+        let mut pending = Some((connected.clone(), queued_at));
+        assert!(
+            settled_network_status(&mut pending, Some(&connected), debounce, settled).is_none()
+        );
+        assert!(pending.is_none());
+    }
+}
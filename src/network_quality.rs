@@ -1,33 +1,204 @@
-use std::net::{Ipv4Addr, SocketAddrV4, TcpStream, ToSocketAddrs};
-use std::sync::atomic::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::ffi::c_void;
+use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, TcpStream, ToSocketAddrs};
+use std::os::windows::io::AsRawSocket;
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Mutex, OnceLock};
 use std::thread::{self};
 use std::time::{Duration, Instant};
 
+use napi::{Env, Task};
+use napi_derive::napi;
 use rand::RngExt;
+use socket2::{Domain, Protocol, SockRef, Socket, TcpKeepalive, Type};
 
-use windows::Win32::Foundation::{ERROR_SUCCESS, GetLastError, WIN32_ERROR};
+use windows::Win32::Foundation::{ERROR_SUCCESS, GetLastError, HANDLE, WIN32_ERROR};
 use windows::Win32::NetworkManagement::IpHelper::{
-    GetTcpStatisticsEx, ICMP_ECHO_REPLY, IcmpCloseHandle, IcmpCreateFile, IcmpSendEcho,
-    MIB_TCPSTATS_LH,
+    FreeMibTable, GetBestRoute2, GetIfEntry2, GetIpForwardTable2, GetTcpStatisticsEx,
+    ICMP_ECHO_REPLY, IP_BAD_DESTINATION, IP_BAD_OPTION, IP_BAD_REQ, IP_BAD_ROUTE, IP_BUF_TOO_SMALL,
+    IP_DEST_HOST_UNREACHABLE, IP_DEST_NET_UNREACHABLE, IP_DEST_PORT_UNREACHABLE,
+    IP_DEST_PROT_UNREACHABLE, IP_FLAG_DF, IP_GENERAL_FAILURE, IP_HW_ERROR, IP_NO_RESOURCES,
+    IP_OPTION_INFORMATION, IP_OPTION_TOO_BIG, IP_PACKET_TOO_BIG, IP_PARAM_PROBLEM,
+    IP_REQ_TIMED_OUT, IP_SOURCE_QUENCH, IP_STATUS_BASE, IP_SUCCESS, IP_TTL_EXPIRED_REASSEM,
+    IP_TTL_EXPIRED_TRANSIT, IcmpCloseHandle, IcmpCreateFile, IcmpSendEcho, IcmpSendEcho2Ex,
+    MAX_IP_STATUS, MIB_IF_ROW2, MIB_IPFORWARD_ROW2, MIB_IPFORWARD_TABLE2, MIB_TCPSTATS_LH,
+};
+use windows::Win32::Networking::WinSock::{
+    AF_INET, AF_INET6, SIO_TCP_INFO, SOCKADDR_INET, SOCKET, TCP_INFO_v0, TCPSTATE_ESTABLISHED,
+    WSAIoctl,
 };
 
-use crate::{report_error_log, report_info_log};
+use crate::{adapters, dns};
+use crate::{report_debug_log, report_error_log, report_info_log};
 
 use crate::global::{
-    DEFAULT_PING_COUNT, DEFAULT_PING_TARGET, DEFAULT_PING_TIMEOUT_MS, DEFAULT_PROBE_INTERVAL_SECS,
-    IP_FAMILY_IPV4, NetworkQualitySample, QUALITY_RUNNING, QUALITY_THREAD, report_net_quality,
+    DEFAULT_PING_COUNT, DEFAULT_PING_DEADLINE_MS, DEFAULT_PING_TARGET, DEFAULT_PING_TIMEOUT_MS,
+    DEFAULT_TCP_FALLBACK_PORT, DEGRADED_JITTER_ENTER_MS, DEGRADED_JITTER_EXIT_MS,
+    DEGRADED_LOSS_ENTER_PERCENT, DEGRADED_LOSS_EXIT_PERCENT, DNS_CACHE_TTL_SECS,
+    DNS_RESOLVE_FAILED, DNS_SERVER_OVERRIDE, ICMP_CREATE_RETRIES, ICMP_ECHO_CONCURRENCY,
+    ICMP_TIMEOUT_ESCALATION, IP_FAMILY_IPV4, LAST_PROBE_SUCCESS_AT, LAST_QUALITY_SAMPLE,
+    LAST_WLAN_STATUS, MAX_PING_COUNT, MAX_PING_PAYLOAD_SIZE, MAX_PING_TIMEOUT_MS,
+    MAX_PROBE_INTERVAL_JITTER_PERCENT, MIN_PING_TIMEOUT_MS, NETWORK_CONNECTED,
+    NETWORK_MONITOR_ACTIVE, NetworkQualitySample, PERSISTENT_TCP_CONNECTIONS, PING_COUNT,
+    PING_DEADLINE_MS, PING_DONT_FRAGMENT, PING_PAYLOAD_SIZE, PING_TARGETS,
+    PING_TARGETS_ROUND_ROBIN, PING_TIMEOUT_MS, PROBE_ERROR_ICMP_UNAVAILABLE,
+    PROBE_ERROR_IPV6_UNSUPPORTED, PROBE_ERROR_MEASUREMENT_FAILED, PROBE_ERROR_OFFLINE,
+    PROBE_ERROR_RESOLUTION_FAILED, PROBE_INTERVAL_JITTER_PERCENT, PROBE_INTERVAL_SECS, PROBE_MODE,
+    PROBE_MODE_ICMP_FIRST, PROBE_MODE_ICMP_ONLY, PROBE_MODE_TCP_ONLY, PROBE_SOURCE_ADDRESS,
+    PROBE_WIFI_ONLY, PortResult, QUALITY_GENERATION, QUALITY_GRADE_FAIR,
+    QUALITY_GRADE_FAIR_MIN_SCORE, QUALITY_GRADE_GOOD, QUALITY_GRADE_GOOD_MIN_SCORE,
+    QUALITY_GRADE_POOR, QUALITY_JITTER_BAD_MS, QUALITY_JITTER_GOOD_MS, QUALITY_LATENCY_BAD_MS,
+    QUALITY_LATENCY_GOOD_MS, QUALITY_LOSS_BAD_PERCENT, QUALITY_LOSS_GOOD_PERCENT, QUALITY_PAUSED,
+    QUALITY_PROBE_STARTED_AT, QUALITY_RETRANSMISSION_BAD_PERCENT,
+    QUALITY_RETRANSMISSION_GOOD_PERCENT, QUALITY_RUNNING, QUALITY_SMOOTHING_ALPHA,
+    QUALITY_SUBSYSTEM_ENABLED, QUALITY_THREAD, QUALITY_WAKE, QUALITY_WAKE_PENDING,
+    QUALITY_WEIGHT_JITTER, QUALITY_WEIGHT_LATENCY, QUALITY_WEIGHT_LOSS,
+    QUALITY_WEIGHT_RETRANSMISSION, QualityDegradedEvent, RECENT_QUALITY_CAPACITY,
+    RECENT_QUALITY_SAMPLES, RESOLVED_TARGETS, TCP_FALLBACK_PORTS, TCP_PERSISTENT_RTT,
+    TCP_PERSISTENT_RTT_PORTS, TCP_PORT_CHECK_CONCURRENCY, TraceHop, get_current_unix_millis,
+    report_net_quality, report_quality_degraded,
 };
 
 // TCP 统计结果：用于计算重传率并补充其他质量指标
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct TcpStats {
     retransmission_percent: f64,
     segments_sent: i64,
     segments_retransmitted: i64,
+    // 周期内主动建连失败次数（dwAttemptFails 增量）
+    connection_failures: i64,
+    // 当前处于 ESTABLISHED/CLOSE_WAIT 的连接数（dwCurrEstab 快照）
+    current_established: i64,
+}
+
+// 系统 TCP 计数器原始值
+#[derive(Debug)]
+struct TcpCounters {
+    out_segs: i64,
+    retrans_segs: i64,
+    attempt_fails: i64,
+    curr_estab: i64,
 }
 
 static TCP_STATS_BASELINE: OnceLock<Mutex<Option<(i64, i64)>>> = OnceLock::new();
+static TCP_FAILURES_BASELINE: OnceLock<Mutex<Option<i64>>> = OnceLock::new();
+
+// 载荷头部 Echo 序号的字节数
+const ECHO_SEQUENCE_LEN: usize = 4;
+// ICMP 差错报文的长度，IcmpSendEcho 要求回包缓冲区额外预留
+const ICMP_ERROR_MESSAGE_SIZE: usize = 8;
+// 回包缓冲区预留的应答数：单播目标通常只有一个应答，多个应答意味着重复应答或网络环路
+const ICMP_MAX_REPLIES: usize = 4;
+// 携带 IP 选项时需显式指定 TTL，取 Windows 默认值
+const DEFAULT_PING_TTL: u8 = 128;
+// 监控线程退出时等待探测线程退出的上限：线程可能正阻塞在 TCP 连接或 DNS 解析中，超时后不再等待
+pub const QUALITY_STOP_JOIN_TIMEOUT: Duration = Duration::from_secs(5);
+// IcmpCreateFile 重试的退避基数，第 n 次重试前等待 n 倍
+const ICMP_CREATE_RETRY_BACKOFF: Duration = Duration::from_millis(50);
+// TCP 长连接的保活参数：空闲 15 秒后开始保活，对端无响应时每 5 秒重试
+const PERSISTENT_TCP_KEEPALIVE_TIME: Duration = Duration::from_secs(15);
+const PERSISTENT_TCP_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(5);
+// 等待长连接探测字节被确认时查询 TCP 状态的间隔
+const PERSISTENT_TCP_POLL_INTERVAL: Duration = Duration::from_millis(1);
+// 网关探测使用固定的少量 Echo 与短超时，并有独立的测量时限：不少网关丢弃 ICMP，
+// 沿用目标的探测参数会让每个周期先在网关上耗尽测量时限
+const GATEWAY_PING_COUNT: usize = 2;
+const GATEWAY_PING_TIMEOUT_MS: u32 = 500;
+const GATEWAY_PING_DEADLINE: Duration = Duration::from_millis(1500);
+// 网关连续无应答达到该周期数后退避：之后每 GATEWAY_SILENT_RETRY_CYCLES 个周期才重试一次
+const GATEWAY_SILENT_BACKOFF_CYCLES: u32 = 3;
+const GATEWAY_SILENT_RETRY_CYCLES: u32 = 10;
+
+// 默认网关查询失败只记录一次日志，避免每个周期重复输出
+static GATEWAY_QUERY_WARNED: AtomicBool = AtomicBool::new(false);
+// 当前默认网关及其连续无应答的周期数，网关变化或收到应答时清零
+static GATEWAY_SILENCE: Mutex<Option<(Ipv4Addr, u32)>> = Mutex::new(None);
+// GetTcpStatisticsEx 失败只记录一次日志（精简版系统上可能每个周期都失败），恢复后重新计数
+static TCP_STATS_QUERY_WARNED: AtomicBool = AtomicBool::new(false);
+// 仅 WiFi 探测模式下 WiFi 网卡当前不可用，用于只在状态切换时提示一次
+static WIFI_SOURCE_UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+// 周期级指标：同一周期内所有目标共享
+#[derive(Default)]
+struct CycleMetrics {
+    tcp_stats: Option<TcpStats>,
+    gateway: Option<PingStats>,
+    throughput: Throughput,
+    interface_errors: InterfaceErrors,
+}
+
+// IPv4 默认路由：下一跳即默认网关，所在接口视为活动接口
+struct DefaultRoute {
+    interface_index: u32,
+    gateway: Ipv4Addr,
+}
+
+// 到探测目标的最佳路由：出口接口与下一跳（直连目标时下一跳为未指定地址）
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ProbeRoute {
+    interface_index: u32,
+    next_hop: IpAddr,
+}
+
+// 活动接口的收发速率（字节/秒）
+#[derive(Debug, Default, PartialEq)]
+struct Throughput {
+    rx_bytes_per_sec: f64,
+    tx_bytes_per_sec: f64,
+}
+
+// 活动接口在本周期内新增的差错/丢弃包数
+#[derive(Debug, Default, PartialEq)]
+struct InterfaceErrors {
+    rx_errors: u64,
+    tx_errors: u64,
+    rx_discards: u64,
+    tx_discards: u64,
+}
+
+// 接口累计收发字节数与差错/丢弃包计数的快照
+#[derive(Debug)]
+struct InterfaceOctets {
+    interface_index: u32,
+    in_octets: u64,
+    out_octets: u64,
+    in_errors: u64,
+    out_errors: u64,
+    in_discards: u64,
+    out_discards: u64,
+    read_at: Instant,
+}
+
+static THROUGHPUT_BASELINE: OnceLock<Mutex<Option<InterfaceOctets>>> = OnceLock::new();
+
+// 单个目标的指数移动平均状态，尚无有效样本时为 None
+#[derive(Debug, Default)]
+struct QualityEma {
+    latency_avg_ms: Option<f64>,
+    jitter_ms: Option<f64>,
+    packet_loss_percent: Option<f64>,
+}
+
+// 质量劣化默认阈值：抖动达到 100ms 或丢包达到 10% 进入劣化，抖动不超过 50ms 且丢包不超过 5% 时恢复
+const DEFAULT_DEGRADED_JITTER_ENTER_MS: u32 = 100;
+const DEFAULT_DEGRADED_JITTER_EXIT_MS: u32 = 50;
+const DEFAULT_DEGRADED_LOSS_ENTER_PERCENT: u32 = 10;
+const DEFAULT_DEGRADED_LOSS_EXIT_PERCENT: u32 = 5;
+
+// 暂停期间轮询恢复信号的间隔
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+// 断网退避的探测间隔上限
+const MAX_OFFLINE_PROBE_INTERVAL: Duration = Duration::from_secs(60);
+
+// 探测模式，对应 PROBE_MODE 的取值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProbeMode {
+    IcmpFirst,
+    TcpOnly,
+    IcmpOnly,
+}
 
 // ICMP 探测结果：用于计算延迟、抖动与丢包
 #[derive(Debug)]
@@ -35,207 +206,1939 @@ struct PingStats {
     avg_ms: u32,
     min_ms: u32,
     max_ms: u32,
+    p50_ms: u32,
+    p95_ms: u32,
+    p99_ms: u32,
     jitter_ms: u32,
+    jitter_stddev_ms: u32,
+    jitter_rfc3550_ms: f64,
     loss_percent: f64,
+    icmp_error_percent: f64,
     success_count: usize,
+    // 实际发出的 Echo（或 TCP 握手）次数
+    echo_count: usize,
     last_error: u32,
     last_reply_status: Option<u32>,
+    // 设置 DF 标志后收到 IP_PACKET_TOO_BIG：说明路径 MTU 小于当前报文
+    packet_too_big: bool,
+    // 最后一个 Echo 的 IP_STATUS（应答状态或 GetLastError），TCP 握手探测时为 None
+    last_status: Option<u32>,
+    // 回显序号与发送序号不符的应答数，加上单次 Echo 收到的多余应答数
+    out_of_order_count: usize,
+    // ICMP 全部失败、本结果来自 TCP 握手回退（疑似网络过滤 ICMP）
+    icmp_blocked: bool,
+    // 仅 ICMP 模式下 ICMP 句柄重试后仍创建失败，本结果按全部丢包处理
+    icmp_unavailable: bool,
 }
 
 // 启动网络质量探测线程：周期性采样并输出到日志
 pub fn start_quality_probe() {
+    if !QUALITY_SUBSYSTEM_ENABLED.load(Ordering::SeqCst) {
+        report_info_log!("网络质量探测已通过初始化参数关闭，忽略启动请求");
+        return;
+    }
     let already_running = QUALITY_RUNNING.swap(true, Ordering::SeqCst);
     if already_running {
         report_info_log!("网络质量探测线程已启动，跳过重复创建");
         return;
     }
 
-    let handle = thread::spawn(|| {
-        let mut rng = rand::rng();
-        // 生成 [1.0, 1.5] 的随机倍率
-        let jitter_factor = rng.random_range(1.0..=1.5);
-        let base_secs = DEFAULT_PROBE_INTERVAL_SECS as f64;
-        let jitter_secs = jitter_factor * base_secs;
-        // 将随机扰动后的秒数转换为稳定的探测间隔
-        let interval = Duration::from_secs(jitter_secs as u64);
-        init_tcp_stats_baseline();
-        while QUALITY_RUNNING.load(Ordering::SeqCst) {
-            let start_at = Instant::now();
-            if let Some(sample) = probe_quality_once() {
-                report_quality_sample(&sample);
-                report_net_quality(sample);
-            }
+    let generation = QUALITY_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    // 丢弃探测未运行期间积累的立即采样请求
+    *QUALITY_WAKE_PENDING.lock().unwrap() = false;
+    QUALITY_PROBE_STARTED_AT
+        .lock()
+        .unwrap()
+        .replace(Instant::now());
+
+    let handle = thread::spawn(move || {
+        let mut rng = rand::rng();
+        // 平滑状态按目标保存，随探测线程退出而释放，重新启动探测时从原始值重新开始
+        let mut smoothing: HashMap<String, QualityEma> = HashMap::new();
+        // 各目标当前是否处于劣化状态
+        let mut degraded: HashMap<String, bool> = HashMap::new();
+        // 各目标上一周期的最佳路由，用于识别默认路由切换
+        let mut routes: HashMap<String, ProbeRoute> = HashMap::new();
+        init_tcp_stats_baseline();
+        init_throughput_baseline();
+        // 连续处于断网状态的周期数，用于计算退避间隔
+        let mut offline_cycles = 0u32;
+        // 轮询模式下下一个要探测的目标序号
+        let mut round_robin_cursor = 0usize;
+        while is_current_probe(generation) {
+            if QUALITY_PAUSED.load(Ordering::SeqCst) {
+                thread::sleep(PAUSE_POLL_INTERVAL);
+                continue;
+            }
+
+            // 探测间隔按整个周期计算，而不是按单个目标，避免慢目标挤占其他目标的时间
+            let start_at = Instant::now();
+            // 断网（含仅本地连接、强制门户等无 Internet 的状态）期间不发起 DNS、ICMP 与网关探测，
+            // 直接上报全丢包采样；退避间隔到期或被唤醒时都按当前连通状态判断
+            let offline = is_offline_cycle(
+                NETWORK_MONITOR_ACTIVE.load(Ordering::SeqCst),
+                NETWORK_CONNECTED.load(Ordering::SeqCst),
+            );
+            // TCP 统计是系统级指标，每个周期只取一次并共享给所有目标
+            let cycle = if offline {
+                CycleMetrics::default()
+            } else {
+                collect_cycle_metrics()
+            };
+            let targets = select_cycle_targets(
+                current_ping_targets(),
+                PING_TARGETS_ROUND_ROBIN.load(Ordering::SeqCst),
+                &mut round_robin_cursor,
+            );
+            for target in targets {
+                if !is_current_probe(generation) {
+                    break;
+                }
+                let mut sample = if offline {
+                    build_offline_sample(&target)
+                } else {
+                    let mut route = routes.get(&target).copied();
+                    let sample = probe_quality_once(&target, &cycle, &mut route);
+                    if let Some(route) = route {
+                        routes.insert(target.clone(), route);
+                    }
+                    sample
+                };
+                // 探测期间已停止（或已被新线程取代）时丢弃本次结果，避免写入已重置的状态
+                if !is_current_probe(generation) {
+                    break;
+                }
+                let alpha = f64::from_bits(QUALITY_SMOOTHING_ALPHA.load(Ordering::SeqCst));
+                if alpha > 0.0 {
+                    let ema = smoothing.entry(target).or_default();
+                    apply_quality_smoothing(ema, alpha, &mut sample);
+                } else {
+                    smoothing.clear();
+                }
+                report_quality_sample(&mut sample);
+                push_recent_sample(sample.clone());
+                let was_degraded = degraded.entry(sample.target.clone()).or_default();
+                update_degraded_state(was_degraded, &sample);
+                report_net_quality(sample);
+            }
+
+            // 每个周期重新读取探测间隔，运行时修改从下一个周期开始生效
+            // 未开启间隔扰动（setProbeIntervalJitterPercent）时严格按配置的秒数探测
+            let interval = Duration::from_secs(PROBE_INTERVAL_SECS.load(Ordering::SeqCst));
+            // 无 Internet 连接期间按指数退避拉长间隔，恢复连通时由立即采样请求唤醒并回到配置的间隔
+            if offline {
+                if offline_cycles == 0 {
+                    report_info_log!("网络无 Internet 连接，探测间隔按指数退避拉长");
+                }
+                offline_cycles = offline_cycles.saturating_add(1);
+            } else {
+                offline_cycles = 0;
+            }
+            let interval = backoff_probe_interval(interval, offline_cycles);
+            let interval = jitter_probe_interval(
+                interval,
+                PROBE_INTERVAL_JITTER_PERCENT.load(Ordering::SeqCst),
+                rng.random_range(-1.0..=1.0),
+            );
+            let elapsed = start_at.elapsed();
+            if elapsed >= interval {
+                report_info_log!(
+                    "警告：探测周期耗时 {}ms 超过探测间隔 {}ms，建议调大探测间隔或减少探测次数/目标",
+                    elapsed.as_millis(),
+                    interval.as_millis()
+                );
+            }
+            if elapsed < interval {
+                wait_for_next_cycle(interval - elapsed, generation);
+            }
+        }
+    });
+
+    QUALITY_THREAD
+        .get_or_init(|| Mutex::new(None))
+        .lock()
+        .unwrap()
+        .replace(handle);
+}
+
+// 选择本周期要探测的目标：默认全部目标；轮询模式下只取游标指向的一个并前移游标，
+// 目标列表在运行时变短时游标按取模回绕
+fn select_cycle_targets(
+    targets: Vec<String>,
+    round_robin: bool,
+    cursor: &mut usize,
+) -> Vec<String> {
+    if !round_robin || targets.is_empty() {
+        return targets;
+    }
+    let index = *cursor % targets.len();
+    *cursor = index + 1;
+    targets.into_iter().skip(index).take(1).collect()
+}
+
+// 本周期是否按断网处理：只有网络事件订阅有效时连通状态才可信，未订阅（如未调用 doInitialize）时照常探测
+fn is_offline_cycle(network_monitor_active: bool, network_connected: bool) -> bool {
+    network_monitor_active && !network_connected
+}
+
+// 断网退避：第 n 个断网周期的间隔为 base × 2^n，上限 MAX_OFFLINE_PROBE_INTERVAL；
+// 配置的间隔本身超过上限时保持不变
+fn backoff_probe_interval(base: Duration, offline_cycles: u32) -> Duration {
+    if offline_cycles == 0 || base >= MAX_OFFLINE_PROBE_INTERVAL {
+        return base;
+    }
+    let factor = 1u32 << offline_cycles.min(16);
+    base.saturating_mul(factor).min(MAX_OFFLINE_PROBE_INTERVAL)
+}
+
+// 探测线程是否仍应运行：探测未停止，且没有被之后启动的探测线程取代
+fn is_current_probe(generation: u64) -> bool {
+    QUALITY_RUNNING.load(Ordering::SeqCst)
+        && QUALITY_GENERATION.load(Ordering::SeqCst) == generation
+}
+
+// 在条件变量上等待下一个探测周期：收到立即采样请求或停止探测时提前返回
+fn wait_for_next_cycle(duration: Duration, generation: u64) {
+    let deadline = Instant::now() + duration;
+    let mut pending = QUALITY_WAKE_PENDING.lock().unwrap();
+    while !*pending && is_current_probe(generation) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        pending = QUALITY_WAKE.wait_timeout(pending, remaining).unwrap().0;
+    }
+    *pending = false;
+}
+
+// 请求探测线程立即执行一次采样；探测未运行或已暂停时忽略
+// 恢复连通时先重建 TCP 统计与吞吐量基线，避免首个样本包含断网期间的累计增量
+pub fn request_immediate_sample(rebaseline: bool) {
+    if !QUALITY_RUNNING.load(Ordering::SeqCst) || QUALITY_PAUSED.load(Ordering::SeqCst) {
+        return;
+    }
+    if rebaseline {
+        reset_tcp_stats_baseline();
+        init_tcp_stats_baseline();
+        reset_throughput_baseline();
+        init_throughput_baseline();
+    }
+    *QUALITY_WAKE_PENDING.lock().unwrap() = true;
+    QUALITY_WAKE.notify_all();
+    report_info_log!("连通性变化，请求立即执行网络质量采样");
+}
+
+// 断网时的采样：不发起探测，丢包率按 100% 上报，其余指标为零
+fn build_offline_sample(target_host: &str) -> NetworkQualitySample {
+    NetworkQualitySample {
+        target: target_host.to_string(),
+        sampled_at_ms: get_current_unix_millis(),
+        packet_loss_percent: 100.0,
+        packet_loss_percent_raw: 100.0,
+        quality_grade: QUALITY_GRADE_POOR.to_string(),
+        probe_error: PROBE_ERROR_OFFLINE.to_string(),
+        ..Default::default()
+    }
+}
+
+// 停止网络质量探测线程：最多等待 join_timeout 让后台线程退出
+// 未退出的线程按代数判断已被停止，完成当前探测后丢弃结果自行退出，不会写入下面重置的状态
+pub fn stop_quality_probe(join_timeout: Duration) {
+    QUALITY_RUNNING.store(false, Ordering::SeqCst);
+    QUALITY_PAUSED.store(false, Ordering::SeqCst);
+    // 持锁通知，确保正在等待下一周期的探测线程能观察到停止标志
+    {
+        let _pending = QUALITY_WAKE_PENDING.lock().unwrap();
+        QUALITY_WAKE.notify_all();
+    }
+    if let Some(lock) = QUALITY_THREAD.get()
+        && let Some(handle) = lock.lock().unwrap().take()
+    {
+        // QUALITY_RUNNING 已清除，线程会在当前阻塞调用返回后自行退出且不再上报；有限等待避免拖慢进程退出
+        let deadline = Instant::now() + join_timeout;
+        while !handle.is_finished() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(20));
+        }
+        if handle.is_finished() {
+            let _ = handle.join();
+        } else if join_timeout.is_zero() {
+            report_info_log!("网络质量探测线程正在完成当前探测，结束后将丢弃结果并自行退出");
+        } else {
+            report_error_log!(
+                "等待网络质量探测线程退出超时（{}s），放弃等待，线程将在当前探测结束后丢弃结果并自行退出",
+                join_timeout.as_secs()
+            );
+        }
+    }
+    reset_tcp_stats_baseline();
+    reset_throughput_baseline();
+    RECENT_QUALITY_SAMPLES.lock().unwrap().clear();
+    LAST_PROBE_SUCCESS_AT.lock().unwrap().take();
+    QUALITY_PROBE_STARTED_AT.lock().unwrap().take();
+    clear_resolved_targets();
+    close_persistent_tcp_connections();
+}
+
+// 暂停网络质量探测：线程保持存活，仅跳过探测，重复调用无副作用
+pub fn pause_quality_probe() {
+    let already_paused = QUALITY_PAUSED.swap(true, Ordering::SeqCst);
+    if !already_paused {
+        report_info_log!("暂停网络质量探测");
+    }
+}
+
+// 恢复网络质量探测：重新建立 TCP 统计基线，避免首个样本包含暂停期间的累计增量
+pub fn resume_quality_probe() {
+    let was_paused = QUALITY_PAUSED.swap(false, Ordering::SeqCst);
+    if was_paused {
+        reset_tcp_stats_baseline();
+        init_tcp_stats_baseline();
+        reset_throughput_baseline();
+        init_throughput_baseline();
+        report_info_log!("恢复网络质量探测");
+    }
+}
+
+// 更新探测目标列表：去除空白与重复项，为空时回退到默认目标
+pub fn set_ping_target_list(targets: Vec<String>) {
+    let targets = normalize_ping_targets(targets);
+    *PING_TARGETS
+        .get_or_init(|| Mutex::new(Vec::new()))
+        .lock()
+        .unwrap() = targets;
+}
+
+// 校验探测目标：需为合法 IPv4 或可解析的域名，仅在初始化时执行一次
+pub fn validate_ping_targets() {
+    for target in current_ping_targets() {
+        if resolve_target(&target).is_none() {
+            report_error_log!("探测目标无效：{} 既不是 IP 地址也无法解析", target);
+        }
+    }
+}
+
+// 读取当前探测目标列表，未初始化时使用默认目标
+fn current_ping_targets() -> Vec<String> {
+    PING_TARGETS
+        .get()
+        .map(|targets| targets.lock().unwrap().clone())
+        .filter(|targets| !targets.is_empty())
+        .unwrap_or_else(|| vec![DEFAULT_PING_TARGET.to_string()])
+}
+
+fn normalize_ping_targets(targets: Vec<String>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::with_capacity(targets.len());
+    for target in targets {
+        let target = target.trim();
+        if !target.is_empty() && !result.iter().any(|existing| existing == target) {
+            result.push(target.to_string());
+        }
+    }
+    if result.is_empty() {
+        result.push(DEFAULT_PING_TARGET.to_string());
+    }
+    result
+}
+
+// 立即对首个探测目标执行一次质量探测，用于 Node 侧按需触发
+// 与周期探测共享 TCP 统计基线：本次取走的重传增量不会在下一个周期重复计算
+pub fn probe_first_target_now() -> NetworkQualitySample {
+    let cycle = collect_cycle_metrics();
+    let target = current_ping_targets().remove(0);
+    let mut sample = probe_quality_once(&target, &cycle, &mut None);
+    report_quality_sample(&mut sample);
+    sample
+}
+
+// 按需探测任务：在 libuv 线程池中执行，避免阻塞 Node 主线程
+pub struct ProbeQualityTask;
+
+#[napi]
+impl Task for ProbeQualityTask {
+    type Output = NetworkQualitySample;
+    type JsValue = NetworkQualitySample;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        Ok(probe_first_target_now())
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+// 对目标执行一次 traceroute：TTL 从 1 递增逐跳发送 ICMP Echo，中间路由器以 TTL 超时应答，
+// 到达目标或收到其他 ICMP 差错（如目标不可达）时结束；仅支持 IPv4，解析失败或句柄创建失败返回 None
+pub fn traceroute(target_host: &str, max_hops: u32, timeout: Duration) -> Option<Vec<TraceHop>> {
+    let target = match resolve_target(target_host) {
+        Some(IpAddr::V4(target)) => target,
+        Some(IpAddr::V6(target)) => {
+            report_error_log!(
+                "traceroute 仅支持 IPv4，目标仅解析到 IPv6 地址：target={} ipv6={}",
+                target_host,
+                target
+            );
+            return None;
+        }
+        None => {
+            report_error_log!("traceroute 的目标解析失败：{}", target_host);
+            return None;
+        }
+    };
+    let handle = create_icmp_handle()?;
+    let payload = vec![0u8; PING_PAYLOAD_SIZE.load(Ordering::SeqCst) as usize];
+    let reply_size = icmp_reply_buffer_size(payload.len(), ICMP_MAX_REPLIES);
+    let source = current_probe_source_address();
+    let mut hops = Vec::with_capacity(max_hops as usize);
+
+    for ttl in 1..=max_hops {
+        let options = IP_OPTION_INFORMATION {
+            Ttl: ttl as u8,
+            ..Default::default()
+        };
+        let mut reply_buffer = vec![0u8; reply_size];
+        let response_count = send_icmp_echo(
+            handle,
+            source,
+            target,
+            &payload,
+            Some(&options as *const IP_OPTION_INFORMATION),
+            &mut reply_buffer,
+            timeout,
+        );
+        let last_error = unsafe { GetLastError().0 };
+        // TTL 超时等差错可能以返回 0 + GetLastError 的形式报告，此时缓冲区中仍有应答方地址
+        let replies = parse_icmp_replies(&reply_buffer, response_count.max(1));
+        let reply = replies.first().filter(|reply| {
+            response_count > 0 || (reply.Status == last_error && reply.Address != 0)
+        });
+        let hop = build_trace_hop(ttl, reply, last_error);
+        let finished = is_final_trace_hop(&hop);
+        hops.push(hop);
+        if finished {
+            break;
+        }
+    }
+
+    let _ = unsafe { IcmpCloseHandle(handle) };
+    report_info_log!(
+        "traceroute 完成：target={} ip={} 跳数={} 到达={}",
+        target_host,
+        target,
+        hops.len(),
+        hops.last().is_some_and(|hop| hop.reached)
+    );
+    Some(hops)
+}
+
+// 由单跳的应答构造结果：IcmpSendEcho 中应答方地址的字节序与目标地址一致（小端序）
+fn build_trace_hop(ttl: u32, reply: Option<&ICMP_ECHO_REPLY>, last_error: u32) -> TraceHop {
+    match reply {
+        Some(reply) => TraceHop {
+            ttl,
+            address: Ipv4Addr::from(reply.Address.to_le_bytes()).to_string(),
+            rtt_ms: reply.RoundTripTime,
+            status: ip_status_name(reply.Status),
+            reached: reply.Status == IP_SUCCESS,
+        },
+        None => TraceHop {
+            ttl,
+            status: ip_status_name(last_error),
+            ..Default::default()
+        },
+    }
+}
+
+// 到达目标或收到 TTL 超时以外的应答（如目标不可达）时结束；本跳超时无应答则继续下一跳
+fn is_final_trace_hop(hop: &TraceHop) -> bool {
+    hop.reached || (!hop.address.is_empty() && hop.status != ip_status_name(IP_TTL_EXPIRED_TRANSIT))
+}
+
+// traceroute 任务：逐跳探测最长需要 maxHops × timeoutMs，在 libuv 线程池中执行
+pub struct TracerouteTask {
+    pub target: String,
+    pub max_hops: u32,
+    pub timeout: Duration,
+}
+
+#[napi]
+impl Task for TracerouteTask {
+    type Output = Vec<TraceHop>;
+    type JsValue = Vec<TraceHop>;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        traceroute(&self.target, self.max_hops, self.timeout).ok_or_else(|| {
+            napi::Error::new(
+                napi::Status::GenericFailure,
+                format!("Traceroute failed for target {}", self.target),
+            )
+        })
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+// 检测目标主机一组 TCP 端口的可达性：目标只解析一次，按 TCP_PORT_CHECK_CONCURRENCY 并发建连，
+// 结果顺序与端口列表一致；目标解析失败时返回 None
+pub fn check_tcp_ports(host: &str, ports: &[u16], timeout: Duration) -> Option<Vec<PortResult>> {
+    let Some(addr) = resolve_tcp_socket_addr(host, 0) else {
+        report_error_log!("TCP 端口检测的目标解析失败：{}", host);
+        return None;
+    };
+    let source = current_probe_source_address();
+    let results = run_bounded(ports, TCP_PORT_CHECK_CONCURRENCY, |&port| {
+        let start_at = Instant::now();
+        match connect_tcp(source, SocketAddr::new(addr.ip(), port), timeout) {
+            Ok(stream) => {
+                let _ = stream.shutdown(std::net::Shutdown::Both);
+                PortResult {
+                    port,
+                    reachable: true,
+                    connect_ms: start_at.elapsed().as_millis().min(u128::from(u32::MAX)) as u32,
+                }
+            }
+            Err(error) => {
+                report_debug_log!(
+                    "TCP 端口不可达：host={} port={} error={}",
+                    host,
+                    port,
+                    error
+                );
+                PortResult {
+                    port,
+                    reachable: false,
+                    connect_ms: 0,
+                }
+            }
+        }
+    });
+    report_info_log!(
+        "TCP 端口检测完成：host={} ip={} 可达={}/{}",
+        host,
+        addr.ip(),
+        results.iter().filter(|result| result.reachable).count(),
+        results.len()
+    );
+    Some(results)
+}
+
+// 以最多 concurrency 个线程并发处理 items，结果顺序与输入一致
+fn run_bounded<T, R, F>(items: &[T], concurrency: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let workers = concurrency.clamp(1, items.len().max(1));
+    let mut indexed: Vec<(usize, R)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            break done;
+                        };
+                        done.push((index, f(item)));
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    indexed.sort_unstable_by_key(|(index, _)| *index);
+    indexed.into_iter().map(|(_, result)| result).collect()
+}
+
+// TCP 端口检测任务：在 libuv 线程池中执行，避免并发建连期间阻塞 Node 主线程
+pub struct CheckTcpPortsTask {
+    pub host: String,
+    pub ports: Vec<u16>,
+    pub timeout: Duration,
+}
+
+#[napi]
+impl Task for CheckTcpPortsTask {
+    type Output = Vec<PortResult>;
+    type JsValue = Vec<PortResult>;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        check_tcp_ports(&self.host, &self.ports, self.timeout).ok_or_else(|| {
+            napi::Error::new(
+                napi::Status::GenericFailure,
+                format!("Failed to resolve host: {}", self.host),
+            )
+        })
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+// 对单个目标执行一次完整的质量探测：包含 DNS 解析耗时、延迟、丢包，并附带本周期的 TCP 重传率
+// 目标解析失败时不跳过，而是输出丢包 100% 且 dns_resolve_ms 为 DNS_RESOLVE_FAILED 的样本
+// previous_route 为该目标上一周期的最佳路由，探测后更新为本周期的路由（按需探测传入 None）
+fn probe_quality_once(
+    target_host: &str,
+    cycle: &CycleMetrics,
+    previous_route: &mut Option<ProbeRoute>,
+) -> NetworkQualitySample {
+    let sampled_at_ms = get_current_unix_millis();
+    let started_at = Instant::now();
+    // 本周期的各次测量按同一规则取源地址，这里只用于标记采样是否来自 WiFi 网卡
+    let wifi_bound =
+        PROBE_WIFI_ONLY.load(Ordering::SeqCst) && current_wifi_source_address().is_some();
+    let (resolved, dns_resolve_ms, dns_cached) = resolve_target_cached(target_host);
+    let route_changed = update_probe_route(previous_route, resolved.and_then(query_best_route));
+    if route_changed {
+        report_info_log!(
+            "到探测目标的路由发生变化：target={} route={:?}",
+            target_host,
+            previous_route
+        );
+    }
+    let mode = current_probe_mode();
+    let mut probe_error = "";
+    let ping = match resolved {
+        Some(IpAddr::V4(target)) => measure_target(target_host, target, mode),
+        Some(IpAddr::V6(target)) if mode == ProbeMode::IcmpOnly => {
+            report_error_log!(
+                "仅 ICMP 模式下无法探测仅有 IPv6 地址的目标：target={} ipv6={}",
+                target_host,
+                target
+            );
+            probe_error = PROBE_ERROR_IPV6_UNSUPPORTED;
+            Some(build_ping_stats(&[], 0, 0, 0, 0, None))
+        }
+        // ICMP 探测仅支持 IPv4，仅有 IPv6 地址的目标直接使用 TCP 握手 RTT 探测
+        Some(IpAddr::V6(target)) => {
+            report_info_log!(
+                "探测目标仅解析到 IPv6 地址，使用 TCP 握手 RTT 探测：target={} ipv6={}",
+                target_host,
+                target
+            );
+            measure_tcp_fallback(target_host, IpAddr::V6(target), current_ping_count())
+        }
+        None => {
+            report_error_log!("探测目标解析失败：{}", target_host);
+            probe_error = PROBE_ERROR_RESOLUTION_FAILED;
+            Some(build_ping_stats(&[], 0, 0, 0, 0, None))
+        }
+    };
+    if ping.as_ref().is_some_and(|ping| ping.icmp_unavailable) {
+        probe_error = PROBE_ERROR_ICMP_UNAVAILABLE;
+    }
+    // 目标已解析但没有得到任何测量结果（如 TCP 回退在首个端口前就达到测量时限），同样按全部丢包上报
+    let ping = ping.unwrap_or_else(|| {
+        report_error_log!("探测目标本周期没有测量结果：{}", target_host);
+        probe_error = PROBE_ERROR_MEASUREMENT_FAILED;
+        build_ping_stats(&[], 0, 0, 0, 0, None)
+    });
+
+    // 全部失败时丢弃缓存：目标可能已更换 IP（如 CDN 调度），下个周期重新解析
+    if ping.success_count == 0 {
+        invalidate_resolved_target(target_host);
+    }
+
+    let mut sample = assemble_quality_sample(
+        target_host,
+        sampled_at_ms,
+        dns_resolve_ms,
+        Some(&ping),
+        cycle,
+    );
+    sample.dns_cached = dns_cached;
+    sample.route_changed = route_changed;
+    sample.wifi_bound = wifi_bound;
+    sample.probe_error = probe_error.to_string();
+    sample.cycle_duration_ms = started_at.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
+    sample
+}
+
+// 记录本周期的路由并返回是否与上一周期不同：首次采样不视为变化，
+// 查询失败（如断网时没有路由）时保留上一次的路由，恢复后与断网前的路由比较
+fn update_probe_route(previous: &mut Option<ProbeRoute>, current: Option<ProbeRoute>) -> bool {
+    let Some(current) = current else {
+        return false;
+    };
+    previous
+        .replace(current)
+        .is_some_and(|previous| previous != current)
+}
+
+// 查询到目标地址的最佳路由（与系统实际选路一致，已考虑 VPN 与接口跃点数）
+fn query_best_route(target: IpAddr) -> Option<ProbeRoute> {
+    let destination = SOCKADDR_INET::from(SocketAddr::new(target, 0));
+    let mut route = MIB_IPFORWARD_ROW2::default();
+    let mut source = SOCKADDR_INET::default();
+    let result = unsafe { GetBestRoute2(None, 0, None, &destination, 0, &mut route, &mut source) };
+    if result != ERROR_SUCCESS {
+        report_debug_log!("GetBestRoute2 失败：target={} error={:?}", target, result);
+        return None;
+    }
+    let next_hop = unsafe {
+        if route.NextHop.si_family == AF_INET6 {
+            IpAddr::from(route.NextHop.Ipv6.sin6_addr.u.Byte)
+        } else {
+            IpAddr::from(route.NextHop.Ipv4.sin_addr.S_un.S_addr.to_ne_bytes())
+        }
+    };
+    Some(ProbeRoute {
+        interface_index: route.InterfaceIndex,
+        next_hop,
+    })
+}
+
+// 由探测原始结果组装采样：不调用任何系统 API，缺失的指标填 0，评分与平滑由后续步骤填充
+fn assemble_quality_sample(
+    target_host: &str,
+    sampled_at_ms: f64,
+    dns_resolve_ms: u32,
+    ping: Option<&PingStats>,
+    cycle: &CycleMetrics,
+) -> NetworkQualitySample {
+    let tcp_stats = cycle.tcp_stats.as_ref();
+    let gateway = cycle.gateway.as_ref();
+    NetworkQualitySample {
+        target: target_host.to_string(),
+        sampled_at_ms,
+        dns_resolve_ms,
+        dns_cached: false,
+        latency_avg_ms: ping.map(|p| p.avg_ms).unwrap_or(0),
+        latency_avg_ms_raw: ping.map(|p| p.avg_ms).unwrap_or(0),
+        latency_min_ms: ping.map(|p| p.min_ms).unwrap_or(0),
+        latency_max_ms: ping.map(|p| p.max_ms).unwrap_or(0),
+        latency_p50_ms: ping.map(|p| p.p50_ms).unwrap_or(0),
+        latency_p95_ms: ping.map(|p| p.p95_ms).unwrap_or(0),
+        latency_p99_ms: ping.map(|p| p.p99_ms).unwrap_or(0),
+        jitter_ms: ping.map(|p| p.jitter_ms).unwrap_or(0),
+        jitter_ms_raw: ping.map(|p| p.jitter_ms).unwrap_or(0),
+        jitter_stddev_ms: ping.map(|p| p.jitter_stddev_ms).unwrap_or(0),
+        jitter_rfc3550_ms: ping.map(|p| p.jitter_rfc3550_ms).unwrap_or(0.0),
+        // 没有测量结果时按全部丢包处理，避免零延迟、零丢包被评为满分
+        packet_loss_percent: ping.map(|p| p.loss_percent).unwrap_or(100.0),
+        packet_loss_percent_raw: ping.map(|p| p.loss_percent).unwrap_or(100.0),
+        icmp_error_percent: ping.map(|p| p.icmp_error_percent).unwrap_or(0.0),
+        last_ping_status: ping
+            .and_then(|p| p.last_status)
+            .map(ip_status_name)
+            .unwrap_or_default(),
+        echo_count: ping.map(|p| p.echo_count as u32).unwrap_or(0),
+        cycle_duration_ms: 0,
+        tcp_stats_available: tcp_stats.is_some(),
+        tcp_retransmission_percent: tcp_stats.map(|t| t.retransmission_percent).unwrap_or(0.0),
+        tcp_segments_sent: tcp_stats.map(|t| t.segments_sent).unwrap_or(0),
+        tcp_segments_retransmitted: tcp_stats.map(|t| t.segments_retransmitted).unwrap_or(0),
+        tcp_connection_failures: tcp_stats.map(|t| t.connection_failures).unwrap_or(0),
+        tcp_current_established: tcp_stats.map(|t| t.current_established).unwrap_or(0),
+        mtu_black_hole: ping.map(|p| p.packet_too_big).unwrap_or(false),
+        route_changed: false,
+        out_of_order_count: ping.map(|p| p.out_of_order_count as u32).unwrap_or(0),
+        icmp_blocked: ping.is_some_and(|p| p.icmp_blocked),
+        wifi_bound: false,
+        probe_error: String::new(),
+        gateway_latency_ms: gateway.map(|g| g.avg_ms).unwrap_or(0),
+        gateway_loss_percent: gateway.map(|g| g.loss_percent).unwrap_or(0.0),
+        rx_bytes_per_sec: cycle.throughput.rx_bytes_per_sec,
+        tx_bytes_per_sec: cycle.throughput.tx_bytes_per_sec,
+        rx_errors: cycle.interface_errors.rx_errors as i64,
+        tx_errors: cycle.interface_errors.tx_errors as i64,
+        rx_discards: cycle.interface_errors.rx_discards as i64,
+        tx_discards: cycle.interface_errors.tx_discards as i64,
+        quality_score: 0,
+        quality_grade: QUALITY_GRADE_POOR.to_string(),
+        dropped_samples: 0,
+        seconds_since_last_success: 0,
+    }
+}
+
+// 按滞回阈值更新目标的劣化状态，状态切换时上报劣化/恢复事件
+fn update_degraded_state(is_degraded: &mut bool, sample: &NetworkQualitySample) {
+    let (jitter_enter, jitter_exit) = normalize_degradation_thresholds(
+        DEGRADED_JITTER_ENTER_MS.load(Ordering::SeqCst),
+        DEGRADED_JITTER_EXIT_MS.load(Ordering::SeqCst),
+        DEFAULT_DEGRADED_JITTER_ENTER_MS,
+        DEFAULT_DEGRADED_JITTER_EXIT_MS,
+    );
+    let (loss_enter, loss_exit) = normalize_degradation_thresholds(
+        DEGRADED_LOSS_ENTER_PERCENT.load(Ordering::SeqCst),
+        DEGRADED_LOSS_EXIT_PERCENT.load(Ordering::SeqCst),
+        DEFAULT_DEGRADED_LOSS_ENTER_PERCENT,
+        DEFAULT_DEGRADED_LOSS_EXIT_PERCENT,
+    );
+    let jitter_high = sample.jitter_ms >= jitter_enter;
+    let loss_high = sample.packet_loss_percent >= loss_enter as f64;
+
+    let was_degraded = *is_degraded;
+    if jitter_high || loss_high {
+        *is_degraded = true;
+    } else if sample.jitter_ms <= jitter_exit && sample.packet_loss_percent <= loss_exit as f64 {
+        *is_degraded = false;
+    }
+    if was_degraded == *is_degraded {
+        return;
+    }
+
+    let mut reasons = Vec::new();
+    if *is_degraded && jitter_high {
+        reasons.push("jitter");
+    }
+    if *is_degraded && loss_high {
+        reasons.push("loss");
+    }
+    let reason = reasons.join(",");
+    if *is_degraded {
+        report_info_log!(
+            "网络质量劣化：目标={}，jitter={}ms，丢包={}%，原因={}",
+            sample.target,
+            sample.jitter_ms,
+            sample.packet_loss_percent,
+            reason
+        );
+    } else {
+        report_info_log!(
+            "网络质量恢复：目标={}，jitter={}ms，丢包={}%",
+            sample.target,
+            sample.jitter_ms,
+            sample.packet_loss_percent
+        );
+    }
+    report_quality_degraded(QualityDegradedEvent {
+        degraded: *is_degraded,
+        target: sample.target.clone(),
+        jitter_ms: sample.jitter_ms,
+        packet_loss_percent: sample.packet_loss_percent,
+        reason,
+    });
+}
+
+// 阈值为 0 时回退到默认值，且保证退出阈值小于进入阈值（指标越大越差），避免滞回区间失效
+fn normalize_degradation_thresholds(
+    enter: u32,
+    exit: u32,
+    default_enter: u32,
+    default_exit: u32,
+) -> (u32, u32) {
+    let enter = if enter == 0 { default_enter } else { enter };
+    let mut exit = if exit == 0 { default_exit } else { exit };
+    if exit >= enter {
+        exit = enter / 2;
+    }
+    (enter, exit)
+}
+
+// 指数移动平均：newValue = alpha * 当前值 + (1 - alpha) * 上一次平滑值，首个样本直接取当前值
+fn ema_update(previous: Option<f64>, value: f64, alpha: f64) -> f64 {
+    match previous {
+        Some(previous) => alpha * value + (1.0 - alpha) * previous,
+        None => value,
+    }
+}
+
+// 对延迟、抖动与丢包做指数平滑，*_raw 字段保持本周期原始值
+// 全部丢包时延迟与抖动为 0 并非真实测量值，不计入平滑，沿用上一次的平滑结果
+fn apply_quality_smoothing(ema: &mut QualityEma, alpha: f64, sample: &mut NetworkQualitySample) {
+    if sample.packet_loss_percent_raw < 100.0 {
+        ema.latency_avg_ms = Some(ema_update(
+            ema.latency_avg_ms,
+            sample.latency_avg_ms_raw as f64,
+            alpha,
+        ));
+        ema.jitter_ms = Some(ema_update(
+            ema.jitter_ms,
+            sample.jitter_ms_raw as f64,
+            alpha,
+        ));
+    }
+    ema.packet_loss_percent = Some(ema_update(
+        ema.packet_loss_percent,
+        sample.packet_loss_percent_raw,
+        alpha,
+    ));
+
+    sample.latency_avg_ms = ema
+        .latency_avg_ms
+        .map(|value| value.round() as u32)
+        .unwrap_or(sample.latency_avg_ms_raw);
+    sample.jitter_ms = ema
+        .jitter_ms
+        .map(|value| value.round() as u32)
+        .unwrap_or(sample.jitter_ms_raw);
+    sample.packet_loss_percent = ema
+        .packet_loss_percent
+        .unwrap_or(sample.packet_loss_percent_raw);
+}
+
+// 校验平滑系数：0 表示关闭平滑，(0, 1] 越大越贴近原始值
+pub fn is_valid_smoothing_alpha(alpha: f64) -> bool {
+    (0.0..=1.0).contains(&alpha)
+}
+
+// 按探测模式测量已解析的目标：默认先 ICMP、全部失败时回退为 TCP 握手 RTT 探测；
+// 仅 TCP 模式跳过 ICMP，避免在屏蔽 ICMP 的网络中每周期等满全部 Echo 超时
+fn measure_target(target_host: &str, target: Ipv4Addr, mode: ProbeMode) -> Option<PingStats> {
+    let count = current_ping_count();
+    if mode == ProbeMode::TcpOnly {
+        return measure_tcp_fallback(target_host, IpAddr::V4(target), count);
+    }
+    let ping = measure_latency_and_loss(target, count, current_ping_timeout_ms(), ping_deadline());
+    if ping.is_none() && mode == ProbeMode::IcmpOnly {
+        report_error_log!(
+            "无法创建 ICMP 句柄，仅 ICMP 模式下本周期按全部丢包上报：target={} ipv4={}",
+            target_host,
+            target
+        );
+        let mut stats = build_ping_stats(&[], 0, 0, 0, 0, None);
+        stats.icmp_unavailable = true;
+        return Some(stats);
+    }
+    if !needs_tcp_fallback(mode, ping.as_ref()) {
+        return ping;
+    }
+    let Some(stats) = ping else {
+        report_info_log!(
+            "无法创建 ICMP 句柄，本周期使用 TCP 握手 RTT 探测：target={} ipv4={}",
+            target_host,
+            target
+        );
+        return measure_tcp_fallback(target_host, IpAddr::V4(target), count);
+    };
+    report_info_log!(
+        "ICMP 探测全失败，切换为 TCP 握手 RTT 探测：target={} ipv4={} success_count={}/{} last_error={} last_reply_status={:?}",
+        target_host,
+        target,
+        stats.success_count,
+        stats.echo_count,
+        stats.last_error,
+        stats.last_reply_status
+    );
+    let mut ping = measure_tcp_fallback(target_host, IpAddr::V4(target), count);
+    // TCP 回退不影响 MTU 判断，保留 ICMP 阶段的结论、最后的 ICMP 状态与乱序统计
+    if let Some(tcp_stats) = ping.as_mut() {
+        tcp_stats.packet_too_big = stats.packet_too_big;
+        tcp_stats.last_status = stats.last_status;
+        tcp_stats.out_of_order_count = stats.out_of_order_count;
+        tcp_stats.icmp_blocked = true;
+    }
+    ping
+}
+
+// 按配置顺序尝试 TCP 握手端口，首个有成功握手的端口作为本周期结果；全部失败时返回最后一个端口的统计
+// 所有端口共享同一个测量时限，端口列表再长也不会超过一次测量的耗时上限
+// 沿用本周期已解析（可能来自缓存或 setDnsServer）的地址，不再重新解析，保证与其他测量针对同一 IP
+fn measure_tcp_fallback(target_host: &str, target: IpAddr, count: usize) -> Option<PingStats> {
+    let deadline = ping_deadline();
+    let mut last = None;
+    for port in current_tcp_fallback_ports() {
+        if Instant::now() >= deadline {
+            report_info_log!(
+                "TCP 握手 RTT 探测达到测量时限，跳过剩余端口：target={} port={}",
+                target_host,
+                port
+            );
+            break;
+        }
+        let stats = measure_tcp_handshake_rtt(
+            SocketAddr::new(target, port),
+            count,
+            Duration::from_millis(current_ping_timeout_ms() as u64),
+            deadline,
+        );
+        if stats.success_count > 0 {
+            report_info_log!(
+                "TCP 握手 RTT 探测成功：target={} port={} success_count={}/{}",
+                target_host,
+                port,
+                stats.success_count,
+                stats.echo_count
+            );
+            return Some(stats);
+        }
+        report_info_log!(
+            "TCP 握手 RTT 探测失败：target={} port={}",
+            target_host,
+            port
+        );
+        last = Some(stats);
+    }
+    last
+}
+
+// 仅在先 ICMP 模式下、ICMP 测量没有任何成功回包时回退为 TCP 握手探测；
+// ICMP 句柄创建重试后仍失败（None）时同样回退，避免本周期没有采样
+fn needs_tcp_fallback(mode: ProbeMode, icmp: Option<&PingStats>) -> bool {
+    mode == ProbeMode::IcmpFirst && icmp.is_none_or(|stats| stats.success_count == 0)
+}
+
+// 读取探测模式配置，非法值回退到默认的先 ICMP 后 TCP
+fn current_probe_mode() -> ProbeMode {
+    probe_mode_from_u32(PROBE_MODE.load(Ordering::SeqCst)).unwrap_or(ProbeMode::IcmpFirst)
+}
+
+// 探测模式取值映射，非法值返回 None
+pub fn probe_mode_from_u32(mode: u32) -> Option<ProbeMode> {
+    match mode {
+        PROBE_MODE_ICMP_FIRST => Some(ProbeMode::IcmpFirst),
+        PROBE_MODE_TCP_ONLY => Some(ProbeMode::TcpOnly),
+        PROBE_MODE_ICMP_ONLY => Some(ProbeMode::IcmpOnly),
+        _ => None,
+    }
+}
+
+// 读取每周期 Echo 次数配置，非法值回退到默认值
+fn current_ping_count() -> usize {
+    match PING_COUNT.load(Ordering::SeqCst) {
+        count @ 1..=MAX_PING_COUNT => count as usize,
+        _ => DEFAULT_PING_COUNT,
+    }
+}
+
+// 读取单次 Echo 超时配置，超出合法范围时回退到默认值
+fn current_ping_timeout_ms() -> u32 {
+    match PING_TIMEOUT_MS.load(Ordering::SeqCst) {
+        timeout_ms @ MIN_PING_TIMEOUT_MS..=MAX_PING_TIMEOUT_MS => timeout_ms,
+        _ => DEFAULT_PING_TIMEOUT_MS,
+    }
+}
+
+// 由测量时限配置计算本次测量的截止时间
+fn ping_deadline() -> Instant {
+    let deadline_ms = match PING_DEADLINE_MS.load(Ordering::SeqCst) {
+        0 => DEFAULT_PING_DEADLINE_MS,
+        deadline_ms => deadline_ms,
+    };
+    Instant::now() + Duration::from_millis(deadline_ms as u64)
+}
+
+// 单次 Echo 的超时不超过剩余时限，保证测量在截止时间附近结束；剩余不足 1ms 时返回 None
+fn clamp_echo_timeout(timeout: Duration, deadline: Instant, now: Instant) -> Option<Duration> {
+    let remaining = deadline.saturating_duration_since(now);
+    if remaining < Duration::from_millis(1) {
+        return None;
+    }
+    Some(timeout.min(remaining))
+}
+
+// 更新 TCP 回退端口列表：去除重复项，为空时回退到默认端口
+pub fn set_tcp_fallback_port_list(ports: Vec<u16>) {
+    *TCP_FALLBACK_PORTS
+        .get_or_init(|| Mutex::new(Vec::new()))
+        .lock()
+        .unwrap() = normalize_tcp_fallback_ports(ports);
+}
+
+fn normalize_tcp_fallback_ports(ports: Vec<u16>) -> Vec<u16> {
+    let mut result: Vec<u16> = Vec::with_capacity(ports.len());
+    for port in ports {
+        if !result.contains(&port) {
+            result.push(port);
+        }
+    }
+    if result.is_empty() {
+        result.push(DEFAULT_TCP_FALLBACK_PORT);
+    }
+    result
+}
+
+// 读取当前 TCP 回退端口列表，未设置时使用默认端口
+fn current_tcp_fallback_ports() -> Vec<u16> {
+    TCP_FALLBACK_PORTS
+        .get()
+        .map(|ports| ports.lock().unwrap().clone())
+        .filter(|ports| !ports.is_empty())
+        .unwrap_or_else(|| vec![DEFAULT_TCP_FALLBACK_PORT])
+}
+
+// 采集周期级指标：TCP 统计、网关延迟与吞吐量都是系统/链路级指标，与探测目标无关，每个周期只取一次
+fn collect_cycle_metrics() -> CycleMetrics {
+    let tcp_stats = query_tcp_stats();
+    let route = query_default_route_ipv4();
+    if route.is_none() && !GATEWAY_QUERY_WARNED.swap(true, Ordering::SeqCst) {
+        report_error_log!("无法获取默认网关，网关延迟与吞吐量按 0 上报");
+    }
+    // 仅 TCP 模式下不发送任何 ICMP，网关延迟按 0 上报
+    let gateway = route
+        .as_ref()
+        .filter(|_| current_probe_mode() != ProbeMode::TcpOnly)
+        .and_then(|route| measure_gateway(route.gateway));
+    let (throughput, interface_errors) = route
+        .as_ref()
+        .and_then(|route| query_interface_counters(route.interface_index))
+        .unwrap_or_default();
+    CycleMetrics {
+        tcp_stats,
+        gateway,
+        throughput,
+        interface_errors,
+    }
+}
+
+// 探测默认网关：网关持续无应答（多为丢弃 ICMP）时退避，跳过的周期返回 None
+fn measure_gateway(gateway: Ipv4Addr) -> Option<PingStats> {
+    let silent_cycles = {
+        let mut silence = GATEWAY_SILENCE.lock().unwrap();
+        match *silence {
+            Some((address, cycles)) if address == gateway => cycles,
+            _ => {
+                silence.replace((gateway, 0));
+                0
+            }
+        }
+    };
+    let ping = should_ping_gateway(silent_cycles)
+        .then(|| {
+            measure_latency_and_loss(
+                gateway,
+                GATEWAY_PING_COUNT,
+                GATEWAY_PING_TIMEOUT_MS,
+                Instant::now() + GATEWAY_PING_DEADLINE,
+            )
+        })
+        .flatten();
+    let responded = ping.as_ref().is_some_and(|ping| ping.success_count > 0);
+    if !responded && silent_cycles + 1 == GATEWAY_SILENT_BACKOFF_CYCLES {
+        report_info_log!(
+            "默认网关连续 {} 个周期无 ICMP 应答，降低网关探测频率：gateway={}",
+            GATEWAY_SILENT_BACKOFF_CYCLES,
+            gateway
+        );
+    }
+    let silent_cycles = if responded {
+        0
+    } else {
+        silent_cycles.saturating_add(1)
+    };
+    GATEWAY_SILENCE
+        .lock()
+        .unwrap()
+        .replace((gateway, silent_cycles));
+    ping
+}
+
+// 网关连续无应答未达到退避阈值时每个周期都探测，之后每 GATEWAY_SILENT_RETRY_CYCLES 个周期重试一次
+fn should_ping_gateway(silent_cycles: u32) -> bool {
+    silent_cycles < GATEWAY_SILENT_BACKOFF_CYCLES
+        || (silent_cycles - GATEWAY_SILENT_BACKOFF_CYCLES)
+            .is_multiple_of(GATEWAY_SILENT_RETRY_CYCLES)
+}
+
+// 以默认路由所在接口作为活动接口，计算本周期的收发速率与新增差错/丢弃包数
+fn query_interface_counters(interface_index: u32) -> Option<(Throughput, InterfaceErrors)> {
+    let current = read_interface_octets(interface_index)?;
+    let baseline_lock = THROUGHPUT_BASELINE.get_or_init(|| Mutex::new(None));
+    let mut baseline = baseline_lock.lock().unwrap();
+    let errors = compute_interval_errors(baseline.as_ref(), &current);
+    Some((compute_interval_throughput(&mut baseline, current), errors))
+}
+
+fn init_throughput_baseline() {
+    let Some(route) = query_default_route_ipv4() else {
+        return;
+    };
+    if let Some(current) = read_interface_octets(route.interface_index) {
+        *THROUGHPUT_BASELINE
+            .get_or_init(|| Mutex::new(None))
+            .lock()
+            .unwrap() = Some(current);
+    }
+}
+
+fn reset_throughput_baseline() {
+    if let Some(lock) = THROUGHPUT_BASELINE.get() {
+        *lock.lock().unwrap() = None;
+    }
+}
+
+// 读取指定接口的累计收发字节数
+fn read_interface_octets(interface_index: u32) -> Option<InterfaceOctets> {
+    let mut row = MIB_IF_ROW2 {
+        InterfaceIndex: interface_index,
+        ..Default::default()
+    };
+    let result = unsafe { GetIfEntry2(&mut row) };
+    if result != ERROR_SUCCESS {
+        report_error_log!("GetIfEntry2 失败: {:?}", result);
+        return None;
+    }
+    Some(InterfaceOctets {
+        interface_index,
+        in_octets: row.InOctets,
+        out_octets: row.OutOctets,
+        in_errors: row.InErrors,
+        out_errors: row.OutErrors,
+        in_discards: row.InDiscards,
+        out_discards: row.OutDiscards,
+        read_at: Instant::now(),
+    })
+}
+
+// 与吞吐量共用基线快照：首次采样、活动接口切换或任一计数器回绕时输出 0（基线由 compute_interval_throughput 更新）
+fn compute_interval_errors(
+    previous: Option<&InterfaceOctets>,
+    current: &InterfaceOctets,
+) -> InterfaceErrors {
+    let Some(previous) = previous else {
+        return InterfaceErrors::default();
+    };
+    if previous.interface_index != current.interface_index
+        || current.in_errors < previous.in_errors
+        || current.out_errors < previous.out_errors
+        || current.in_discards < previous.in_discards
+        || current.out_discards < previous.out_discards
+    {
+        return InterfaceErrors::default();
+    }
+    InterfaceErrors {
+        rx_errors: current.in_errors - previous.in_errors,
+        tx_errors: current.out_errors - previous.out_errors,
+        rx_discards: current.in_discards - previous.in_discards,
+        tx_discards: current.out_discards - previous.out_discards,
+    }
+}
+
+// 与 compute_interval_tcp_stats 相同的基线处理：首次采样、活动接口切换或计数器回绕时重建基线并输出 0
+fn compute_interval_throughput(
+    baseline: &mut Option<InterfaceOctets>,
+    current: InterfaceOctets,
+) -> Throughput {
+    let Some(previous) = baseline.replace(current) else {
+        return Throughput::default();
+    };
+    let current = baseline.as_ref().unwrap();
+    if previous.interface_index != current.interface_index
+        || current.in_octets < previous.in_octets
+        || current.out_octets < previous.out_octets
+    {
+        return Throughput::default();
+    }
+
+    let elapsed_secs = current
+        .read_at
+        .duration_since(previous.read_at)
+        .as_secs_f64();
+    if elapsed_secs <= 0.0 {
+        return Throughput::default();
+    }
+    Throughput {
+        rx_bytes_per_sec: (current.in_octets - previous.in_octets) as f64 / elapsed_secs,
+        tx_bytes_per_sec: (current.out_octets - previous.out_octets) as f64 / elapsed_secs,
+    }
+}
+
+// 从 IPv4 路由表中选取默认路由（前缀长度为 0），存在多条时取跃点数最小者
+fn query_default_route_ipv4() -> Option<DefaultRoute> {
+    let mut table: *mut MIB_IPFORWARD_TABLE2 = null_mut();
+    let result = unsafe { GetIpForwardTable2(AF_INET, &mut table) };
+    if result != ERROR_SUCCESS || table.is_null() {
+        if result != ERROR_SUCCESS {
+            report_error_log!("GetIpForwardTable2 失败: {:?}", result);
+        }
+        return None;
+    }
+
+    let rows = unsafe {
+        std::slice::from_raw_parts((*table).Table.as_ptr(), (*table).NumEntries as usize)
+    };
+    let route = rows
+        .iter()
+        .filter(|row| row.DestinationPrefix.PrefixLength == 0)
+        .filter_map(|row| {
+            let next_hop = unsafe { row.NextHop.Ipv4.sin_addr.S_un.S_addr };
+            let next_hop = Ipv4Addr::from(next_hop.to_ne_bytes());
+            (!next_hop.is_unspecified()).then_some((row.Metric, row.InterfaceIndex, next_hop))
+        })
+        .min_by_key(|(metric, _, _)| *metric)
+        .map(|(_, interface_index, gateway)| DefaultRoute {
+            interface_index,
+            gateway,
+        });
 
-            let elapsed = start_at.elapsed();
-            if elapsed < interval {
-                thread::sleep(interval - elapsed);
-            }
-        }
-    });
+    unsafe { FreeMibTable(table as *const c_void) };
 
-    QUALITY_THREAD
-        .get_or_init(|| Mutex::new(None))
-        .lock()
-        .unwrap()
-        .replace(handle);
+    route
 }
 
-// 停止网络质量探测线程：等待后台线程退出
-pub fn stop_quality_probe() {
-    QUALITY_RUNNING.store(false, Ordering::SeqCst);
-    if let Some(lock) = QUALITY_THREAD.get()
-        && let Some(handle) = lock.lock().unwrap().take()
-    {
-        let _ = handle.join();
+// 将周期采样追加到最近采样缓冲区，容量按配置实时读取
+fn push_recent_sample(sample: NetworkQualitySample) {
+    let capacity = RECENT_QUALITY_CAPACITY.load(Ordering::SeqCst) as usize;
+    push_bounded(
+        &mut RECENT_QUALITY_SAMPLES.lock().unwrap(),
+        sample,
+        capacity,
+    );
+}
+
+// 追加到定长队列尾部，超出容量时从头部丢弃最旧的元素
+fn push_bounded<T>(buffer: &mut VecDeque<T>, item: T, capacity: usize) {
+    while buffer.len() >= capacity.max(1) {
+        buffer.pop_front();
+    }
+    if capacity > 0 {
+        buffer.push_back(item);
     }
-    reset_tcp_stats_baseline();
 }
 
-// 执行一次完整的质量探测：包含延迟、丢包和 TCP 重传率
-fn probe_quality_once() -> Option<NetworkQualitySample> {
-    let target = resolve_ipv4_target(DEFAULT_PING_TARGET)?;
-    let mut ping = measure_latency_and_loss(target, DEFAULT_PING_COUNT, DEFAULT_PING_TIMEOUT_MS);
-    if let Some(stats) = ping.as_ref()
-        && stats.success_count == 0
-    {
-        report_info_log!(
-            "ICMP 探测全失败，切换为 TCP 握手 RTT 探测：target={} ipv4={} success_count={}/{} last_error={} last_reply_status={:?}",
-            DEFAULT_PING_TARGET,
-            target,
-            stats.success_count,
-            DEFAULT_PING_COUNT,
-            stats.last_error,
-            stats.last_reply_status
-        );
-        ping = measure_tcp_handshake_rtt(
-            DEFAULT_PING_TARGET,
-            443,
-            DEFAULT_PING_COUNT,
-            Duration::from_millis(DEFAULT_PING_TIMEOUT_MS as u64),
-        );
+// 更新目标最近一次成功时间并填充 seconds_since_last_success：有 Echo 未超时且未返回差错即视为成功
+fn apply_success_staleness(sample: &mut NetworkQualitySample) {
+    let now = Instant::now();
+    let succeeded =
+        sample.echo_count > 0 && sample.packet_loss_percent_raw + sample.icmp_error_percent < 100.0;
+    let mut last_success = LAST_PROBE_SUCCESS_AT.lock().unwrap();
+    let last_success = last_success.get_or_insert_with(HashMap::new);
+    if succeeded {
+        last_success.insert(sample.target.clone(), now);
     }
-    let tcp_stats = query_tcp_stats();
+    // 按需探测可能发生在周期探测启动之前，此时以本次采样作为计时起点
+    let started_at = QUALITY_PROBE_STARTED_AT.lock().unwrap().unwrap_or(now);
+    sample.seconds_since_last_success =
+        seconds_since_last_success(last_success.get(&sample.target).copied(), started_at, now);
+}
 
-    Some(NetworkQualitySample {
-        latency_avg_ms: ping.as_ref().map(|p| p.avg_ms).unwrap_or(0),
-        latency_min_ms: ping.as_ref().map(|p| p.min_ms).unwrap_or(0),
-        latency_max_ms: ping.as_ref().map(|p| p.max_ms).unwrap_or(0),
-        jitter_ms: ping.as_ref().map(|p| p.jitter_ms).unwrap_or(0),
-        packet_loss_percent: ping.as_ref().map(|p| p.loss_percent).unwrap_or(0.0),
-        tcp_retransmission_percent: tcp_stats
-            .as_ref()
-            .map(|t| t.retransmission_percent)
-            .unwrap_or(0.0),
-        tcp_segments_sent: tcp_stats.as_ref().map(|t| t.segments_sent).unwrap_or(0),
-        tcp_segments_retransmitted: tcp_stats
-            .as_ref()
-            .map(|t| t.segments_retransmitted)
-            .unwrap_or(0),
-    })
+// 距最近一次成功的秒数；从未成功时从 started_at 开始计时
+fn seconds_since_last_success(
+    last_success: Option<Instant>,
+    started_at: Instant,
+    now: Instant,
+) -> u32 {
+    let since = last_success.unwrap_or(started_at);
+    now.saturating_duration_since(since)
+        .as_secs()
+        .min(u32::MAX as u64) as u32
 }
 
-// 记录采样结果：统一输出，便于日志聚合与后续消费
-fn report_quality_sample(sample: &NetworkQualitySample) {
+// 记录采样结果：补充质量评分后统一输出，便于日志聚合与后续消费
+fn report_quality_sample(sample: &mut NetworkQualitySample) {
+    apply_quality_score(sample);
+    apply_success_staleness(sample);
+    LAST_QUALITY_SAMPLE.lock().unwrap().replace(sample.clone());
     let retransmission_percent_total = compute_retransmission_percent_total(
         sample.tcp_segments_sent,
         sample.tcp_segments_retransmitted,
     );
     report_info_log!(
-        "网络质量采样：延迟avg={:?}ms,min={:?}ms,max={:?}ms,jitter={:?}ms,丢包={:?}%,重传率(out)={:?}%,重传率(total)={:?}%,发送段={:?},重传段={:?}",
+        "网络质量采样：目标={},DNS={:?}ms,延迟avg={:?}ms,min={:?}ms,max={:?}ms,p50={:?}ms,p95={:?}ms,p99={:?}ms,jitter={:?}ms,jitter(stddev)={:?}ms,jitter(rfc3550)={:.1}ms,丢包={:?}%,ICMP差错={:?}%,重传率(out)={:?}%,重传率(total)={:?}%,发送段={:?},重传段={:?},建连失败={:?},当前连接={:?},MTU黑洞={},网关延迟={:?}ms,网关丢包={:?}%,接收={:.0}B/s,发送={:.0}B/s,评分={}({})",
+        sample.target,
+        sample.dns_resolve_ms,
         sample.latency_avg_ms,
         sample.latency_min_ms,
         sample.latency_max_ms,
+        sample.latency_p50_ms,
+        sample.latency_p95_ms,
+        sample.latency_p99_ms,
         sample.jitter_ms,
+        sample.jitter_stddev_ms,
+        sample.jitter_rfc3550_ms,
         sample.packet_loss_percent,
+        sample.icmp_error_percent,
         sample.tcp_retransmission_percent,
         retransmission_percent_total,
         sample.tcp_segments_sent,
-        sample.tcp_segments_retransmitted
+        sample.tcp_segments_retransmitted,
+        sample.tcp_connection_failures,
+        sample.tcp_current_established,
+        sample.mtu_black_hole,
+        sample.gateway_latency_ms,
+        sample.gateway_loss_percent,
+        sample.rx_bytes_per_sec,
+        sample.tx_bytes_per_sec,
+        sample.quality_score,
+        sample.quality_grade
     );
 }
 
-// 计算指定目标的延迟与丢包率
-fn measure_latency_and_loss(target: Ipv4Addr, count: usize, timeout_ms: u32) -> Option<PingStats> {
-    let handle = unsafe { IcmpCreateFile() };
-    let handle = match handle {
-        Ok(handle) => handle,
-        Err(error) => {
-            report_error_log!("IcmpCreateFile 失败: {}", error);
-            return None;
-        }
+// 计算采样的综合评分与分档
+// 全部丢包视为探测完全失败，评分固定为 0；其余情况按（可能已平滑的）对外指标评分
+// ICMP 差错虽不计入丢包，但同样意味着 Echo 未能到达目标，评分时与丢包合并计算
+fn apply_quality_score(sample: &mut NetworkQualitySample) {
+    let unreachable_percent = sample.packet_loss_percent_raw + sample.icmp_error_percent;
+    sample.quality_score = if unreachable_percent >= 100.0 {
+        0
+    } else {
+        compute_quality_score(
+            sample.latency_avg_ms,
+            sample.jitter_ms,
+            (sample.packet_loss_percent + sample.icmp_error_percent).min(100.0),
+            sample.tcp_retransmission_percent,
+        )
     };
+    sample.quality_grade = quality_grade(sample.quality_score).to_string();
+}
 
-    let mut rtts = Vec::with_capacity(count);
-    let mut success_count = 0usize;
-    let mut last_error = 0u32;
-    let mut last_reply_status: Option<u32> = None;
-    let payload = [0u8; 32];
-    let reply_size = (std::mem::size_of::<ICMP_ECHO_REPLY>() + payload.len()) as u32;
+// 综合评分（0-100）：各指标在“优”与“差”阈值之间线性映射为 100-0 分，再按权重加权求和
+// 权重与阈值见 global.rs 中的 QUALITY_* 常量
+fn compute_quality_score(
+    latency_ms: u32,
+    jitter_ms: u32,
+    loss_percent: f64,
+    retransmission_percent: f64,
+) -> u32 {
+    let score = QUALITY_WEIGHT_LATENCY
+        * linear_metric_score(
+            latency_ms as f64,
+            QUALITY_LATENCY_GOOD_MS,
+            QUALITY_LATENCY_BAD_MS,
+        )
+        + QUALITY_WEIGHT_JITTER
+            * linear_metric_score(
+                jitter_ms as f64,
+                QUALITY_JITTER_GOOD_MS,
+                QUALITY_JITTER_BAD_MS,
+            )
+        + QUALITY_WEIGHT_LOSS
+            * linear_metric_score(
+                loss_percent,
+                QUALITY_LOSS_GOOD_PERCENT,
+                QUALITY_LOSS_BAD_PERCENT,
+            )
+        + QUALITY_WEIGHT_RETRANSMISSION
+            * linear_metric_score(
+                retransmission_percent,
+                QUALITY_RETRANSMISSION_GOOD_PERCENT,
+                QUALITY_RETRANSMISSION_BAD_PERCENT,
+            );
+    score.round().clamp(0.0, 100.0) as u32
+}
 
-    for _ in 0..count {
-        let mut reply_buffer = vec![0u8; reply_size as usize];
-        // IcmpSendEcho 的目标 IP 字节序必须使用小端序
-        // 虽然网络字节序为大端序，但是 x86/x64/ARM 架构使用是小端序
-        // 192.168.0.1 被存储为 01 00 A8 C0
-        // 如果以大端序传入，实际ping的是 1.0.168.192
-        let response_count = unsafe {
+// 单项指标评分：不超过 good 为 100 分，不低于 bad 为 0 分，中间线性插值
+fn linear_metric_score(value: f64, good: f64, bad: f64) -> f64 {
+    if value <= good {
+        100.0
+    } else if value >= bad {
+        0.0
+    } else {
+        (bad - value) / (bad - good) * 100.0
+    }
+}
+
+// 评分分档：good / fair / poor
+fn quality_grade(score: u32) -> &'static str {
+    if score >= QUALITY_GRADE_GOOD_MIN_SCORE {
+        QUALITY_GRADE_GOOD
+    } else if score >= QUALITY_GRADE_FAIR_MIN_SCORE {
+        QUALITY_GRADE_FAIR
+    } else {
+        QUALITY_GRADE_POOR
+    }
+}
+
+// 创建 ICMP 句柄，失败时按 ICMP_CREATE_RETRIES 线性退避重试，全部失败返回 None
+fn create_icmp_handle() -> Option<HANDLE> {
+    let retries = ICMP_CREATE_RETRIES.load(Ordering::SeqCst);
+    for attempt in 0..=retries {
+        match unsafe { IcmpCreateFile() } {
+            Ok(handle) => return Some(handle),
+            Err(error) if attempt < retries => {
+                let backoff = ICMP_CREATE_RETRY_BACKOFF * (attempt + 1);
+                report_debug_log!(
+                    "IcmpCreateFile 失败: {}，{}ms 后重试（{}/{}）",
+                    error,
+                    backoff.as_millis(),
+                    attempt + 1,
+                    retries
+                );
+                thread::sleep(backoff);
+            }
+            Err(error) => {
+                report_error_log!("IcmpCreateFile 失败（已重试 {} 次）: {}", retries, error);
+            }
+        }
+    }
+    None
+}
+
+// 发送一次 ICMP Echo，返回应答数（0 表示失败，原因见 GetLastError）
+// IcmpSendEcho 的目标 IP 字节序必须使用小端序
+// 虽然网络字节序为大端序，但是 x86/x64/ARM 架构使用是小端序
+// 192.168.0.1 被存储为 01 00 A8 C0
+// 如果以大端序传入，实际ping的是 1.0.168.192
+// 指定源地址时改用 IcmpSendEcho2Ex 从对应网卡发出，源地址字节序与目标一致
+fn send_icmp_echo(
+    handle: HANDLE,
+    source: Option<Ipv4Addr>,
+    target: Ipv4Addr,
+    payload: &[u8],
+    options: Option<*const IP_OPTION_INFORMATION>,
+    reply_buffer: &mut [u8],
+    timeout: Duration,
+) -> u32 {
+    let timeout_ms = timeout.as_millis() as u32;
+    match source {
+        Some(source) => unsafe {
+            IcmpSendEcho2Ex(
+                handle,
+                None,
+                None,
+                None,
+                u32::from_le_bytes(source.octets()),
+                u32::from_le_bytes(target.octets()),
+                payload.as_ptr().cast(),
+                payload.len() as u16,
+                options,
+                reply_buffer.as_mut_ptr().cast(),
+                reply_buffer.len() as u32,
+                timeout_ms,
+            )
+        },
+        None => unsafe {
             IcmpSendEcho(
                 handle,
                 u32::from_le_bytes(target.octets()),
                 payload.as_ptr().cast(),
                 payload.len() as u16,
-                None,
+                options,
                 reply_buffer.as_mut_ptr().cast(),
-                reply_size,
+                reply_buffer.len() as u32,
                 timeout_ms,
             )
-        };
+        },
+    }
+}
+
+// 超时升级只针对周期内的首个 Echo，且仅在确实超时（而非差错应答）时触发，每个周期最多一次
+fn should_escalate_echo_timeout(
+    enabled: bool,
+    sequence: u32,
+    response_count: u32,
+    last_error: u32,
+) -> bool {
+    enabled && sequence == 0 && response_count == 0 && last_error == IP_REQ_TIMED_OUT
+}
+
+// 单个 Echo 的发送参数，串行与并发发送共用
+struct EchoRequest {
+    handle: IcmpHandle,
+    source: Option<Ipv4Addr>,
+    target: Ipv4Addr,
+    payload_len: usize,
+    reply_size: usize,
+    dont_fragment: bool,
+    timeout: Duration,
+    deadline: Instant,
+}
+
+// 并发模式下由各工作线程共享的 ICMP 句柄
+// IcmpCreateFile 返回的是 ICMP 驱动的文件句柄，IcmpSendEcho / IcmpSendEcho2Ex 每次调用各自发起独立的
+// 驱动请求并使用调用方传入的请求与回包缓冲区，不修改句柄本身的状态；IcmpSendEcho2 提供的异步调用方式
+// （传入 Event 或 ApcRoutine 时立即返回）本身就允许同一句柄上同时存在多个未完成的请求。句柄只在全部工作线程结束（run_bounded 返回）后才由 IcmpCloseHandle 关闭
+struct IcmpHandle(HANDLE);
+
+unsafe impl Send for IcmpHandle {}
+unsafe impl Sync for IcmpHandle {}
+
+// 单个应答的状态与往返时间：ICMP_ECHO_REPLY 含指向回包缓冲区的指针，不跨线程传递
+struct EchoReply {
+    status: u32,
+    round_trip_ms: u32,
+}
+
+// 单个 Echo 的结果，测量结束后按序号顺序汇总
+struct EchoOutcome {
+    sequence: u32,
+    response_count: u32,
+    replies: Vec<EchoReply>,
+    // 无应答时的 GetLastError
+    last_error: u32,
+    // 成功应答回显的序号，用于识别乱序
+    echoed_sequence: Option<u32>,
+}
+
+// 发送一个带序号的 Echo，超过 deadline 时不再发送并返回 None
+fn send_sequenced_echo(request: &EchoRequest, sequence: u32) -> Option<EchoOutcome> {
+    let echo_timeout = clamp_echo_timeout(request.timeout, request.deadline, Instant::now())?;
+    // 载荷头部写入本次 Echo 的序号，应答回显后用于识别乱序
+    let mut payload = vec![0u8; request.payload_len];
+    encode_echo_sequence(&mut payload, sequence);
+    // 默认不传 IP 选项（与系统 ping 一致）；开启 DF 后报文超过路径 MTU 时由路由器返回 IP_PACKET_TOO_BIG
+    let options = IP_OPTION_INFORMATION {
+        Ttl: DEFAULT_PING_TTL,
+        Flags: IP_FLAG_DF as u8,
+        ..Default::default()
+    };
+    let options = request
+        .dont_fragment
+        .then_some(&options as *const IP_OPTION_INFORMATION);
+    let mut reply_buffer = vec![0u8; request.reply_size];
+    let mut response_count = send_icmp_echo(
+        request.handle.0,
+        request.source,
+        request.target,
+        &payload,
+        options,
+        &mut reply_buffer,
+        echo_timeout,
+    );
+    let mut last_error = unsafe { GetLastError().0 };
+    // 周期内首个 Echo 超时时以加倍超时重发一次，避免偶发的慢应答被计为丢包
+    if should_escalate_echo_timeout(
+        ICMP_TIMEOUT_ESCALATION.load(Ordering::SeqCst),
+        sequence,
+        response_count,
+        last_error,
+    ) && let Some(escalated_timeout) =
+        clamp_echo_timeout(request.timeout * 2, request.deadline, Instant::now())
+    {
+        report_debug_log!(
+            "ICMP 首个 Echo 超时，以 {}ms 超时重发一次：target={}",
+            escalated_timeout.as_millis(),
+            request.target
+        );
+        reply_buffer.fill(0);
+        response_count = send_icmp_echo(
+            request.handle.0,
+            request.source,
+            request.target,
+            &payload,
+            options,
+            &mut reply_buffer,
+            escalated_timeout,
+        );
+        last_error = unsafe { GetLastError().0 };
+    }
+
+    let replies = parse_icmp_replies(&reply_buffer, response_count);
+    // 只有成功应答携带回显载荷，差错应答的 Data 为原始报文头部
+    let echoed_sequence = replies
+        .first()
+        .filter(|reply| reply.Status == ERROR_SUCCESS.0)
+        .and_then(|reply| echo_reply_data(&reply_buffer, reply))
+        .and_then(decode_echo_sequence);
+    let replies = replies
+        .iter()
+        .map(|reply| EchoReply {
+            status: reply.Status,
+            round_trip_ms: reply.RoundTripTime,
+        })
+        .collect();
+    Some(EchoOutcome {
+        sequence,
+        response_count,
+        replies,
+        last_error,
+        echoed_sequence,
+    })
+}
+
+// 计算指定目标的延迟与丢包率
+// 超过 deadline 后放弃剩余 Echo，丢包率按实际发出的次数计算
+// 默认逐个串行发送 Echo，抖动测量更稳定；ICMP_ECHO_CONCURRENCY 大于 1 时并发发送，
+// 更贴近突发流量的丢包特征并缩短周期耗时
+fn measure_latency_and_loss(
+    target: Ipv4Addr,
+    count: usize,
+    timeout_ms: u32,
+    deadline: Instant,
+) -> Option<PingStats> {
+    let handle = create_icmp_handle()?;
+    let payload_len = PING_PAYLOAD_SIZE.load(Ordering::SeqCst) as usize;
+    let request = EchoRequest {
+        handle: IcmpHandle(handle),
+        source: current_probe_source_address(),
+        target,
+        payload_len,
+        reply_size: icmp_reply_buffer_size(payload_len, ICMP_MAX_REPLIES),
+        dont_fragment: PING_DONT_FRAGMENT.load(Ordering::SeqCst),
+        timeout: Duration::from_millis(timeout_ms as u64),
+        deadline,
+    };
+    let concurrency = ICMP_ECHO_CONCURRENCY.load(Ordering::SeqCst) as usize;
+    // 达到测量时限而未发出的序号按跳过计数：串行模式在首个跳过处停止，并发模式下各工作线程
+    // 按实际发送时刻判断，跳过的序号不一定位于末尾，逐个统计后只汇总实际发出的 Echo
+    let (outcomes, skipped): (Vec<EchoOutcome>, usize) = if concurrency > 1 {
+        let sequences: Vec<u32> = (0..count as u32).collect();
+        let results = run_bounded(&sequences, concurrency, |&sequence| {
+            send_sequenced_echo(&request, sequence)
+        });
+        let skipped = results.iter().filter(|result| result.is_none()).count();
+        (results.into_iter().flatten().collect(), skipped)
+    } else {
+        let outcomes: Vec<EchoOutcome> = (0..count as u32)
+            .map_while(|sequence| send_sequenced_echo(&request, sequence))
+            .collect();
+        let skipped = count - outcomes.len();
+        (outcomes, skipped)
+    };
+
+    let _ = unsafe { IcmpCloseHandle(handle) };
+
+    // 丢包率按实际发出的 Echo 计算，与串行模式一致
+    let echo_count = count - skipped;
+    if skipped > 0 {
+        report_info_log!(
+            "ICMP 探测达到测量时限，放弃剩余 Echo：target={} 已发送={}/{} 跳过={}",
+            target,
+            echo_count,
+            count,
+            skipped
+        );
+    }
+
+    let mut rtts = Vec::with_capacity(count);
+    let mut success_count = 0usize;
+    // 返回 ICMP 差错（TTL 超时、目标不可达等）的 Echo 数：属于可达性问题，不计入丢包
+    let mut icmp_error_count = 0usize;
+    let mut last_error = 0u32;
+    let mut last_reply_status: Option<u32> = None;
+    let mut packet_too_big = false;
+    let mut last_status: Option<u32> = None;
+    // 收到多个应答的 Echo 次数与最近一次的应答状态，测量结束后汇总输出一次日志
+    let mut duplicate_echoes = 0usize;
+    let mut duplicate_statuses: Vec<u32> = Vec::new();
+    let mut out_of_order_count = 0usize;
 
-        if response_count > 0 {
-            let reply = unsafe { &*(reply_buffer.as_ptr() as *const ICMP_ECHO_REPLY) };
-            last_reply_status = Some(reply.Status);
-            if reply.Status == ERROR_SUCCESS.0 {
-                rtts.push(reply.RoundTripTime);
+    for outcome in &outcomes {
+        let replies = &outcome.replies;
+        if replies.len() > 1 || outcome.response_count as usize > replies.len() {
+            duplicate_echoes += 1;
+            duplicate_statuses = replies.iter().map(|reply| reply.status).collect();
+        }
+        out_of_order_count += count_out_of_order_replies(
+            outcome.sequence,
+            outcome.echoed_sequence,
+            (outcome.response_count as usize).max(replies.len()),
+        );
+        // 多个应答时仍以第一个应答作为本次 Echo 的结果
+        if let Some(reply) = replies.first() {
+            last_reply_status = Some(reply.status);
+            last_status = Some(reply.status);
+            packet_too_big |= reply.status == IP_PACKET_TOO_BIG;
+            if reply.status == ERROR_SUCCESS.0 {
+                rtts.push(reply.round_trip_ms);
                 success_count += 1;
+            } else if is_icmp_error_status(reply.status) {
+                icmp_error_count += 1;
             }
         } else {
-            last_error = unsafe { GetLastError().0 };
+            last_error = outcome.last_error;
+            last_status = Some(last_error);
+            packet_too_big |= last_error == IP_PACKET_TOO_BIG;
+            // 部分 ICMP 差错（如目标不可达）以返回 0 + GetLastError 的形式报告
+            if is_icmp_error_status(last_error) {
+                icmp_error_count += 1;
+            }
         }
     }
 
-    let _ = unsafe { IcmpCloseHandle(handle) };
+    if out_of_order_count > 0 {
+        report_info_log!(
+            "ICMP 探测检测到乱序或重复应答：target={} 次数={} 已发送={}",
+            target,
+            out_of_order_count,
+            echo_count
+        );
+    }
+    if duplicate_echoes > 0 {
+        report_info_log!(
+            "ICMP 探测收到多个应答，可能存在重复应答或网络环路：target={} 次数={}/{} 最近应答状态={:?}",
+            target,
+            duplicate_echoes,
+            echo_count,
+            duplicate_statuses
+        );
+    }
+
+    let mut stats = build_ping_stats(
+        &rtts,
+        echo_count,
+        success_count,
+        icmp_error_count,
+        last_error,
+        last_reply_status,
+    );
+    stats.packet_too_big = packet_too_big;
+    stats.last_status = last_status;
+    stats.out_of_order_count = out_of_order_count;
+    Some(stats)
+}
+
+// 将 Echo 序号以小端序写入载荷头部，载荷不足 ECHO_SEQUENCE_LEN 字节时不写入（不做乱序检测）
+fn encode_echo_sequence(payload: &mut [u8], sequence: u32) {
+    if let Some(head) = payload.get_mut(..ECHO_SEQUENCE_LEN) {
+        head.copy_from_slice(&sequence.to_le_bytes());
+    }
+}
+
+// 从回显数据头部读取 Echo 序号
+fn decode_echo_sequence(data: &[u8]) -> Option<u32> {
+    data.get(..ECHO_SEQUENCE_LEN)
+        .map(|head| u32::from_le_bytes(head.try_into().unwrap()))
+}
+
+// 定位应答的回显数据：Data 指向回包缓冲区内部，为空或越界时返回 None
+fn echo_reply_data<'a>(buffer: &'a [u8], reply: &ICMP_ECHO_REPLY) -> Option<&'a [u8]> {
+    let start = (reply.Data as usize).checked_sub(buffer.as_ptr() as usize)?;
+    buffer.get(start..start.checked_add(reply.DataSize as usize)?)
+}
+
+// 单次 Echo 的乱序/重复计数：首个应答的回显序号与发送序号不符记 1 次乱序，
+// 第一个之后的每个应答各记 1 次重复；无法读取回显序号时只统计重复
+fn count_out_of_order_replies(sequence: u32, echoed: Option<u32>, reply_count: usize) -> usize {
+    let reordered = echoed.is_some_and(|echoed| echoed != sequence) as usize;
+    reordered + reply_count.saturating_sub(1)
+}
+
+// IP_STATUS 转可读名称（去掉 IP_ 前缀的小写形式），未收录的取值输出 "status_<code>"
+fn ip_status_name(status: u32) -> String {
+    const NAMES: [(u32, &str); 20] = [
+        (IP_SUCCESS, "success"),
+        (IP_BUF_TOO_SMALL, "buf_too_small"),
+        (IP_DEST_NET_UNREACHABLE, "dest_net_unreachable"),
+        (IP_DEST_HOST_UNREACHABLE, "dest_host_unreachable"),
+        (IP_DEST_PROT_UNREACHABLE, "dest_prot_unreachable"),
+        (IP_DEST_PORT_UNREACHABLE, "dest_port_unreachable"),
+        (IP_NO_RESOURCES, "no_resources"),
+        (IP_BAD_OPTION, "bad_option"),
+        (IP_HW_ERROR, "hw_error"),
+        (IP_PACKET_TOO_BIG, "packet_too_big"),
+        (IP_REQ_TIMED_OUT, "req_timed_out"),
+        (IP_BAD_REQ, "bad_req"),
+        (IP_BAD_ROUTE, "bad_route"),
+        (IP_TTL_EXPIRED_TRANSIT, "ttl_expired_transit"),
+        (IP_TTL_EXPIRED_REASSEM, "ttl_expired_reassem"),
+        (IP_PARAM_PROBLEM, "param_problem"),
+        (IP_SOURCE_QUENCH, "source_quench"),
+        (IP_OPTION_TOO_BIG, "option_too_big"),
+        (IP_BAD_DESTINATION, "bad_destination"),
+        (IP_GENERAL_FAILURE, "general_failure"),
+    ];
+    NAMES
+        .iter()
+        .find(|(code, _)| *code == status)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| format!("status_{}", status))
+}
+
+// ICMP 差错状态（IP_STATUS_BASE 之上、排除超时）：TTL 超时、目标/网络/端口不可达等
+fn is_icmp_error_status(status: u32) -> bool {
+    status > IP_STATUS_BASE && status <= MAX_IP_STATUS && status != IP_REQ_TIMED_OUT
+}
+
+// 每个应答需容纳 ICMP_ECHO_REPLY、回显载荷，以及 ICMP 差错报文附带的 8 字节
+fn icmp_reply_buffer_size(payload_len: usize, replies: usize) -> usize {
+    (std::mem::size_of::<ICMP_ECHO_REPLY>() + payload_len + ICMP_ERROR_MESSAGE_SIZE) * replies
+}
+
+// 解析回包缓冲区头部连续存放的 ICMP_ECHO_REPLY 数组（回显数据位于数组之后）
+// Vec<u8> 不保证结构体对齐，逐个按非对齐读取；应答数超过缓冲区容量时只解析能容纳的部分
+fn parse_icmp_replies(buffer: &[u8], response_count: u32) -> Vec<ICMP_ECHO_REPLY> {
+    let reply_len = std::mem::size_of::<ICMP_ECHO_REPLY>();
+    let count = (response_count as usize).min(buffer.len() / reply_len);
+    (0..count)
+        .map(|index| unsafe {
+            std::ptr::read_unaligned(
+                buffer.as_ptr().add(index * reply_len) as *const ICMP_ECHO_REPLY
+            )
+        })
+        .collect()
+}
 
+// 校验 ICMP 载荷大小：超过上限时 IcmpSendEcho 的 u16 请求长度与回包缓冲区计算都会失真
+pub fn is_valid_ping_payload_size(size: u32) -> bool {
+    size <= MAX_PING_PAYLOAD_SIZE
+}
+
+// 由单次周期内的 RTT 序列汇总延迟、抖动与丢包指标，ICMP 与 TCP 握手探测共用
+// 丢包只统计无应答（超时）的 Echo，返回 ICMP 差错的 Echo 单独计入 icmp_error_percent
+fn build_ping_stats(
+    rtts: &[u32],
+    count: usize,
+    success_count: usize,
+    icmp_error_count: usize,
+    last_error: u32,
+    last_reply_status: Option<u32>,
+) -> PingStats {
+    // 一次 Echo 都未发出（如目标解析失败）时按全部丢包处理
+    let (loss_percent, icmp_error_percent) = if count == 0 {
+        (100.0, 0.0)
+    } else {
+        let timeout_count = count.saturating_sub(success_count + icmp_error_count);
+        (
+            (timeout_count as f64 / count as f64) * 100.0,
+            (icmp_error_count as f64 / count as f64) * 100.0,
+        )
+    };
     if rtts.is_empty() {
-        return Some(PingStats {
+        return PingStats {
             avg_ms: 0,
             min_ms: 0,
             max_ms: 0,
+            p50_ms: 0,
+            p95_ms: 0,
+            p99_ms: 0,
             jitter_ms: 0,
-            loss_percent: 100.0,
+            jitter_stddev_ms: 0,
+            jitter_rfc3550_ms: 0.0,
+            loss_percent,
+            icmp_error_percent,
             success_count,
+            echo_count: count,
             last_error,
             last_reply_status,
-        });
+            packet_too_big: false,
+            last_status: None,
+            out_of_order_count: 0,
+            icmp_blocked: false,
+            icmp_unavailable: false,
+        };
     }
 
     let min_ms = *rtts.iter().min().unwrap();
     let max_ms = *rtts.iter().max().unwrap();
     let sum: u32 = rtts.iter().copied().sum();
     let avg_ms = sum / rtts.len() as u32;
-    let jitter_ms = compute_jitter(&rtts);
-    let failure_count = count.saturating_sub(success_count);
-    let loss_percent = (failure_count as f64 / count as f64) * 100.0;
+    let jitter_ms = compute_jitter(rtts);
+    let mut sorted = rtts.to_vec();
+    sorted.sort_unstable();
 
-    Some(PingStats {
+    PingStats {
         avg_ms,
         min_ms,
         max_ms,
+        p50_ms: compute_percentile(&sorted, 50, max_ms),
+        p95_ms: compute_percentile(&sorted, 95, max_ms),
+        p99_ms: compute_percentile(&sorted, 99, max_ms),
         jitter_ms,
+        jitter_stddev_ms: compute_jitter_stddev(rtts),
+        jitter_rfc3550_ms: compute_jitter_rfc3550(rtts),
         loss_percent,
+        icmp_error_percent,
         success_count,
+        echo_count: count,
         last_error,
         last_reply_status,
-    })
+        packet_too_big: false,
+        last_status: None,
+        out_of_order_count: 0,
+        icmp_blocked: false,
+        icmp_unavailable: false,
+    }
+}
+
+// 按 ±percent% 扰动探测间隔，offset 为 [-1, 1] 的随机数；percent 为 0 时保持原间隔
+fn jitter_probe_interval(interval: Duration, percent: u32, offset: f64) -> Duration {
+    if percent == 0 {
+        return interval;
+    }
+    let factor = 1.0
+        + offset.clamp(-1.0, 1.0) * percent.min(MAX_PROBE_INTERVAL_JITTER_PERCENT) as f64 / 100.0;
+    interval.mul_f64(factor)
+}
+
+// 最近秩法 (nearest-rank) 计算百分位：rank = ceil(p / 100 * N)，取升序序列的第 rank 个值
+// 样本为空或秩超出范围时回退到 max_ms
+fn compute_percentile(sorted: &[u32], percentile: u32, max_ms: u32) -> u32 {
+    let rank = (percentile as usize * sorted.len()).div_ceil(100);
+    if rank == 0 {
+        return max_ms;
+    }
+    sorted.get(rank - 1).copied().unwrap_or(max_ms)
 }
 
 // 计算简单抖动指标：相邻 RTT 差值的平均值
@@ -253,22 +2156,62 @@ fn compute_jitter(rtts: &[u32]) -> u32 {
     sum / (rtts.len() as u32 - 1)
 }
 
+// 按 RFC 3550 计算到达间隔抖动：D 取相邻 RTT 之差，J 从 0 开始以 1/16 的增益平滑
+// $J_i = J_{i-1} + \frac{|D_i| - J_{i-1}}{16}$，保留一位小数
+fn compute_jitter_rfc3550(rtts: &[u32]) -> f64 {
+    let jitter = rtts.windows(2).fold(0.0, |jitter: f64, pair| {
+        let diff = pair[0].abs_diff(pair[1]) as f64;
+        jitter + (diff - jitter) / 16.0
+    });
+    (jitter * 10.0).round() / 10.0
+}
+
+// 计算 RTT 样本的总体标准差，作为对突发波动更敏感的抖动指标
+// $\sigma = \sqrt{\frac{\sum_{i=1}^{N} (RTT_i - \mu)^2}{N}}$
+fn compute_jitter_stddev(rtts: &[u32]) -> u32 {
+    if rtts.len() < 2 {
+        return 0;
+    }
+    let count = rtts.len() as f64;
+    let mean = rtts.iter().map(|&rtt| rtt as f64).sum::<f64>() / count;
+    let variance = rtts
+        .iter()
+        .map(|&rtt| {
+            let diff = rtt as f64 - mean;
+            diff * diff
+        })
+        .sum::<f64>()
+        / count;
+    variance.sqrt().round() as u32
+}
+
 fn measure_tcp_handshake_rtt(
-    target: &str,
-    port: u16,
+    addr: SocketAddr,
     count: usize,
     timeout: Duration,
-) -> Option<PingStats> {
-    let addrs = resolve_ipv4_socket_addrs(target, port)?;
-    let addr = addrs.first().copied()?;
+    deadline: Instant,
+) -> PingStats {
+    let source = current_probe_source_address();
+
+    if is_persistent_rtt_port(addr.port()) {
+        if let Some(stats) = measure_persistent_tcp_rtt(source, addr, count, timeout, deadline) {
+            return stats;
+        }
+        report_debug_log!("TCP 长连接不可用，回退为握手 RTT 探测：{}", addr);
+    }
 
     let mut rtts = Vec::with_capacity(count);
     let mut success_count = 0usize;
     let mut last_error = 0u32;
+    let mut echo_count = 0usize;
 
     for _ in 0..count {
         let start_at = Instant::now();
-        match TcpStream::connect_timeout(&addr.into(), timeout) {
+        let Some(timeout) = clamp_echo_timeout(timeout, deadline, start_at) else {
+            break;
+        };
+        echo_count += 1;
+        match connect_tcp(source, addr, timeout) {
             Ok(stream) => {
                 let _ = stream.shutdown(std::net::Shutdown::Both);
                 let elapsed_ms = start_at.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
@@ -280,59 +2223,236 @@ fn measure_tcp_handshake_rtt(
             }
         }
     }
-
-    if rtts.is_empty() {
-        return Some(PingStats {
-            avg_ms: 0,
-            min_ms: 0,
-            max_ms: 0,
-            jitter_ms: 0,
-            loss_percent: 100.0,
-            success_count,
-            last_error,
-            last_reply_status: None,
-        });
-    }
-
-    let min_ms = *rtts.iter().min().unwrap();
-    let max_ms = *rtts.iter().max().unwrap();
-    let sum: u32 = rtts.iter().copied().sum();
-    let avg_ms = sum / rtts.len() as u32;
-    let jitter_ms = compute_jitter(&rtts);
-    let failure_count = count.saturating_sub(success_count);
-    let loss_percent = (failure_count as f64 / count as f64) * 100.0;
-
-    Some(PingStats {
-        avg_ms,
-        min_ms,
-        max_ms,
-        jitter_ms,
-        loss_percent,
-        success_count,
-        last_error,
-        last_reply_status: None,
-    })
+
+    build_ping_stats(&rtts, echo_count, success_count, 0, last_error, None)
+}
+
+// 在保持的长连接上测量 RTT：每次 Echo 发送 1 字节，按墙钟计时到对端确认为止，抖动与分位数据此计算。
+// 连接不存在时先建立；建连失败或连接在首个 Echo 前断开时返回 None，由调用方回退为握手测量
+fn measure_persistent_tcp_rtt(
+    source: Option<Ipv4Addr>,
+    addr: SocketAddr,
+    count: usize,
+    timeout: Duration,
+    deadline: Instant,
+) -> Option<PingStats> {
+    // 测量期间不持锁，避免按需探测与周期探测互相阻塞；同一地址并发测量时各自建连
+    let cached = PERSISTENT_TCP_CONNECTIONS
+        .lock()
+        .unwrap()
+        .as_mut()
+        .and_then(|connections| connections.remove(&addr));
+    let stream = match cached {
+        Some(stream) => stream,
+        None => match open_persistent_tcp(source, addr, timeout) {
+            Ok(stream) => {
+                report_info_log!("已建立 TCP 长连接用于 RTT 探测：{}", addr);
+                stream
+            }
+            Err(error) => {
+                report_debug_log!("建立 TCP 长连接失败：{} {}", addr, error);
+                return None;
+            }
+        },
+    };
+
+    let mut rtts = Vec::with_capacity(count);
+    let mut echo_count = 0usize;
+    let mut dropped = false;
+    for _ in 0..count {
+        let start_at = Instant::now();
+        let Some(timeout) = clamp_echo_timeout(timeout, deadline, start_at) else {
+            break;
+        };
+        echo_count += 1;
+        match persistent_tcp_round_trip(&stream, timeout) {
+            Ok(Some(rtt_ms)) => rtts.push(rtt_ms),
+            // 超时未确认按丢包计
+            Ok(None) => {}
+            Err(error) => {
+                report_info_log!("TCP 长连接已断开：{} {}", addr, error);
+                echo_count -= 1;
+                dropped = true;
+                break;
+            }
+        }
+    }
+
+    if dropped {
+        if echo_count == 0 {
+            return None;
+        }
+    } else if TCP_PERSISTENT_RTT.load(Ordering::SeqCst) {
+        PERSISTENT_TCP_CONNECTIONS
+            .lock()
+            .unwrap()
+            .get_or_insert_with(HashMap::new)
+            .insert(addr, stream);
+    }
+    Some(build_ping_stats(&rtts, echo_count, rtts.len(), 0, 0, None))
+}
+
+// 建立用于 RTT 探测的长连接：关闭 Nagle 使 1 字节探测立即发出，开启保活以便及时发现对端失联
+fn open_persistent_tcp(
+    source: Option<Ipv4Addr>,
+    addr: SocketAddr,
+    timeout: Duration,
+) -> std::io::Result<TcpStream> {
+    let stream = connect_tcp(source, addr, timeout)?;
+    stream.set_nodelay(true)?;
+    SockRef::from(&stream).set_tcp_keepalive(
+        &TcpKeepalive::new()
+            .with_time(PERSISTENT_TCP_KEEPALIVE_TIME)
+            .with_interval(PERSISTENT_TCP_KEEPALIVE_INTERVAL),
+    )?;
+    Ok(stream)
+}
+
+// 长连接模式已开启且端口被标记为可容忍探测字节
+fn is_persistent_rtt_port(port: u16) -> bool {
+    TCP_PERSISTENT_RTT.load(Ordering::SeqCst)
+        && TCP_PERSISTENT_RTT_PORTS.lock().unwrap().contains(&port)
+}
+
+// 发送 1 字节并轮询 SIO_TCP_INFO，按墙钟计时到该字节被确认为止，返回本次往返耗时（毫秒，含对端的延迟确认）；
+// 超时未确认返回 Ok(None)，连接不再处于 ESTABLISHED 或发送失败时返回错误
+fn persistent_tcp_round_trip(
+    stream: &TcpStream,
+    timeout: Duration,
+) -> std::io::Result<Option<u32>> {
+    let info = query_tcp_info(stream)?;
+    if info.State != TCPSTATE_ESTABLISHED {
+        return Err(std::io::Error::from(std::io::ErrorKind::ConnectionReset));
+    }
+    let bytes_out_before = info.BytesOut;
+    let started_at = Instant::now();
+    let mut writer = stream;
+    writer.write_all(&[0])?;
+    loop {
+        let info = query_tcp_info(stream)?;
+        if info.State != TCPSTATE_ESTABLISHED {
+            return Err(std::io::Error::from(std::io::ErrorKind::ConnectionReset));
+        }
+        if is_probe_byte_acked(bytes_out_before, info.BytesOut, info.BytesInFlight) {
+            let elapsed_ms = started_at.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
+            return Ok(Some(elapsed_ms));
+        }
+        if started_at.elapsed() >= timeout {
+            return Ok(None);
+        }
+        thread::sleep(PERSISTENT_TCP_POLL_INTERVAL);
+    }
+}
+
+// 探测字节已离开发送缓冲区（BytesOut 增加）且没有在途字节时才算被确认：
+// 仅看 BytesInFlight == 0 会把尚未发出的字节误判为已确认
+fn is_probe_byte_acked(bytes_out_before: u64, bytes_out: u64, bytes_in_flight: u32) -> bool {
+    bytes_out > bytes_out_before && bytes_in_flight == 0
+}
+
+// 通过 SIO_TCP_INFO 读取连接的 TCP 状态与 RTT 估计（Windows 10 1703 起支持）
+fn query_tcp_info(stream: &TcpStream) -> std::io::Result<TCP_INFO_v0> {
+    let version = 0u32;
+    let mut info = TCP_INFO_v0::default();
+    let mut returned = 0u32;
+    let result = unsafe {
+        WSAIoctl(
+            SOCKET(stream.as_raw_socket() as usize),
+            SIO_TCP_INFO,
+            Some(&version as *const u32 as *const c_void),
+            size_of::<u32>() as u32,
+            Some(&mut info as *mut TCP_INFO_v0 as *mut c_void),
+            size_of::<TCP_INFO_v0>() as u32,
+            &mut returned,
+            None,
+            None,
+        )
+    };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(info)
+}
+
+// 关闭全部 TCP 长连接：停止探测或关闭长连接模式时调用，下次探测重新建立
+pub fn close_persistent_tcp_connections() {
+    PERSISTENT_TCP_CONNECTIONS.lock().unwrap().take();
+}
+
+// 建立 TCP 连接：指定源地址时先绑定到该地址（端口由系统分配）再连接，否则按系统默认路由
+// 源地址仅支持 IPv4，IPv6 目标忽略源地址配置
+fn connect_tcp(
+    source: Option<Ipv4Addr>,
+    addr: SocketAddr,
+    timeout: Duration,
+) -> std::io::Result<TcpStream> {
+    let Some(source) = source.filter(|_| addr.is_ipv4()) else {
+        return TcpStream::connect_timeout(&addr, timeout);
+    };
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, Some(Protocol::TCP))?;
+    socket.bind(&SocketAddrV4::new(source, 0).into())?;
+    socket.connect_timeout(&addr.into(), timeout)?;
+    Ok(socket.into())
+}
+
+// 读取探测源地址配置，未指定时返回 None；仅 WiFi 模式下取 WiFi 网卡地址
+fn current_probe_source_address() -> Option<Ipv4Addr> {
+    if PROBE_WIFI_ONLY.load(Ordering::SeqCst) {
+        return current_wifi_source_address();
+    }
+    let source = PROBE_SOURCE_ADDRESS.load(Ordering::SeqCst);
+    (source != 0).then(|| Ipv4Addr::from(source))
+}
+
+// 取当前已连接 WLAN 接口对应网卡的 IPv4 地址；WiFi 不可用时返回 None（按系统默认路由探测），
+// 不可用与恢复各提示一次，避免误以为数据来自 WiFi
+fn current_wifi_source_address() -> Option<Ipv4Addr> {
+    let interface_guid = LAST_WLAN_STATUS
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|status| status.interface_guid.clone());
+    let source = interface_guid.and_then(|guid| adapters::wifi_source_address(&guid));
+    match source {
+        Some(source) => {
+            if WIFI_SOURCE_UNAVAILABLE.swap(false, Ordering::SeqCst) {
+                report_info_log!("WiFi 已可用，探测重新绑定到 WiFi 网卡：{}", source);
+            }
+        }
+        None => {
+            if !WIFI_SOURCE_UNAVAILABLE.swap(true, Ordering::SeqCst) {
+                report_info_log!(
+                    "已开启仅 WiFi 探测，但 WiFi 当前不是可用网卡（未连接或没有 IPv4 地址），按系统默认路由探测，采样的 wifiBound 为 false"
+                );
+            }
+        }
+    }
+    source
 }
 
 // 读取系统 TCP 统计并计算重传率
 fn query_tcp_stats() -> Option<TcpStats> {
-    let (current_sent, current_retrans) = read_tcp_counters()?;
+    let counters = read_tcp_counters()?;
+    let (current_sent, current_retrans) = (counters.out_segs, counters.retrans_segs);
     let baseline_lock = TCP_STATS_BASELINE.get_or_init(|| Mutex::new(None));
     let mut baseline = baseline_lock.lock().unwrap();
     let previous = *baseline;
-    let stats = compute_interval_tcp_stats(&mut baseline, (current_sent, current_retrans));
+    let mut stats = compute_interval_tcp_stats(&mut baseline, (current_sent, current_retrans));
 
-    if cfg!(debug_assertions) {
-        report_info_log!(
-            "TCP 重传率（周期内）：prev={:?} curr=({},{}) delta=({},{}) percent={:.6}%",
-            previous,
-            current_sent,
-            current_retrans,
-            stats.segments_sent,
-            stats.segments_retransmitted,
-            stats.retransmission_percent
-        );
-    }
+    let failures_lock = TCP_FAILURES_BASELINE.get_or_init(|| Mutex::new(None));
+    stats.connection_failures =
+        compute_counter_delta(&mut failures_lock.lock().unwrap(), counters.attempt_fails);
+    stats.current_established = counters.curr_estab;
+
+    report_debug_log!(
+        "TCP 重传率（周期内）：prev={:?} curr=({},{}) delta=({},{}) percent={:.6}%",
+        previous,
+        current_sent,
+        current_retrans,
+        stats.segments_sent,
+        stats.segments_retransmitted,
+        stats.retransmission_percent
+    );
 
     Some(stats)
 }
@@ -343,11 +2463,18 @@ fn init_tcp_stats_baseline() {
     if baseline.is_some() {
         return;
     }
-    if let Some((sent, retrans)) = read_tcp_counters() {
-        *baseline = Some((sent, retrans));
-        if cfg!(debug_assertions) {
-            report_info_log!("TCP 重传率（周期开始）：baseline=({},{})", sent, retrans);
-        }
+    if let Some(counters) = read_tcp_counters() {
+        *baseline = Some((counters.out_segs, counters.retrans_segs));
+        *TCP_FAILURES_BASELINE
+            .get_or_init(|| Mutex::new(None))
+            .lock()
+            .unwrap() = Some(counters.attempt_fails);
+        report_debug_log!(
+            "TCP 重传率（周期开始）：baseline=({},{}) attempt_fails={}",
+            counters.out_segs,
+            counters.retrans_segs,
+            counters.attempt_fails
+        );
     }
 }
 
@@ -355,36 +2482,53 @@ fn reset_tcp_stats_baseline() {
     if let Some(lock) = TCP_STATS_BASELINE.get() {
         *lock.lock().unwrap() = None;
     }
+    if let Some(lock) = TCP_FAILURES_BASELINE.get() {
+        *lock.lock().unwrap() = None;
+    }
 }
 
-fn read_tcp_counters() -> Option<(i64, i64)> {
+fn read_tcp_counters() -> Option<TcpCounters> {
     let mut stats = MIB_TCPSTATS_LH::default();
     let result = unsafe { GetTcpStatisticsEx(&mut stats, IP_FAMILY_IPV4) };
     if result != ERROR_SUCCESS.0 {
-        report_error_log!("GetTcpStatisticsEx 失败: {:?}", WIN32_ERROR(result));
+        if !TCP_STATS_QUERY_WARNED.swap(true, Ordering::SeqCst) {
+            report_error_log!(
+                "GetTcpStatisticsEx 失败: {:?}，TCP 统计不可用，采样的 tcpStatsAvailable 为 false",
+                WIN32_ERROR(result)
+            );
+        }
         return None;
     }
-    Some((stats.dwOutSegs as i64, stats.dwRetransSegs as i64))
+    if TCP_STATS_QUERY_WARNED.swap(false, Ordering::SeqCst) {
+        report_info_log!("GetTcpStatisticsEx 已恢复，TCP 统计重新可用");
+    }
+    Some(TcpCounters {
+        out_segs: stats.dwOutSegs as i64,
+        retrans_segs: stats.dwRetransSegs as i64,
+        attempt_fails: stats.dwAttemptFails as i64,
+        curr_estab: stats.dwCurrEstab as i64,
+    })
+}
+
+// 计算单个累计计数器在周期内的增量：首次采样或计数器回绕/重置时重建基线并返回 0
+fn compute_counter_delta(baseline: &mut Option<i64>, current: i64) -> i64 {
+    let previous = baseline.replace(current);
+    match previous {
+        Some(previous) if current >= previous => current - previous,
+        _ => 0,
+    }
 }
 
 fn compute_interval_tcp_stats(baseline: &mut Option<(i64, i64)>, current: (i64, i64)) -> TcpStats {
     let (current_sent, current_retrans) = current;
     let Some((prev_sent, prev_retrans)) = *baseline else {
         *baseline = Some(current);
-        return TcpStats {
-            retransmission_percent: 0.0,
-            segments_sent: 0,
-            segments_retransmitted: 0,
-        };
+        return TcpStats::default();
     };
 
     if current_sent < prev_sent || current_retrans < prev_retrans {
         *baseline = Some(current);
-        return TcpStats {
-            retransmission_percent: 0.0,
-            segments_sent: 0,
-            segments_retransmitted: 0,
-        };
+        return TcpStats::default();
     }
 
     let delta_sent = current_sent - prev_sent;
@@ -397,34 +2541,105 @@ fn compute_interval_tcp_stats(baseline: &mut Option<(i64, i64)>, current: (i64,
         retransmission_percent,
         segments_sent: delta_sent,
         segments_retransmitted: delta_retrans,
+        ..TcpStats::default()
     }
 }
 
-fn resolve_ipv4_target(target: &str) -> Option<Ipv4Addr> {
-    if let Ok(ipv4) = target.parse::<Ipv4Addr>() {
-        return Some(ipv4);
+// 解析探测目标并统计 DNS 耗时：字面量 IP 不发起查询，耗时为 0；解析失败时耗时为 DNS_RESOLVE_FAILED
+// 设置了 DNS 服务器时直接向该服务器查询并计时；该服务器解析失败时仍用系统解析器取得探测地址，
+// 只把 DNS 耗时记为失败，使 DNS 问题与网络质量分开呈现
+fn resolve_target_timed(target: &str) -> (Option<IpAddr>, u32) {
+    if let Ok(ip) = target.parse::<IpAddr>() {
+        return (Some(ip), 0);
     }
 
-    let mut addrs = (target, 0).to_socket_addrs().ok()?;
-    addrs.find_map(|addr| match addr.ip() {
-        std::net::IpAddr::V4(ipv4) => Some(ipv4),
-        std::net::IpAddr::V6(_) => None,
-    })
+    let dns_server = *DNS_SERVER_OVERRIDE.lock().unwrap();
+    let start_at = Instant::now();
+    let resolved = match dns_server {
+        Some(server) => dns::resolve_with_server(target, server),
+        None => resolve_target(target),
+    };
+    match resolved {
+        Some(ip) => {
+            let elapsed_ms = start_at.elapsed().as_millis().min(u128::from(u32::MAX - 1)) as u32;
+            (Some(ip), elapsed_ms)
+        }
+        None if dns_server.is_some() => (resolve_target(target), DNS_RESOLVE_FAILED),
+        None => (None, DNS_RESOLVE_FAILED),
+    }
 }
 
-fn resolve_ipv4_socket_addrs(host: &str, port: u16) -> Option<Vec<SocketAddrV4>> {
-    let addrs = (host, port).to_socket_addrs().ok()?;
-    let mut result = Vec::new();
-    for addr in addrs {
-        if let std::net::SocketAddr::V4(v4) = addr {
-            result.push(v4);
-        }
+// 解析探测目标并缓存域名的解析结果：缓存未过期时直接复用，返回 (地址, 0, true)，
+// 不再把 DNS 耗时计入探测；缓存过期、上次探测全部失败或 TTL 为 0 时重新解析并计时
+fn resolve_target_cached(target: &str) -> (Option<IpAddr>, u32, bool) {
+    let ttl = Duration::from_secs(DNS_CACHE_TTL_SECS.load(Ordering::SeqCst) as u64);
+    let now = Instant::now();
+    let cached = RESOLVED_TARGETS
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|targets| targets.get(target).copied());
+    if let Some(addr) = cached_target_address(cached, ttl, now) {
+        return (Some(addr), 0, true);
     }
-    if result.is_empty() {
-        None
-    } else {
-        Some(result)
+
+    let (resolved, dns_resolve_ms) = resolve_target_timed(target);
+    if let Some(addr) = resolved
+        && !ttl.is_zero()
+        && target.parse::<IpAddr>().is_err()
+    {
+        RESOLVED_TARGETS
+            .lock()
+            .unwrap()
+            .get_or_insert_with(HashMap::new)
+            .insert(target.to_string(), (addr, now));
+    }
+    (resolved, dns_resolve_ms, false)
+}
+
+// 缓存条目在 TTL 内时返回缓存的地址
+fn cached_target_address(
+    cached: Option<(IpAddr, Instant)>,
+    ttl: Duration,
+    now: Instant,
+) -> Option<IpAddr> {
+    let (addr, resolved_at) = cached?;
+    (now.saturating_duration_since(resolved_at) < ttl).then_some(addr)
+}
+
+fn invalidate_resolved_target(target: &str) {
+    if let Some(targets) = RESOLVED_TARGETS.lock().unwrap().as_mut() {
+        targets.remove(target);
+    }
+}
+
+// 清空解析缓存：停止探测、修改 DNS 服务器或缓存时长后，下个周期重新解析全部目标
+pub fn clear_resolved_targets() {
+    RESOLVED_TARGETS.lock().unwrap().take();
+}
+
+// 解析探测目标，优先使用 IPv4 地址，没有 IPv4 时回退到 IPv6
+fn resolve_target(target: &str) -> Option<IpAddr> {
+    if let Ok(ip) = target.parse::<IpAddr>() {
+        return Some(ip);
     }
+
+    let addrs: Vec<SocketAddr> = (target, 0).to_socket_addrs().ok()?.collect();
+    prefer_ipv4(&addrs).map(|addr| addr.ip())
+}
+
+// 解析 TCP 握手目标地址，同样优先 IPv4，仅有 IPv6 时使用 IPv6
+fn resolve_tcp_socket_addr(host: &str, port: u16) -> Option<SocketAddr> {
+    let addrs: Vec<SocketAddr> = (host, port).to_socket_addrs().ok()?.collect();
+    prefer_ipv4(&addrs)
+}
+
+fn prefer_ipv4(addrs: &[SocketAddr]) -> Option<SocketAddr> {
+    addrs
+        .iter()
+        .find(|addr| addr.is_ipv4())
+        .or_else(|| addrs.first())
+        .copied()
 }
 
 fn compute_retransmission_percent_out(segments_sent: i64, segments_retransmitted: i64) -> f64 {
@@ -447,9 +2662,32 @@ fn compute_retransmission_percent_total(segments_sent: i64, segments_retransmitt
 
 #[cfg(test)]
 mod tests {
+    use std::collections::VecDeque;
+    use std::net::{IpAddr, SocketAddr};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+
     use super::{
-        compute_interval_tcp_stats, compute_retransmission_percent_out,
-        compute_retransmission_percent_total,
+        CycleMetrics, InterfaceErrors, InterfaceOctets, ProbeMode, ProbeRoute, QualityEma,
+        TcpStats, Throughput, apply_quality_smoothing, assemble_quality_sample,
+        backoff_probe_interval, build_offline_sample, build_ping_stats, build_trace_hop,
+        cached_target_address, clamp_echo_timeout, compute_counter_delta, compute_interval_errors,
+        compute_interval_tcp_stats, compute_interval_throughput, compute_jitter_rfc3550,
+        compute_jitter_stddev, compute_percentile, compute_quality_score,
+        compute_retransmission_percent_out, compute_retransmission_percent_total,
+        count_out_of_order_replies, decode_echo_sequence, echo_reply_data, encode_echo_sequence,
+        icmp_reply_buffer_size, ip_status_name, is_final_trace_hop, is_icmp_error_status,
+        is_offline_cycle, is_probe_byte_acked, is_valid_ping_payload_size, jitter_probe_interval,
+        needs_tcp_fallback, normalize_degradation_thresholds, normalize_ping_targets,
+        normalize_tcp_fallback_ports, parse_icmp_replies, prefer_ipv4, probe_mode_from_u32,
+        push_bounded, quality_grade, run_bounded, seconds_since_last_success, select_cycle_targets,
+        should_escalate_echo_timeout, should_ping_gateway, update_degraded_state,
+        update_probe_route,
+    };
+    use crate::global::{DEFAULT_PING_TARGET, NetworkQualitySample, PROBE_ERROR_OFFLINE};
+    use windows::Win32::NetworkManagement::IpHelper::{
+        ICMP_ECHO_REPLY, IP_DEST_HOST_UNREACHABLE, IP_REQ_TIMED_OUT, IP_SUCCESS,
+        IP_TTL_EXPIRED_TRANSIT,
     };
 
     #[test]
@@ -498,4 +2736,674 @@ mod tests {
         assert_eq!(stats.retransmission_percent, 0.0);
         assert_eq!(baseline, Some((50, 2)));
     }
+
+    #[test]
+    fn ping_targets_are_trimmed_and_deduplicated() {
+        let targets = normalize_ping_targets(vec![
+            " 1.1.1.1 ".to_string(),
+            String::new(),
+            "cdn.example.com".to_string(),
+            "1.1.1.1".to_string(),
+        ]);
+        assert_eq!(targets, vec!["1.1.1.1", "cdn.example.com"]);
+    }
+
+    #[test]
+    fn ping_targets_fall_back_to_default_when_empty() {
+        let targets = normalize_ping_targets(vec!["  ".to_string()]);
+        assert_eq!(targets, vec![DEFAULT_PING_TARGET]);
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let sorted: Vec<u32> = (1..=100).collect();
+        assert_eq!(compute_percentile(&sorted, 50, 100), 50);
+        assert_eq!(compute_percentile(&sorted, 95, 100), 95);
+        assert_eq!(compute_percentile(&sorted, 99, 100), 99);
+
+        let sorted = [10, 20, 30, 40];
+        assert_eq!(compute_percentile(&sorted, 50, 40), 20);
+        assert_eq!(compute_percentile(&sorted, 95, 40), 40);
+        assert_eq!(compute_percentile(&[], 50, 0), 0);
+    }
+
+    #[test]
+    fn ping_stats_percentiles_ignore_sample_order() {
+        let stats = build_ping_stats(&[40, 10, 30, 20], 5, 4, 0, 0, None);
+        assert_eq!(stats.min_ms, 10);
+        assert_eq!(stats.max_ms, 40);
+        assert_eq!(stats.avg_ms, 25);
+        assert_eq!(stats.p50_ms, 20);
+        assert_eq!(stats.p99_ms, 40);
+        assert!((stats.loss_percent - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ipv4_addresses_are_preferred_over_ipv6() {
+        let v6: SocketAddr = "[2001:db8::1]:443".parse().unwrap();
+        let v4: SocketAddr = "192.0.2.1:443".parse().unwrap();
+        assert_eq!(prefer_ipv4(&[v6, v4]), Some(v4));
+        assert_eq!(prefer_ipv4(&[v6]), Some(v6));
+        assert_eq!(prefer_ipv4(&[]), None);
+    }
+
+    #[test]
+    fn offline_backoff_doubles_up_to_cap() {
+        let base = Duration::from_secs(10);
+        assert_eq!(backoff_probe_interval(base, 0), base);
+        assert_eq!(backoff_probe_interval(base, 1), Duration::from_secs(20));
+        assert_eq!(backoff_probe_interval(base, 2), Duration::from_secs(40));
+        assert_eq!(backoff_probe_interval(base, 3), Duration::from_secs(60));
+        assert_eq!(
+            backoff_probe_interval(base, u32::MAX),
+            Duration::from_secs(60)
+        );
+        // 配置的间隔已超过上限时不缩短
+        let slow = Duration::from_secs(120);
+        assert_eq!(backoff_probe_interval(slow, 5), slow);
+    }
+
+    #[test]
+    fn bounded_buffer_keeps_newest_items() {
+        let mut buffer = VecDeque::new();
+        for item in 0..5 {
+            push_bounded(&mut buffer, item, 3);
+        }
+        assert_eq!(buffer, [2, 3, 4]);
+
+        // 容量缩小时一次性裁剪到新容量
+        push_bounded(&mut buffer, 5, 2);
+        assert_eq!(buffer, [4, 5]);
+
+        push_bounded(&mut buffer, 6, 0);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn echo_timeout_is_clamped_to_deadline() {
+        let now = Instant::now();
+        let timeout = Duration::from_millis(3000);
+        assert_eq!(
+            clamp_echo_timeout(timeout, now + Duration::from_secs(10), now),
+            Some(timeout)
+        );
+        assert_eq!(
+            clamp_echo_timeout(timeout, now + Duration::from_millis(500), now),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(clamp_echo_timeout(timeout, now, now), None);
+    }
+
+    #[test]
+    fn icmp_errors_are_separated_from_timeouts() {
+        // 10 次 Echo：6 次成功、3 次 TTL 超时等 ICMP 差错、1 次无应答
+        let stats = build_ping_stats(&[10, 12, 11, 10, 13, 12], 10, 6, 3, 11010, None);
+        assert!((stats.loss_percent - 10.0).abs() < 1e-9);
+        assert!((stats.icmp_error_percent - 30.0).abs() < 1e-9);
+
+        let stats = build_ping_stats(&[], 4, 0, 4, 0, Some(11013));
+        assert!(stats.loss_percent.abs() < 1e-9);
+        assert!((stats.icmp_error_percent - 100.0).abs() < 1e-9);
+
+        assert!(is_icmp_error_status(11013));
+        assert!(is_icmp_error_status(11003));
+        assert!(!is_icmp_error_status(11010));
+        assert!(!is_icmp_error_status(0));
+    }
+
+    #[test]
+    fn ping_stats_loss_uses_echoes_actually_sent() {
+        let stats = build_ping_stats(&[], 3, 0, 0, 0, None);
+        assert_eq!(stats.echo_count, 3);
+        assert!((stats.loss_percent - 100.0).abs() < 1e-9);
+        let stats = build_ping_stats(&[20, 30], 4, 2, 0, 0, None);
+        assert_eq!(stats.echo_count, 4);
+        assert!((stats.loss_percent - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn jitter_stddev_uses_population_deviation() {
+        assert_eq!(compute_jitter_stddev(&[]), 0);
+        assert_eq!(compute_jitter_stddev(&[42]), 0);
+        assert_eq!(compute_jitter_stddev(&[10, 10, 10]), 0);
+        // 均值 5，方差 (9+1+1+1+0+0+4+16)/8 = 4，标准差 2
+        assert_eq!(compute_jitter_stddev(&[2, 4, 4, 4, 5, 5, 7, 9]), 2);
+    }
+
+    #[test]
+    fn counter_delta_rebaselines_on_first_sample_and_wrap() {
+        let mut baseline = None;
+        assert_eq!(compute_counter_delta(&mut baseline, 10), 0);
+        assert_eq!(baseline, Some(10));
+        assert_eq!(compute_counter_delta(&mut baseline, 14), 4);
+        assert_eq!(compute_counter_delta(&mut baseline, 3), 0);
+        assert_eq!(baseline, Some(3));
+        assert_eq!(compute_counter_delta(&mut baseline, 5), 2);
+    }
+
+    #[test]
+    fn reply_buffer_fits_largest_payload_and_error_message() {
+        let header = std::mem::size_of::<super::ICMP_ECHO_REPLY>();
+        assert_eq!(icmp_reply_buffer_size(32, 1), header + 32 + 8);
+        assert_eq!(icmp_reply_buffer_size(32, 4), (header + 32 + 8) * 4);
+        assert!(is_valid_ping_payload_size(1472));
+        assert!(is_valid_ping_payload_size(65507));
+        assert!(!is_valid_ping_payload_size(65508));
+    }
+
+    #[test]
+    fn icmp_replies_are_parsed_up_to_buffer_capacity() {
+        let reply_len = std::mem::size_of::<super::ICMP_ECHO_REPLY>();
+        let mut buffer = vec![0u8; icmp_reply_buffer_size(32, 2)];
+        for (index, status) in [0u32, 11013].into_iter().enumerate() {
+            let reply = super::ICMP_ECHO_REPLY {
+                Status: status,
+                RoundTripTime: 10 + index as u32,
+                ..Default::default()
+            };
+            unsafe {
+                std::ptr::write_unaligned(
+                    buffer.as_mut_ptr().add(index * reply_len) as *mut super::ICMP_ECHO_REPLY,
+                    reply,
+                );
+            }
+        }
+
+        assert!(parse_icmp_replies(&buffer, 0).is_empty());
+        let replies = parse_icmp_replies(&buffer, 2);
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[0].RoundTripTime, 10);
+        assert_eq!(replies[1].Status, 11013);
+        // 应答数超出缓冲区容量时不越界读取
+        assert_eq!(parse_icmp_replies(&buffer[..reply_len], 3).len(), 1);
+    }
+
+    fn raw_sample(latency: u32, jitter: u32, loss: f64) -> NetworkQualitySample {
+        NetworkQualitySample {
+            latency_avg_ms_raw: latency,
+            jitter_ms_raw: jitter,
+            packet_loss_percent_raw: loss,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn smoothing_blends_with_previous_and_keeps_raw_values() {
+        let mut ema = QualityEma::default();
+        let mut first = raw_sample(100, 10, 0.0);
+        apply_quality_smoothing(&mut ema, 0.5, &mut first);
+        assert_eq!(first.latency_avg_ms, 100);
+
+        let mut second = raw_sample(200, 30, 20.0);
+        apply_quality_smoothing(&mut ema, 0.5, &mut second);
+        assert_eq!(second.latency_avg_ms, 150);
+        assert_eq!(second.jitter_ms, 20);
+        assert!((second.packet_loss_percent - 10.0).abs() < 1e-9);
+        assert_eq!(second.latency_avg_ms_raw, 200);
+        assert!((second.packet_loss_percent_raw - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn smoothing_ignores_latency_of_fully_lost_samples() {
+        let mut ema = QualityEma::default();
+        apply_quality_smoothing(&mut ema, 0.5, &mut raw_sample(100, 10, 0.0));
+
+        let mut lost = raw_sample(0, 0, 100.0);
+        apply_quality_smoothing(&mut ema, 0.5, &mut lost);
+        assert_eq!(lost.latency_avg_ms, 100);
+        assert_eq!(lost.jitter_ms, 10);
+        assert!((lost.packet_loss_percent - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn quality_score_weights_metrics_and_grades() {
+        assert_eq!(compute_quality_score(20, 5, 0.0, 0.5), 100);
+        assert_eq!(quality_grade(100), "good");
+        // 延迟 100ms 位于 50-300ms 的 20% 处，延迟单项得 80 分：100 - 35 * 0.2
+        assert_eq!(compute_quality_score(100, 5, 0.0, 0.5), 93);
+        assert_eq!(compute_quality_score(400, 200, 50.0, 20.0), 0);
+        assert_eq!(quality_grade(0), "poor");
+        assert_eq!(quality_grade(50), "fair");
+    }
+
+    #[test]
+    fn fully_lost_probe_scores_zero() {
+        let mut sample = raw_sample(0, 0, 100.0);
+        sample.packet_loss_percent = 30.0;
+        super::apply_quality_score(&mut sample);
+        assert_eq!(sample.quality_score, 0);
+        assert_eq!(sample.quality_grade, "poor");
+    }
+
+    #[test]
+    fn throughput_rebaselines_on_interface_change_and_counter_reset() {
+        let start = std::time::Instant::now();
+        let octets = |interface_index, in_octets, out_octets, secs| InterfaceOctets {
+            interface_index,
+            in_octets,
+            out_octets,
+            in_errors: 0,
+            out_errors: 0,
+            in_discards: 0,
+            out_discards: 0,
+            read_at: start + std::time::Duration::from_secs(secs),
+        };
+        let mut baseline = None;
+
+        let first = compute_interval_throughput(&mut baseline, octets(3, 1_000, 500, 0));
+        assert_eq!(first, Throughput::default());
+
+        let second = compute_interval_throughput(&mut baseline, octets(3, 21_000, 2_500, 10));
+        assert_eq!(
+            second,
+            Throughput {
+                rx_bytes_per_sec: 2_000.0,
+                tx_bytes_per_sec: 200.0,
+            }
+        );
+
+        let switched = compute_interval_throughput(&mut baseline, octets(7, 50_000, 9_000, 20));
+        assert_eq!(switched, Throughput::default());
+
+        let wrapped = compute_interval_throughput(&mut baseline, octets(7, 10, 9_100, 30));
+        assert_eq!(wrapped, Throughput::default());
+    }
+
+    #[test]
+    fn tcp_fallback_ports_keep_order_and_fall_back_to_default() {
+        assert_eq!(
+            normalize_tcp_fallback_ports(vec![80, 443, 80, 8443]),
+            vec![80, 443, 8443]
+        );
+        assert_eq!(normalize_tcp_fallback_ports(Vec::new()), vec![443]);
+    }
+
+    #[test]
+    fn degradation_thresholds_fall_back_and_keep_exit_below_enter() {
+        assert_eq!(normalize_degradation_thresholds(0, 0, 100, 50), (100, 50));
+        assert_eq!(normalize_degradation_thresholds(80, 0, 100, 50), (80, 50));
+        assert_eq!(normalize_degradation_thresholds(40, 0, 100, 50), (40, 20));
+        assert_eq!(normalize_degradation_thresholds(10, 10, 100, 50), (10, 5));
+    }
+
+    #[test]
+    fn degraded_state_uses_hysteresis() {
+        let sample = |jitter, loss| NetworkQualitySample {
+            jitter_ms: jitter,
+            packet_loss_percent: loss,
+            ..Default::default()
+        };
+        let mut degraded = false;
+        update_degraded_state(&mut degraded, &sample(200, 0.0));
+        assert!(degraded);
+        // 处于进入与退出阈值之间时保持劣化
+        update_degraded_state(&mut degraded, &sample(70, 0.0));
+        assert!(degraded);
+        update_degraded_state(&mut degraded, &sample(30, 2.0));
+        assert!(!degraded);
+        update_degraded_state(&mut degraded, &sample(0, 15.0));
+        assert!(degraded);
+    }
+
+    #[test]
+    fn probe_modes_map_from_config_values() {
+        assert_eq!(probe_mode_from_u32(0), Some(ProbeMode::IcmpFirst));
+        assert_eq!(probe_mode_from_u32(1), Some(ProbeMode::TcpOnly));
+        assert_eq!(probe_mode_from_u32(2), Some(ProbeMode::IcmpOnly));
+        assert_eq!(probe_mode_from_u32(3), None);
+    }
+
+    #[test]
+    fn staleness_counts_from_last_success_or_start() {
+        let started_at = Instant::now();
+        let now = started_at + Duration::from_secs(45);
+        assert_eq!(seconds_since_last_success(None, started_at, now), 45);
+        let last = Some(started_at + Duration::from_secs(40));
+        assert_eq!(seconds_since_last_success(last, started_at, now), 5);
+        assert_eq!(seconds_since_last_success(Some(now), started_at, now), 0);
+    }
+
+    #[test]
+    fn assembled_sample_treats_missing_measurement_as_full_loss() {
+        let sample = assemble_quality_sample("example.com", 1.0, 7, None, &CycleMetrics::default());
+        assert_eq!(sample.target, "example.com");
+        assert_eq!(sample.dns_resolve_ms, 7);
+        assert_eq!(sample.latency_avg_ms, 0);
+        assert_eq!(sample.packet_loss_percent, 100.0);
+        assert_eq!(sample.packet_loss_percent_raw, 100.0);
+        assert_eq!(sample.echo_count, 0);
+        assert_eq!(sample.tcp_segments_sent, 0);
+        assert_eq!(sample.gateway_latency_ms, 0);
+        assert!(!sample.mtu_black_hole);
+    }
+
+    #[test]
+    fn assembled_sample_copies_ping_and_cycle_metrics() {
+        let ping = build_ping_stats(&[10, 30], 4, 2, 0, 0, None);
+        let cycle = CycleMetrics {
+            tcp_stats: Some(TcpStats {
+                retransmission_percent: 1.5,
+                segments_sent: 200,
+                segments_retransmitted: 3,
+                connection_failures: 1,
+                current_established: 12,
+            }),
+            gateway: Some(build_ping_stats(&[2], 1, 1, 0, 0, None)),
+            throughput: Throughput::default(),
+            interface_errors: InterfaceErrors {
+                rx_errors: 4,
+                tx_errors: 0,
+                rx_discards: 2,
+                tx_discards: 1,
+            },
+        };
+        let sample = assemble_quality_sample("1.1.1.1", 2.0, 0, Some(&ping), &cycle);
+        assert_eq!(sample.rx_errors, 4);
+        assert_eq!(sample.rx_discards, 2);
+        assert_eq!(sample.tx_discards, 1);
+        assert_eq!(sample.latency_avg_ms, 20);
+        assert_eq!(sample.latency_avg_ms_raw, 20);
+        assert_eq!(sample.packet_loss_percent, 50.0);
+        assert_eq!(sample.packet_loss_percent_raw, 50.0);
+        assert_eq!(sample.echo_count, 4);
+        assert_eq!(sample.tcp_retransmission_percent, 1.5);
+        assert_eq!(sample.tcp_segments_retransmitted, 3);
+        assert!(!sample.icmp_blocked);
+        assert!(sample.tcp_stats_available);
+
+        let mut fallback = build_ping_stats(&[15], 4, 1, 0, 0, None);
+        fallback.icmp_blocked = true;
+        let sample = assemble_quality_sample("1.1.1.1", 2.0, 0, Some(&fallback), &cycle);
+        assert!(sample.icmp_blocked);
+        assert_eq!(sample.tcp_current_established, 12);
+        assert_eq!(sample.gateway_latency_ms, 2);
+    }
+
+    #[test]
+    fn tcp_fallback_only_when_icmp_first_fails_completely() {
+        let failed = build_ping_stats(&[], 4, 0, 0, 0, None);
+        let partial = build_ping_stats(&[10], 4, 1, 0, 0, None);
+        assert!(needs_tcp_fallback(ProbeMode::IcmpFirst, Some(&failed)));
+        assert!(!needs_tcp_fallback(ProbeMode::IcmpFirst, Some(&partial)));
+        assert!(needs_tcp_fallback(ProbeMode::IcmpFirst, None));
+        assert!(!needs_tcp_fallback(ProbeMode::IcmpOnly, None));
+        assert!(!needs_tcp_fallback(ProbeMode::IcmpOnly, Some(&failed)));
+    }
+
+    #[test]
+    fn round_robin_advances_one_target_per_cycle() {
+        let targets = || vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let mut cursor = 0;
+        assert_eq!(select_cycle_targets(targets(), false, &mut cursor).len(), 3);
+        assert_eq!(cursor, 0);
+
+        let picked: Vec<String> = (0..4)
+            .flat_map(|_| select_cycle_targets(targets(), true, &mut cursor))
+            .collect();
+        assert_eq!(picked, ["a", "b", "c", "a"]);
+
+        cursor = 5;
+        assert_eq!(
+            select_cycle_targets(vec!["x".to_string(), "y".to_string()], true, &mut cursor),
+            ["y"]
+        );
+        assert!(select_cycle_targets(Vec::new(), true, &mut cursor).is_empty());
+    }
+
+    #[test]
+    fn ip_status_codes_map_to_readable_names() {
+        assert_eq!(ip_status_name(0), "success");
+        assert_eq!(ip_status_name(11010), "req_timed_out");
+        assert_eq!(ip_status_name(11013), "ttl_expired_transit");
+        assert_eq!(ip_status_name(11003), "dest_host_unreachable");
+        assert_eq!(ip_status_name(11999), "status_11999");
+    }
+
+    #[test]
+    fn echo_sequence_detects_reordered_and_duplicate_replies() {
+        let mut payload = vec![0u8; 32];
+        encode_echo_sequence(&mut payload, 7);
+        assert_eq!(decode_echo_sequence(&payload), Some(7));
+        // 载荷过短时不写入序号
+        let mut short = vec![0u8; 2];
+        encode_echo_sequence(&mut short, 7);
+        assert_eq!(short, vec![0, 0]);
+        assert_eq!(decode_echo_sequence(&short), None);
+
+        assert_eq!(count_out_of_order_replies(7, Some(7), 1), 0);
+        assert_eq!(count_out_of_order_replies(7, Some(6), 1), 1);
+        assert_eq!(count_out_of_order_replies(7, Some(7), 3), 2);
+        assert_eq!(count_out_of_order_replies(7, None, 0), 0);
+    }
+
+    #[test]
+    fn echo_reply_data_stays_within_reply_buffer() {
+        let buffer = vec![1u8; 64];
+        let mut reply = super::ICMP_ECHO_REPLY {
+            Data: buffer[48..].as_ptr() as *mut _,
+            DataSize: 16,
+            ..Default::default()
+        };
+        assert_eq!(echo_reply_data(&buffer, &reply).map(<[u8]>::len), Some(16));
+        reply.DataSize = 17;
+        assert!(echo_reply_data(&buffer, &reply).is_none());
+        reply.Data = std::ptr::null_mut();
+        assert!(echo_reply_data(&buffer, &reply).is_none());
+    }
+
+    #[test]
+    fn bounded_runner_keeps_input_order_and_limits_workers() {
+        let active = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let ports: Vec<u16> = (1..=20).collect();
+        let results = run_bounded(&ports, 4, |&port| {
+            let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(5));
+            active.fetch_sub(1, Ordering::SeqCst);
+            port * 2
+        });
+        assert_eq!(
+            results,
+            ports.iter().map(|port| port * 2).collect::<Vec<_>>()
+        );
+        assert!(peak.load(Ordering::SeqCst) <= 4);
+        assert!(run_bounded(&[] as &[u16], 4, |&port| port).is_empty());
+    }
+
+    #[test]
+    fn cached_target_address_expires_after_ttl() {
+        let resolved_at = Instant::now();
+        let addr: IpAddr = "93.184.216.34".parse().unwrap();
+        let ttl = Duration::from_secs(300);
+        let cached = Some((addr, resolved_at));
+        assert_eq!(
+            cached_target_address(cached, ttl, resolved_at + Duration::from_secs(299)),
+            Some(addr)
+        );
+        assert_eq!(cached_target_address(cached, ttl, resolved_at + ttl), None);
+        assert_eq!(
+            cached_target_address(cached, Duration::ZERO, resolved_at),
+            None
+        );
+        assert_eq!(cached_target_address(None, ttl, resolved_at), None);
+    }
+
+    #[test]
+    fn probe_route_changes_only_between_known_routes() {
+        let wifi = ProbeRoute {
+            interface_index: 12,
+            next_hop: "192.168.1.1".parse().unwrap(),
+        };
+        let ethernet = ProbeRoute {
+            interface_index: 7,
+            next_hop: "10.0.0.1".parse().unwrap(),
+        };
+        let mut previous = None;
+        assert!(!update_probe_route(&mut previous, Some(wifi)));
+        assert!(!update_probe_route(&mut previous, Some(wifi)));
+        // 查询失败时保留上一次的路由
+        assert!(!update_probe_route(&mut previous, None));
+        assert_eq!(previous, Some(wifi));
+        assert!(update_probe_route(&mut previous, Some(ethernet)));
+        assert_eq!(previous, Some(ethernet));
+    }
+
+    #[test]
+    fn probe_interval_jitter_stays_within_percent() {
+        let interval = Duration::from_secs(10);
+        assert_eq!(jitter_probe_interval(interval, 0, 1.0), interval);
+        assert_eq!(jitter_probe_interval(interval, 10, 0.0), interval);
+        assert_eq!(
+            jitter_probe_interval(interval, 10, 1.0),
+            Duration::from_secs(11)
+        );
+        assert_eq!(
+            jitter_probe_interval(interval, 10, -1.0),
+            Duration::from_secs(9)
+        );
+        // 幅度超过上限时按 50% 处理
+        assert_eq!(
+            jitter_probe_interval(interval, 80, -1.0),
+            Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn interface_errors_reset_on_interface_change_and_counter_wrap() {
+        let counters = |interface_index, in_errors, out_discards| InterfaceOctets {
+            interface_index,
+            in_octets: 0,
+            out_octets: 0,
+            in_errors,
+            out_errors: 0,
+            in_discards: 0,
+            out_discards,
+            read_at: std::time::Instant::now(),
+        };
+        assert_eq!(
+            compute_interval_errors(None, &counters(3, 10, 5)),
+            InterfaceErrors::default()
+        );
+        assert_eq!(
+            compute_interval_errors(Some(&counters(3, 10, 5)), &counters(3, 17, 6)),
+            InterfaceErrors {
+                rx_errors: 7,
+                tx_errors: 0,
+                rx_discards: 0,
+                tx_discards: 1,
+            }
+        );
+        assert_eq!(
+            compute_interval_errors(Some(&counters(3, 10, 5)), &counters(7, 17, 6)),
+            InterfaceErrors::default()
+        );
+        assert_eq!(
+            compute_interval_errors(Some(&counters(3, 10, 5)), &counters(3, 2, 6)),
+            InterfaceErrors::default()
+        );
+    }
+
+    #[test]
+    fn only_first_timed_out_echo_escalates() {
+        assert!(should_escalate_echo_timeout(true, 0, 0, IP_REQ_TIMED_OUT));
+        assert!(!should_escalate_echo_timeout(false, 0, 0, IP_REQ_TIMED_OUT));
+        assert!(!should_escalate_echo_timeout(true, 1, 0, IP_REQ_TIMED_OUT));
+        assert!(!should_escalate_echo_timeout(true, 0, 1, IP_REQ_TIMED_OUT));
+        assert!(!should_escalate_echo_timeout(
+            true,
+            0,
+            0,
+            IP_DEST_HOST_UNREACHABLE
+        ));
+    }
+
+    #[test]
+    fn trace_hop_reports_responder_and_stops_at_target() {
+        let transit = ICMP_ECHO_REPLY {
+            Address: u32::from_le_bytes([192, 168, 1, 1]),
+            Status: IP_TTL_EXPIRED_TRANSIT,
+            RoundTripTime: 3,
+            ..Default::default()
+        };
+        let hop = build_trace_hop(1, Some(&transit), 0);
+        assert_eq!(hop.address, "192.168.1.1");
+        assert_eq!(hop.rtt_ms, 3);
+        assert!(!hop.reached);
+        assert!(!is_final_trace_hop(&hop));
+
+        let silent = build_trace_hop(2, None, IP_REQ_TIMED_OUT);
+        assert!(silent.address.is_empty());
+        assert_eq!(silent.status, "req_timed_out");
+        assert!(!is_final_trace_hop(&silent));
+
+        let reached = ICMP_ECHO_REPLY {
+            Address: u32::from_le_bytes([8, 8, 8, 8]),
+            Status: IP_SUCCESS,
+            RoundTripTime: 20,
+            ..Default::default()
+        };
+        let hop = build_trace_hop(3, Some(&reached), 0);
+        assert!(hop.reached);
+        assert!(is_final_trace_hop(&hop));
+
+        let unreachable = ICMP_ECHO_REPLY {
+            Address: u32::from_le_bytes([10, 0, 0, 1]),
+            Status: IP_DEST_HOST_UNREACHABLE,
+            ..Default::default()
+        };
+        assert!(is_final_trace_hop(&build_trace_hop(
+            4,
+            Some(&unreachable),
+            0
+        )));
+    }
+
+    #[test]
+    fn rfc3550_jitter_smooths_adjacent_differences() {
+        assert_eq!(compute_jitter_rfc3550(&[]), 0.0);
+        assert_eq!(compute_jitter_rfc3550(&[42]), 0.0);
+        assert_eq!(compute_jitter_rfc3550(&[10, 10, 10]), 0.0);
+        // 单个差值 16：J = 16 / 16 = 1
+        assert_eq!(compute_jitter_rfc3550(&[10, 26]), 1.0);
+        // J1 = 1，J2 = 1 + (16 - 1) / 16 = 1.9375
+        assert_eq!(compute_jitter_rfc3550(&[10, 26, 10]), 1.9);
+    }
+
+    #[test]
+    fn offline_sample_explains_missing_measurement() {
+        let sample = build_offline_sample(DEFAULT_PING_TARGET);
+        assert_eq!(sample.probe_error, PROBE_ERROR_OFFLINE);
+        assert_eq!(sample.packet_loss_percent, 100.0);
+        assert_eq!(sample.latency_avg_ms, 0);
+    }
+
+    #[test]
+    fn silent_gateway_is_probed_with_backoff() {
+        assert!(should_ping_gateway(0));
+        assert!(should_ping_gateway(2));
+        // 连续 3 个周期无应答后立即重试一次，之后每 10 个周期重试一次
+        assert!(should_ping_gateway(3));
+        assert!(!should_ping_gateway(4));
+        assert!(!should_ping_gateway(12));
+        assert!(should_ping_gateway(13));
+    }
+
+    #[test]
+    fn probe_byte_counts_as_acked_only_after_leaving_send_buffer() {
+        // 尚未发出：在途为 0 但 BytesOut 未增加
+        assert!(!is_probe_byte_acked(100, 100, 0));
+        // 已发出、等待确认
+        assert!(!is_probe_byte_acked(100, 101, 1));
+        // 已发出且已确认
+        assert!(is_probe_byte_acked(100, 101, 0));
+    }
+
+    #[test]
+    fn offline_cycle_follows_connectivity_when_monitored() {
+        assert!(is_offline_cycle(true, false));
+        assert!(!is_offline_cycle(true, true));
+        // 未订阅网络事件时连通状态不可信，照常探测
+        assert!(!is_offline_cycle(false, false));
+    }
 }
@@ -0,0 +1,21 @@
+// 轮询接口：直接读取共享的原子变量与全局快照，不依赖 ThreadsafeFunction，
+// 供 CLI 工具或集成测试在没有 Node 运行时的情况下使用；napi 导出的同名查询委托到这里
+use std::sync::atomic::Ordering;
+
+use crate::global::{LAST_QUALITY_SAMPLE, LAST_WLAN_STATUS, NETWORK_STATUS};
+pub use crate::global::{NetworkQualitySample, WlanStatus};
+
+// 当前网络连通状态：1 可访问互联网，2 仅本地网络，0 未连接（尚未产生事件时为 0）
+pub fn current_network_status() -> u32 {
+    NETWORK_STATUS.load(Ordering::SeqCst)
+}
+
+// 最近一次网络质量采样（含平滑与评分），首次探测完成前返回 None
+pub fn last_quality_sample() -> Option<NetworkQualitySample> {
+    LAST_QUALITY_SAMPLE.lock().unwrap().clone()
+}
+
+// 最近一次 WiFi 信号状态，尚未查询到信号、已断开或 WLAN 监控未运行时返回 None
+pub fn current_wlan_status() -> Option<WlanStatus> {
+    LAST_WLAN_STATUS.lock().unwrap().clone()
+}
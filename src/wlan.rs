@@ -1,39 +1,88 @@
+use std::collections::VecDeque;
 use std::ffi::c_void;
 use std::ptr::null_mut;
 use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use windows::Win32::Foundation::{ERROR_SUCCESS, HANDLE, WIN32_ERROR};
+use napi::{Env, Task};
+use napi_derive::napi;
+
+use windows::Win32::Foundation::{
+    ERROR_INVALID_STATE, ERROR_NOT_CONNECTED, ERROR_SUCCESS, HANDLE, WIN32_ERROR,
+};
 use windows::Win32::NetworkManagement::WiFi::{
-    L2_NOTIFICATION_DATA, WLAN_CONNECTION_ATTRIBUTES, WLAN_INTERFACE_INFO_LIST,
+    L2_NOTIFICATION_DATA, WLAN_AVAILABLE_NETWORK_LIST, WLAN_BSS_LIST, WLAN_CONNECTION_ATTRIBUTES,
+    WLAN_CONNECTION_PARAMETERS, WLAN_INTERFACE_INFO_LIST, WLAN_NOTIFICATION_SOURCE_ACM,
     WLAN_NOTIFICATION_SOURCE_MSM, WLAN_NOTIFICATION_SOURCE_NONE, WLAN_OPCODE_VALUE_TYPE,
-    WlanCloseHandle, WlanEnumInterfaces, WlanFreeMemory, WlanOpenHandle, WlanQueryInterface,
-    WlanRegisterNotification, wlan_intf_opcode_current_connection, wlan_notification_msm_connected,
+    WLAN_SECURITY_ATTRIBUTES, WlanCloseHandle, WlanConnect, WlanDisconnect, WlanEnumInterfaces,
+    WlanFreeMemory, WlanGetAvailableNetworkList, WlanGetNetworkBssList, WlanOpenHandle,
+    WlanQueryInterface, WlanRegisterNotification, WlanScan, dot11_BSS_type_any,
+    wlan_connection_mode_profile, wlan_interface_state_connected, wlan_intf_opcode_channel_number,
+    wlan_intf_opcode_current_connection, wlan_notification_acm_scan_complete,
+    wlan_notification_acm_scan_fail, wlan_notification_msm_connected,
     wlan_notification_msm_disconnected, wlan_notification_msm_signal_quality_change,
 };
-use windows::core::{Error as WinError, GUID, HRESULT, Result as WinResult};
+use windows::core::{Error as WinError, GUID, HRESULT, PCWSTR, Result as WinResult};
 
 use crate::global::{
-    NETWORK_CONNECTED, SignalMonitorContext, THRESHOLD_DROP, THRESHOLD_RECOVER, WlanStatus,
-    report_wlan_status, with_monitor_state,
+    COALESCED_WLAN_STATUS_EVENTS, DEFAULT_WLAN_STATUS_DEBOUNCE_MS, InterfaceSignalState,
+    LAST_WLAN_STATUS, MAX_WLAN_STATUS_DEBOUNCE_MS, NETWORK_CONNECTED, SIGNAL_CONTEXT,
+    SIGNAL_TREND_FALLING, SIGNAL_TREND_RISING, SIGNAL_TREND_STABLE, SIGNAL_TREND_STABLE_BAND,
+    SIGNAL_TREND_WINDOW, SignalMonitorContext, THRESHOLD_DROP, THRESHOLD_RECOVER,
+    WLAN_SCAN_COMPLETED, WLAN_SCAN_SIGNAL, WLAN_STATUS_DEBOUNCE_MS, WlanEvent, WlanNetworkInfo,
+    WlanStatus, get_current_time, mark_event_received, report_wlan_event, report_wlan_status,
 };
 use crate::{report_error_log, report_info_log};
 
 const DEFAULT_SIGNAL_DROP: u32 = 30;
 const DEFAULT_SIGNAL_RECOVER: u32 = 40;
+// 信号质量为 0 视为未连接，RSSI 上报 0 作为“无有效信号”的标记，而不是 -100dBm
+const DISCONNECTED_RSSI: i32 = 0;
+// WlanOpenHandle 依次尝试的客户端版本
+const WLAN_CLIENT_VERSIONS: [u32; 2] = [2, 1];
+// 等待扫描完成通知的上限：系统要求驱动在 4 秒内完成扫描
+const WLAN_SCAN_TIMEOUT: Duration = Duration::from_secs(4);
+// 802.11 能力信息中的 Privacy 位：无法从可用网络列表取得安全配置时用于判断是否加密
+const DOT11_CAPABILITY_PRIVACY: u16 = 0x0010;
+
+// 单次查询得到的 WLAN 连接信号信息
+struct InterfaceSignal {
+    quality: u32,
+    rssi: i32,
+    ssid: String,
+    bssid: String,
+    security: WlanSecurity,
+    // DOT11_PHY_TYPE 原始值（如 7=802.11n、8=802.11ac、10=802.11ax），未连接时为 0
+    phy_type: u32,
+    channel: u32,
+    // 协商的发送/接收速率（Kbps），未连接时为 0
+    tx_rate_kbps: u32,
+    rx_rate_kbps: u32,
+}
+
+// 当前连接的安全配置，未连接时全部为 0/false
+#[derive(Debug, Default, Clone, PartialEq)]
+struct WlanSecurity {
+    enabled: bool,
+    // DOT11_AUTH_ALGORITHM 原始值，如 1=Open、7=RSNA-PSK（WPA2-Personal）
+    auth_algorithm: u32,
+    // DOT11_CIPHER_ALGORITHM 原始值，如 0=None、4=CCMP（AES）
+    cipher_algorithm: u32,
+}
 
 // 初始化 WLAN 监控：打开句柄、注册回调并推送一次当前信号
 pub fn initialize_wlan_monitor() -> WinResult<()> {
-    let mut negotiated_version = 0u32;
-    let mut wlan_handle = HANDLE(null_mut());
-    let open_result = unsafe { WlanOpenHandle(2, None, &mut negotiated_version, &mut wlan_handle) };
-    check_win32(WIN32_ERROR(open_result), "WlanOpenHandle")?;
+    let wlan_handle = open_wlan_handle()?;
 
     let mut interface_list: *mut WLAN_INTERFACE_INFO_LIST = null_mut();
     let enum_result = unsafe { WlanEnumInterfaces(wlan_handle, None, &mut interface_list) };
-    check_win32(WIN32_ERROR(enum_result), "WlanEnumInterfaces")?;
+    if let Err(error) = check_win32(WIN32_ERROR(enum_result), "WlanEnumInterfaces") {
+        let _ = unsafe { WlanCloseHandle(wlan_handle, None) };
+        return Err(error);
+    }
 
-    let interface_guid = extract_first_interface_guid(interface_list);
+    let interface_infos = extract_interfaces(interface_list);
 
     if !interface_list.is_null() {
         unsafe { WlanFreeMemory(interface_list as *mut c_void) };
@@ -41,37 +90,50 @@ pub fn initialize_wlan_monitor() -> WinResult<()> {
 
     let (threshold_drop, threshold_recover) = resolve_signal_thresholds();
 
-    let mut is_signal_weak = false;
-    let mut last_quality = 0;
-
-    if let Some(guid) = interface_guid
-        && let Some((quality, _rssi)) = query_interface_signal(wlan_handle, &guid)
-    {
-        if quality < threshold_drop {
-            is_signal_weak = true;
-        } else if quality >= threshold_recover {
-            is_signal_weak = false;
-        }
-        last_quality = quality;
-    }
+    let interfaces = interface_infos
+        .into_iter()
+        .map(|(interface_guid, interface_name)| {
+            report_info_log!(
+                "WLAN 接口：{}，{}",
+                interface_name,
+                guid_to_string(&interface_guid)
+            );
+            let mut state = InterfaceSignalState {
+                interface_guid,
+                interface_name,
+                is_signal_weak: false,
+                last_quality: 0,
+                recent_qualities: VecDeque::new(),
+                last_status_at: None,
+                pending_status: false,
+            };
+            if let Some(InterfaceSignal { quality, .. }) =
+                query_interface_signal(wlan_handle, &interface_guid)
+            {
+                (state.is_signal_weak, _) =
+                    next_signal_weak(false, quality, threshold_drop, threshold_recover);
+                state.last_quality = quality;
+                record_signal_quality(&mut state.recent_qualities, quality);
+            }
+            state
+        })
+        .collect::<Vec<_>>();
+    report_info_log!("初始化 WLAN 监控，接口数量：{}", interfaces.len());
 
     let context = Arc::new(Mutex::new(SignalMonitorContext {
         wlan_handle: wlan_handle.0 as isize,
         threshold_drop,
         threshold_recover,
-        is_signal_weak,
-        last_quality,
+        interfaces,
     }));
     let context_ptr = Arc::into_raw(Arc::clone(&context)) as *mut c_void;
 
-    with_monitor_state(|state| {
-        state.signal_context = Some(context);
-    });
-
+    // WLAN 通知按句柄注册，一次注册即可覆盖该句柄下的所有接口，回调中再按 InterfaceGuid 分发
+    // ACM 通知仅用于等待 scanWlanNetworks 的扫描完成
     let register_result = unsafe {
         WlanRegisterNotification(
             wlan_handle,
-            WLAN_NOTIFICATION_SOURCE_MSM,
+            WLAN_NOTIFICATION_SOURCE_MSM | WLAN_NOTIFICATION_SOURCE_ACM,
             true,
             Some(wlan_notification_callback),
             Some(context_ptr),
@@ -79,40 +141,371 @@ pub fn initialize_wlan_monitor() -> WinResult<()> {
             None,
         )
     };
-    check_win32(WIN32_ERROR(register_result), "WlanRegisterNotification")?;
+    if let Err(error) = check_win32(WIN32_ERROR(register_result), "WlanRegisterNotification") {
+        // 注册失败时回调不会被调用，收回交给回调的引用并关闭句柄，监控保持未激活
+        unsafe {
+            drop(Arc::from_raw(
+                context_ptr as *const Mutex<SignalMonitorContext>,
+            ))
+        };
+        let _ = unsafe { WlanCloseHandle(wlan_handle, None) };
+        return Err(error);
+    }
+
+    // 注册成功后才发布上下文，is_wlan_monitor_active 据此反映真实的初始化结果
+    SIGNAL_CONTEXT.lock().unwrap().replace(context);
 
     Ok(())
 }
 
-// 释放 WLAN 监控资源：注销通知并关闭句柄
+// 释放 WLAN 监控资源：注销句柄上的全部接口通知并关闭句柄
 pub fn cleanup_wlan_monitor() {
-    with_monitor_state(|state| {
-        if let Some(context) = state.signal_context.take() {
-            let context_ptr = Arc::as_ptr(&context);
-            let handle = context
-                .lock()
-                .map(|ctx| HANDLE(ctx.wlan_handle as *mut c_void))
-                .unwrap_or(HANDLE(null_mut()));
-            if handle.0.is_null() {
-                unsafe { Arc::decrement_strong_count(context_ptr) };
-                return;
-            }
-
-            let _ = unsafe {
-                WlanRegisterNotification(
-                    handle,
-                    WLAN_NOTIFICATION_SOURCE_NONE,
-                    true,
-                    None,
-                    None,
-                    None,
-                    None,
-                )
-            };
-            let _ = unsafe { WlanCloseHandle(handle, None) };
+    LAST_WLAN_STATUS.lock().unwrap().take();
+    let context = SIGNAL_CONTEXT.lock().unwrap().take();
+    if let Some(context) = context {
+        let context_ptr = Arc::as_ptr(&context);
+        let handle = context
+            .lock()
+            .map(|ctx| HANDLE(ctx.wlan_handle as *mut c_void))
+            .unwrap_or(HANDLE(null_mut()));
+        if handle.0.is_null() {
             unsafe { Arc::decrement_strong_count(context_ptr) };
+            return;
         }
-    });
+
+        let _ = unsafe {
+            WlanRegisterNotification(
+                handle,
+                WLAN_NOTIFICATION_SOURCE_NONE,
+                true,
+                None,
+                None,
+                None,
+                None,
+            )
+        };
+        let _ = unsafe { WlanCloseHandle(handle, None) };
+        unsafe { Arc::decrement_strong_count(context_ptr) };
+    }
+}
+
+// 运行时更新弱信号阈值：校验规则与初始化一致，下一次信号变化通知即生效
+pub fn update_signal_thresholds(drop: u32, recover: u32) {
+    THRESHOLD_DROP.store(drop, Ordering::SeqCst);
+    THRESHOLD_RECOVER.store(recover, Ordering::SeqCst);
+    let (threshold_drop, threshold_recover) = resolve_signal_thresholds();
+
+    let context = SIGNAL_CONTEXT.lock().unwrap().clone();
+    if let Some(context) = context
+        && let Ok(mut context) = context.lock()
+    {
+        context.threshold_drop = threshold_drop;
+        context.threshold_recover = threshold_recover;
+    }
+    report_info_log!(
+        "更新 WiFi 弱信号阈值：drop={}, recover={}",
+        threshold_drop,
+        threshold_recover
+    );
+}
+
+// WLAN 监控是否已初始化（句柄与回调已注册）
+pub fn is_wlan_monitor_active() -> bool {
+    SIGNAL_CONTEXT.lock().unwrap().is_some()
+}
+
+// 断开并按原配置文件重连当前已连接的 WLAN 接口，返回重连接口的网卡描述。
+// 仅处理第一个处于已连接状态的接口；WlanConnect 为异步请求，连接结果通过 MSM 通知上报
+pub fn reconnect_wlan() -> WinResult<String> {
+    let context = SIGNAL_CONTEXT.lock().unwrap().clone();
+    let Some(context) = context else {
+        return Err(WinError::new(
+            HRESULT::from_win32(ERROR_INVALID_STATE.0),
+            "WLAN monitor is not initialized",
+        ));
+    };
+    // 复制句柄与接口列表后立即释放锁，避免断开通知回调等待同一把锁
+    let (wlan_handle, interfaces) = {
+        let context = context.lock().unwrap();
+        (
+            HANDLE(context.wlan_handle as *mut c_void),
+            context
+                .interfaces
+                .iter()
+                .map(|state| (state.interface_guid, state.interface_name.clone()))
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    let Some((interface_guid, interface_name, profile)) =
+        interfaces.into_iter().find_map(|(guid, name)| {
+            query_connected_profile(wlan_handle, &guid).map(|profile| (guid, name, profile))
+        })
+    else {
+        report_error_log!("WLAN 重连失败：没有处于已连接状态的接口");
+        return Err(WinError::new(
+            HRESULT::from_win32(ERROR_NOT_CONNECTED.0),
+            "no connected WLAN interface to reconnect",
+        ));
+    };
+
+    report_info_log!(
+        "WLAN 重连：断开接口 {}（{}），配置文件={}",
+        interface_name,
+        guid_to_string(&interface_guid),
+        profile
+    );
+    let disconnect_result = unsafe { WlanDisconnect(wlan_handle, &interface_guid, None) };
+    check_win32(WIN32_ERROR(disconnect_result), "WlanDisconnect")?;
+
+    let profile_wide: Vec<u16> = profile.encode_utf16().chain(Some(0)).collect();
+    let parameters = WLAN_CONNECTION_PARAMETERS {
+        wlanConnectionMode: wlan_connection_mode_profile,
+        strProfile: PCWSTR(profile_wide.as_ptr()),
+        pDot11Ssid: null_mut(),
+        pDesiredBssidList: null_mut(),
+        dot11BssType: dot11_BSS_type_any,
+        dwFlags: 0,
+    };
+    let connect_result = unsafe { WlanConnect(wlan_handle, &interface_guid, &parameters, None) };
+    check_win32(WIN32_ERROR(connect_result), "WlanConnect")?;
+
+    report_info_log!("WLAN 重连请求已提交，接口={}", interface_name);
+    Ok(interface_name)
+}
+
+// 触发全部 WLAN 接口扫描并返回附近的接入点（每个 BSSID 一条，按质量降序），用于现场勘测选择信道。
+// WlanScan 为异步请求：等待各接口的扫描完成/失败通知（最长 WLAN_SCAN_TIMEOUT）后读取结果，
+// 超时或扫描请求失败的接口返回系统缓存的最近一次扫描结果
+pub fn scan_wlan_networks() -> WinResult<Vec<WlanNetworkInfo>> {
+    let context = SIGNAL_CONTEXT.lock().unwrap().clone();
+    let Some(context) = context else {
+        return Err(WinError::new(
+            HRESULT::from_win32(ERROR_INVALID_STATE.0),
+            "WLAN monitor is not initialized",
+        ));
+    };
+    let (wlan_handle, interface_guids) = {
+        let context = context.lock().unwrap();
+        (
+            HANDLE(context.wlan_handle as *mut c_void),
+            context
+                .interfaces
+                .iter()
+                .map(|state| state.interface_guid)
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    WLAN_SCAN_COMPLETED.lock().unwrap().clear();
+    let pending = interface_guids
+        .iter()
+        .filter(|guid| {
+            let scan_result = unsafe { WlanScan(wlan_handle, *guid, None, None, None) };
+            check_win32(WIN32_ERROR(scan_result), "WlanScan").is_ok()
+        })
+        .copied()
+        .collect::<Vec<_>>();
+    let timed_out = WLAN_SCAN_SIGNAL
+        .wait_timeout_while(
+            WLAN_SCAN_COMPLETED.lock().unwrap(),
+            WLAN_SCAN_TIMEOUT,
+            |completed| !pending.iter().all(|guid| completed.contains(guid)),
+        )
+        .map(|(_completed, wait_result)| wait_result.timed_out())
+        .unwrap();
+    if timed_out {
+        report_info_log!("等待 WLAN 扫描完成超时，使用系统缓存的扫描结果");
+    }
+
+    let mut networks = interface_guids
+        .iter()
+        .flat_map(|guid| query_bss_networks(wlan_handle, guid))
+        .collect::<Vec<_>>();
+    networks.sort_by_key(|network| std::cmp::Reverse(network.quality));
+    report_info_log!(
+        "WLAN 扫描完成：接口数量={}，接入点数量={}",
+        interface_guids.len(),
+        networks.len()
+    );
+    Ok(networks)
+}
+
+// 记录扫描完成/失败通知并唤醒等待中的 scan_wlan_networks
+fn handle_scan_notification(notification: &L2_NOTIFICATION_DATA) {
+    let code = notification.NotificationCode;
+    if code != wlan_notification_acm_scan_complete.0 as u32
+        && code != wlan_notification_acm_scan_fail.0 as u32
+    {
+        return;
+    }
+    WLAN_SCAN_COMPLETED
+        .lock()
+        .unwrap()
+        .push(notification.InterfaceGuid);
+    WLAN_SCAN_SIGNAL.notify_all();
+}
+
+// 读取接口的 BSS 列表；安全配置取自可用网络列表中同 SSID 的条目，找不到时按 Privacy 位判断
+fn query_bss_networks(handle: HANDLE, interface_guid: &GUID) -> Vec<WlanNetworkInfo> {
+    let securities = query_network_securities(handle, interface_guid);
+
+    let mut bss_list: *mut WLAN_BSS_LIST = null_mut();
+    let bss_result = unsafe {
+        WlanGetNetworkBssList(
+            handle,
+            interface_guid,
+            None,
+            dot11_BSS_type_any,
+            false,
+            None,
+            &mut bss_list,
+        )
+    };
+    if check_win32(WIN32_ERROR(bss_result), "WlanGetNetworkBssList").is_err() || bss_list.is_null()
+    {
+        return Vec::new();
+    }
+
+    let list = unsafe { &*bss_list };
+    let entries = unsafe {
+        std::slice::from_raw_parts(list.wlanBssEntries.as_ptr(), list.dwNumberOfItems as usize)
+    };
+    let interface_guid = guid_to_string(interface_guid);
+    let networks = entries
+        .iter()
+        .map(|entry| {
+            let ssid_len = (entry.dot11Ssid.uSSIDLength as usize).min(entry.dot11Ssid.ucSSID.len());
+            let ssid_bytes = &entry.dot11Ssid.ucSSID[..ssid_len];
+            let security = securities
+                .iter()
+                .find(|(ssid, _)| ssid.as_slice() == ssid_bytes)
+                .map(|(_, security)| security)
+                .cloned()
+                .unwrap_or(WlanSecurity {
+                    enabled: entry.usCapabilityInformation & DOT11_CAPABILITY_PRIVACY != 0,
+                    ..Default::default()
+                });
+            let (channel, band) = frequency_to_channel(entry.ulChCenterFrequency);
+            WlanNetworkInfo {
+                ssid: ssid_to_string(&entry.dot11Ssid.ucSSID, entry.dot11Ssid.uSSIDLength),
+                bssid: bssid_to_string(&entry.dot11Bssid),
+                quality: entry.uLinkQuality,
+                rssi: entry.lRssi,
+                channel,
+                band: band.to_string(),
+                security_enabled: security.enabled,
+                auth_algorithm: security.auth_algorithm,
+                cipher_algorithm: security.cipher_algorithm,
+                phy_type: entry.dot11BssPhyType.0 as u32,
+                interface_guid: interface_guid.clone(),
+            }
+        })
+        .collect();
+
+    unsafe { WlanFreeMemory(bss_list as *mut c_void) };
+
+    networks
+}
+
+// 读取可用网络列表中各 SSID 的默认安全配置（同一 SSID 有多个条目时取第一个）
+fn query_network_securities(handle: HANDLE, interface_guid: &GUID) -> Vec<(Vec<u8>, WlanSecurity)> {
+    let mut network_list: *mut WLAN_AVAILABLE_NETWORK_LIST = null_mut();
+    let list_result =
+        unsafe { WlanGetAvailableNetworkList(handle, interface_guid, 0, None, &mut network_list) };
+    if check_win32(WIN32_ERROR(list_result), "WlanGetAvailableNetworkList").is_err()
+        || network_list.is_null()
+    {
+        return Vec::new();
+    }
+
+    let list = unsafe { &*network_list };
+    let networks =
+        unsafe { std::slice::from_raw_parts(list.Network.as_ptr(), list.dwNumberOfItems as usize) };
+    let securities = networks
+        .iter()
+        .map(|network| {
+            let ssid_len =
+                (network.dot11Ssid.uSSIDLength as usize).min(network.dot11Ssid.ucSSID.len());
+            (
+                network.dot11Ssid.ucSSID[..ssid_len].to_vec(),
+                WlanSecurity {
+                    enabled: network.bSecurityEnabled.as_bool(),
+                    auth_algorithm: network.dot11DefaultAuthAlgorithm.0 as u32,
+                    cipher_algorithm: network.dot11DefaultCipherAlgorithm.0 as u32,
+                },
+            )
+        })
+        .collect();
+
+    unsafe { WlanFreeMemory(network_list as *mut c_void) };
+
+    securities
+}
+
+// 中心频率（kHz）换算信道号与频段，无法识别的频率返回 (0, "")
+fn frequency_to_channel(frequency_khz: u32) -> (u32, &'static str) {
+    let mhz = frequency_khz / 1000;
+    match mhz {
+        2484 => (14, "2.4GHz"),
+        2412..=2472 => ((mhz - 2407) / 5, "2.4GHz"),
+        5160..=5885 => ((mhz - 5000) / 5, "5GHz"),
+        5955..=7115 => ((mhz - 5950) / 5, "6GHz"),
+        _ => (0, ""),
+    }
+}
+
+// 后台扫描任务：等待扫描完成最长约 4 秒，在 libuv 线程池中执行以免阻塞 Node 主线程
+pub struct ScanWlanNetworksTask;
+
+#[napi]
+impl Task for ScanWlanNetworksTask {
+    type Output = Vec<WlanNetworkInfo>;
+    type JsValue = Vec<WlanNetworkInfo>;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        scan_wlan_networks().map_err(|err| {
+            napi::Error::new(
+                napi::Status::GenericFailure,
+                format!("WLAN scan failed: {}", err.message()),
+            )
+        })
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+// 查询接口当前连接使用的配置文件名，未连接或没有配置文件时返回 None
+fn query_connected_profile(handle: HANDLE, interface_guid: &GUID) -> Option<String> {
+    let mut data_size = 0u32;
+    let mut data_ptr: *mut c_void = null_mut();
+    let query_result = unsafe {
+        WlanQueryInterface(
+            handle,
+            interface_guid,
+            wlan_intf_opcode_current_connection,
+            None,
+            &mut data_size,
+            &mut data_ptr,
+            None,
+        )
+    };
+    if WIN32_ERROR(query_result) != ERROR_SUCCESS || data_ptr.is_null() {
+        return None;
+    }
+
+    let attributes = unsafe { &*(data_ptr as *const WLAN_CONNECTION_ATTRIBUTES) };
+    let profile = if attributes.isState == wlan_interface_state_connected {
+        Some(utf16_to_string(&attributes.strProfileName)).filter(|name| !name.is_empty())
+    } else {
+        None
+    };
+
+    unsafe { WlanFreeMemory(data_ptr) };
+
+    profile
 }
 
 // WLAN 通知回调：根据事件类型拉取信号并派发消息
@@ -125,9 +518,15 @@ unsafe extern "system" fn wlan_notification_callback(
     }
 
     let notification = unsafe { &*notification_data };
+    if notification.NotificationSource == WLAN_NOTIFICATION_SOURCE_ACM {
+        handle_scan_notification(notification);
+        return;
+    }
     if notification.NotificationSource != WLAN_NOTIFICATION_SOURCE_MSM {
         return;
     }
+    mark_event_received();
+    forward_wlan_event(notification);
 
     let context = context as *const Mutex<SignalMonitorContext>;
     unsafe { Arc::increment_strong_count(context) };
@@ -135,42 +534,303 @@ unsafe extern "system" fn wlan_notification_callback(
     let interface_guid = &notification.InterfaceGuid;
 
     if let Ok(mut context) = context.lock() {
+        let wlan_handle = HANDLE(context.wlan_handle as *mut c_void);
+        let (threshold_drop, threshold_recover) =
+            (context.threshold_drop, context.threshold_recover);
+        let state = interface_state_mut(&mut context.interfaces, wlan_handle, interface_guid);
+
         if notification.NotificationCode == wlan_notification_msm_disconnected.0 as u32 {
-            context.last_quality = 0;
-            context.is_signal_weak = false;
+            state.last_quality = 0;
+            state.recent_qualities.clear();
+            state.is_signal_weak = false;
+            state.pending_status = false;
+            let interface_guid = guid_to_string(interface_guid);
+            let mut last_status = LAST_WLAN_STATUS.lock().unwrap();
+            if last_status
+                .as_ref()
+                .is_some_and(|status| status.interface_guid == interface_guid)
+            {
+                last_status.take();
+            }
             return;
         }
 
-        if (notification.NotificationCode == wlan_notification_msm_connected.0 as u32
-            || notification.NotificationCode
-                == wlan_notification_msm_signal_quality_change.0 as u32)
-            && let Some((quality, rssi)) =
-                query_interface_signal(HANDLE(context.wlan_handle as *mut c_void), interface_guid)
+        // 信号变化通知自带质量值：未跨越弱/强阈值且处于节流窗口内时只记录最新质量，
+        // 跳过 WlanQueryInterface 与回调，由监控线程在窗口结束后补发
+        let now = Instant::now();
+        if notification.NotificationCode == wlan_notification_msm_signal_quality_change.0 as u32
+            && let Some(quality) = notification_signal_quality(notification)
+            && !next_signal_weak(
+                state.is_signal_weak,
+                quality,
+                threshold_drop,
+                threshold_recover,
+            )
+            .1
+            && !debounce_elapsed(state.last_status_at, now, current_wlan_status_debounce())
         {
-            update_signal_state(&mut context, quality, rssi);
+            state.last_quality = quality;
+            state.pending_status = true;
+            COALESCED_WLAN_STATUS_EVENTS.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        if notification.NotificationCode == wlan_notification_msm_connected.0 as u32
+            || notification.NotificationCode == wlan_notification_msm_signal_quality_change.0 as u32
+        {
+            match query_interface_signal(wlan_handle, interface_guid) {
+                Some(signal) => {
+                    update_signal_state(state, threshold_drop, threshold_recover, signal, now)
+                }
+                None => report_unknown_signal(state, now),
+            }
         }
     }
 }
 
-// 从接口列表提取首个 WLAN 接口 GUID
-fn extract_first_interface_guid(interface_list: *mut WLAN_INTERFACE_INFO_LIST) -> Option<GUID> {
-    if interface_list.is_null() {
+// 补发节流窗口内被合并的信号变化：由监控线程定时调用，只处理窗口已结束的接口
+pub fn flush_pending_wlan_status() {
+    let context = SIGNAL_CONTEXT.lock().unwrap().clone();
+    let Some(context) = context else {
+        return;
+    };
+    let Ok(mut context) = context.lock() else {
+        return;
+    };
+    let wlan_handle = HANDLE(context.wlan_handle as *mut c_void);
+    let (threshold_drop, threshold_recover) = (context.threshold_drop, context.threshold_recover);
+    let debounce = current_wlan_status_debounce();
+    let now = Instant::now();
+    for state in context.interfaces.iter_mut() {
+        if !state.pending_status || !debounce_elapsed(state.last_status_at, now, debounce) {
+            continue;
+        }
+        state.pending_status = false;
+        match query_interface_signal(wlan_handle, &state.interface_guid) {
+            Some(signal) => {
+                update_signal_state(state, threshold_drop, threshold_recover, signal, now)
+            }
+            None => report_unknown_signal(state, now),
+        }
+    }
+}
+
+// 读取信号变化通知附带的 WLAN_SIGNAL_QUALITY（0-100），数据缺失时返回 None
+fn notification_signal_quality(notification: &L2_NOTIFICATION_DATA) -> Option<u32> {
+    if notification.pData.is_null() || (notification.dwDataSize as usize) < size_of::<u32>() {
         return None;
     }
+    Some(unsafe { std::ptr::read_unaligned(notification.pData as *const u32) })
+}
+
+// 弱信号滞回：质量不高于 drop 进入弱信号，不低于 recover 恢复，介于两者之间保持原状态。
+// 返回新的弱信号状态以及是否发生了切换
+fn next_signal_weak(
+    was_weak: bool,
+    quality: u32,
+    threshold_drop: u32,
+    threshold_recover: u32,
+) -> (bool, bool) {
+    let is_weak = if quality <= threshold_drop {
+        true
+    } else if quality >= threshold_recover {
+        false
+    } else {
+        was_weak
+    };
+    (is_weak, is_weak != was_weak)
+}
+
+// 距上次上报是否已超过节流间隔；从未上报或间隔为 0 时视为已超过
+fn debounce_elapsed(last_status_at: Option<Instant>, now: Instant, debounce: Duration) -> bool {
+    debounce.is_zero()
+        || last_status_at.is_none_or(|last| now.saturating_duration_since(last) >= debounce)
+}
+
+// 读取信号上报节流间隔，超出上限时回退到默认值
+fn current_wlan_status_debounce() -> Duration {
+    let debounce_ms = match WLAN_STATUS_DEBOUNCE_MS.load(Ordering::SeqCst) {
+        debounce_ms @ 0..=MAX_WLAN_STATUS_DEBOUNCE_MS => debounce_ms,
+        _ => DEFAULT_WLAN_STATUS_DEBOUNCE_MS,
+    };
+    Duration::from_millis(debounce_ms as u64)
+}
+
+// 转发 MSM 原始通知：除连接/断开/信号变化外的通知只在此处可见，同时写入日志
+fn forward_wlan_event(notification: &L2_NOTIFICATION_DATA) {
+    let code = notification.NotificationCode;
+    let name = msm_notification_name(code);
+    let interface_guid = guid_to_string(&notification.InterfaceGuid);
+    if code != wlan_notification_msm_signal_quality_change.0 as u32 {
+        report_info_log!("WLAN 通知：{}({})，网卡={}", name, code, interface_guid);
+    }
+    report_wlan_event(WlanEvent {
+        code,
+        name: name.to_string(),
+        interface_guid,
+        timestamp: get_current_time(),
+    });
+}
+
+// MSM 通知码转可读名称，与 wlan_notification_msm_* 常量去掉前缀后一致
+fn msm_notification_name(code: u32) -> &'static str {
+    const NAMES: [&str; 17] = [
+        "start",
+        "associating",
+        "associated",
+        "authenticating",
+        "connected",
+        "roaming_start",
+        "roaming_end",
+        "radio_state_change",
+        "signal_quality_change",
+        "disassociating",
+        "disconnected",
+        "peer_join",
+        "peer_leave",
+        "adapter_removal",
+        "adapter_operation_mode_change",
+        "link_degraded",
+        "link_improved",
+    ];
+    NAMES.get(code as usize).copied().unwrap_or("unknown")
+}
+
+// 打开 WLAN 句柄：优先请求客户端版本 2（Vista 及以上），失败时回退到版本 1，
+// 兼容部分精简或受限的企业镜像
+pub fn open_wlan_handle() -> WinResult<HANDLE> {
+    let mut last_error = ERROR_SUCCESS;
+    for client_version in WLAN_CLIENT_VERSIONS {
+        let mut negotiated_version = 0u32;
+        let mut wlan_handle = HANDLE(null_mut());
+        let open_result = unsafe {
+            WlanOpenHandle(
+                client_version,
+                None,
+                &mut negotiated_version,
+                &mut wlan_handle,
+            )
+        };
+        if WIN32_ERROR(open_result) == ERROR_SUCCESS {
+            report_info_log!(
+                "WlanOpenHandle 成功：请求版本={}，协商版本={}",
+                client_version,
+                negotiated_version
+            );
+            return Ok(wlan_handle);
+        }
+        last_error = WIN32_ERROR(open_result);
+        report_error_log!(
+            "WlanOpenHandle 失败：请求版本={}，错误={:?}",
+            client_version,
+            last_error
+        );
+    }
+    Err(WinError::from(HRESULT::from_win32(last_error.0)))
+}
+
+// 按 GUID 查找接口状态，初始化后新插入的网卡（如 USB 无线网卡）在首次通知时补充登记
+fn interface_state_mut<'a>(
+    interfaces: &'a mut Vec<InterfaceSignalState>,
+    wlan_handle: HANDLE,
+    interface_guid: &GUID,
+) -> &'a mut InterfaceSignalState {
+    let index = match interfaces
+        .iter()
+        .position(|state| state.interface_guid == *interface_guid)
+    {
+        Some(index) => index,
+        None => {
+            let interface_name = query_interface_name(wlan_handle, interface_guid);
+            report_info_log!(
+                "发现新的 WLAN 接口：{}，{}",
+                interface_name,
+                guid_to_string(interface_guid)
+            );
+            interfaces.push(InterfaceSignalState {
+                interface_guid: *interface_guid,
+                interface_name,
+                is_signal_weak: false,
+                last_quality: 0,
+                recent_qualities: VecDeque::new(),
+                last_status_at: None,
+                pending_status: false,
+            });
+            interfaces.len() - 1
+        }
+    };
+    &mut interfaces[index]
+}
+
+// 重新枚举接口以读取新网卡的描述，找不到时返回空字符串
+fn query_interface_name(wlan_handle: HANDLE, interface_guid: &GUID) -> String {
+    let mut interface_list: *mut WLAN_INTERFACE_INFO_LIST = null_mut();
+    let enum_result = unsafe { WlanEnumInterfaces(wlan_handle, None, &mut interface_list) };
+    if WIN32_ERROR(enum_result) != ERROR_SUCCESS {
+        return String::new();
+    }
+    let interface_name = extract_interfaces(interface_list)
+        .into_iter()
+        .find(|(guid, _)| guid == interface_guid)
+        .map(|(_, name)| name)
+        .unwrap_or_default();
+    if !interface_list.is_null() {
+        unsafe { WlanFreeMemory(interface_list as *mut c_void) };
+    }
+    interface_name
+}
+
+// 从接口列表提取全部 WLAN 接口的 GUID 与网卡描述
+fn extract_interfaces(interface_list: *mut WLAN_INTERFACE_INFO_LIST) -> Vec<(GUID, String)> {
+    if interface_list.is_null() {
+        return Vec::new();
+    }
 
     let list = unsafe { &*interface_list };
     if list.dwNumberOfItems == 0 {
-        return None;
+        return Vec::new();
     }
 
     let interfaces = unsafe {
         std::slice::from_raw_parts(list.InterfaceInfo.as_ptr(), list.dwNumberOfItems as usize)
     };
-    interfaces.first().map(|info| info.InterfaceGuid)
+    interfaces
+        .iter()
+        .map(|info| {
+            (
+                info.InterfaceGuid,
+                utf16_to_string(&info.strInterfaceDescription),
+            )
+        })
+        .collect()
 }
 
-// 查询 WLAN 信号：返回质量与 RSSI（RSSI 在该结构中不可直接获取时返回 0）
-fn query_interface_signal(handle: HANDLE, interface_guid: &GUID) -> Option<(u32, i32)> {
+// 以 NUL 结尾的定长 UTF-16 缓冲区转为字符串
+fn utf16_to_string(buffer: &[u16]) -> String {
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    String::from_utf16_lossy(&buffer[..len])
+}
+
+// GUID 格式化为带花括号的大写形式，与 GetAdaptersAddresses 的 AdapterName 一致
+fn guid_to_string(guid: &GUID) -> String {
+    format!(
+        "{{{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}}}",
+        guid.data1,
+        guid.data2,
+        guid.data3,
+        guid.data4[0],
+        guid.data4[1],
+        guid.data4[2],
+        guid.data4[3],
+        guid.data4[4],
+        guid.data4[5],
+        guid.data4[6],
+        guid.data4[7]
+    )
+}
+
+// 查询 WLAN 信号：返回质量、由质量换算得到的 RSSI（dBm）以及当前关联的 SSID/BSSID
+fn query_interface_signal(handle: HANDLE, interface_guid: &GUID) -> Option<InterfaceSignal> {
     let mut data_size = 0u32;
     let mut data_ptr: *mut c_void = null_mut();
     let mut opcode = WLAN_OPCODE_VALUE_TYPE(0);
@@ -195,62 +855,269 @@ fn query_interface_signal(handle: HANDLE, interface_guid: &GUID) -> Option<(u32,
     }
 
     let attributes = unsafe { &*(data_ptr as *const WLAN_CONNECTION_ATTRIBUTES) };
-    let quality = attributes.wlanAssociationAttributes.wlanSignalQuality;
-    let rssi = quality_to_rssi(quality);
+    let association = &attributes.wlanAssociationAttributes;
+    let quality = association.wlanSignalQuality;
+    let connected = attributes.isState == wlan_interface_state_connected;
+    let (phy_type, channel, tx_rate_kbps, rx_rate_kbps) = if connected {
+        (
+            association.dot11PhyType.0 as u32,
+            query_channel_number(handle, interface_guid),
+            association.ulTxRate,
+            association.ulRxRate,
+        )
+    } else {
+        (0, 0, 0, 0)
+    };
+    let signal = InterfaceSignal {
+        quality,
+        rssi: quality_to_rssi(quality),
+        ssid: ssid_to_string(
+            &association.dot11Ssid.ucSSID,
+            association.dot11Ssid.uSSIDLength,
+        ),
+        bssid: bssid_to_string(&association.dot11Bssid),
+        security: resolve_security(connected, &attributes.wlanSecurityAttributes),
+        phy_type,
+        channel,
+        tx_rate_kbps,
+        rx_rate_kbps,
+    };
 
     unsafe { WlanFreeMemory(data_ptr) };
 
-    Some((quality, rssi))
+    Some(signal)
+}
+
+// 查询当前连接所在的信道号，失败时返回 0
+fn query_channel_number(handle: HANDLE, interface_guid: &GUID) -> u32 {
+    let mut data_size = 0u32;
+    let mut data_ptr: *mut c_void = null_mut();
+    let query_result = unsafe {
+        WlanQueryInterface(
+            handle,
+            interface_guid,
+            wlan_intf_opcode_channel_number,
+            None,
+            &mut data_size,
+            &mut data_ptr,
+            None,
+        )
+    };
+    if WIN32_ERROR(query_result) != ERROR_SUCCESS || data_ptr.is_null() {
+        if WIN32_ERROR(query_result) != ERROR_SUCCESS {
+            report_error_log!("查询 WLAN 信道失败: {:?}", query_result);
+        }
+        return 0;
+    }
+    let channel = unsafe { *(data_ptr as *const u32) };
+    unsafe { WlanFreeMemory(data_ptr) };
+    channel
+}
+
+// 由信道号推断频段：1-14 为 2.4GHz，5GHz 信道为 36-177；6GHz 信道编号（1, 5, 9 … 233）与前两者部分重叠，
+// 仅在信道号只可能属于 6GHz 时才判定为 6GHz，重叠的编号按更常见的 2.4GHz/5GHz 处理。未知信道返回空字符串
+fn channel_to_band(channel: u32) -> &'static str {
+    match channel {
+        1..=14 => "2.4GHz",
+        36..=64 | 100..=144 if channel.is_multiple_of(4) => "5GHz",
+        149..=177 if channel % 4 == 1 => "5GHz",
+        15..=233 if channel % 4 == 1 => "6GHz",
+        _ => "",
+    }
+}
+
+// 提取连接安全属性：仅在已连接状态下有效，断开或关联中时按未加密且算法为 0 处理
+fn resolve_security(connected: bool, attributes: &WLAN_SECURITY_ATTRIBUTES) -> WlanSecurity {
+    if !connected {
+        return WlanSecurity::default();
+    }
+    WlanSecurity {
+        enabled: attributes.bSecurityEnabled.as_bool(),
+        auth_algorithm: attributes.dot11AuthAlgorithm.0 as u32,
+        cipher_algorithm: attributes.dot11CipherAlgorithm.0 as u32,
+    }
+}
+
+// SSID 为最长 32 字节的原始字节串，按 UTF-8 尽力解码
+fn ssid_to_string(ssid: &[u8], length: u32) -> String {
+    let length = (length as usize).min(ssid.len());
+    String::from_utf8_lossy(&ssid[..length]).into_owned()
 }
 
+// BSSID 格式化为常见的冒号分隔十六进制形式，如 aa:bb:cc:dd:ee:ff
+fn bssid_to_string(bssid: &[u8; 6]) -> String {
+    bssid
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+// 按 Windows 文档的线性关系换算 RSSI：质量 0~100 对应 -100~-50dBm，即 rssi = quality / 2 - 100
 fn quality_to_rssi(quality: u32) -> i32 {
     if quality == 0 {
-        -100
+        DISCONNECTED_RSSI
     } else if quality >= 100 {
         -50
     } else {
-        ((quality as f32) / 2.0) as i32 - 100
+        (quality / 2) as i32 - 100
     }
 }
 
-// 根据信号质量更新弱信号状态，避免频繁抖动
-fn update_signal_state(context: &mut SignalMonitorContext, quality: u32, rssi: i32) {
-    let was_weak = context.is_signal_weak;
+// 根据信号质量更新单个接口的弱信号状态，避免频繁抖动
+fn update_signal_state(
+    state: &mut InterfaceSignalState,
+    threshold_drop: u32,
+    threshold_recover: u32,
+    signal: InterfaceSignal,
+    now: Instant,
+) {
+    let InterfaceSignal {
+        quality,
+        rssi,
+        ssid,
+        bssid,
+        security,
+        phy_type,
+        channel,
+        tx_rate_kbps,
+        rx_rate_kbps,
+    } = signal;
+    let (is_weak, transitioned) = next_signal_weak(
+        state.is_signal_weak,
+        quality,
+        threshold_drop,
+        threshold_recover,
+    );
+    state.is_signal_weak = is_weak;
+    state.last_quality = quality;
+    record_signal_quality(&mut state.recent_qualities, quality);
+    state.last_status_at = Some(now);
+    state.pending_status = false;
 
-    if quality <= context.threshold_drop {
-        context.is_signal_weak = true;
-    } else if quality >= context.threshold_recover {
-        context.is_signal_weak = false;
+    let interface_guid = guid_to_string(&state.interface_guid);
+    if transitioned {
+        if state.is_signal_weak {
+            report_info_log!(
+                "WiFi 信号进入弱信号区间，接口={}（{}），质量={}",
+                state.interface_name,
+                interface_guid,
+                quality
+            );
+        } else {
+            report_info_log!(
+                "WiFi 信号恢复，接口={}（{}），质量={}",
+                state.interface_name,
+                interface_guid,
+                quality
+            );
+        }
     }
 
-    context.last_quality = quality;
+    let status = WlanStatus {
+        strong: if state.is_signal_weak { 0 } else { 1 },
+        is_weak: state.is_signal_weak,
+        weak_state_changed: transitioned,
+        quality,
+        rssi,
+        ssid,
+        bssid,
+        interface_guid,
+        interface_name: state.interface_name.clone(),
+        signal_valid: true,
+        security_enabled: security.enabled,
+        auth_algorithm: security.auth_algorithm,
+        cipher_algorithm: security.cipher_algorithm,
+        phy_type,
+        channel,
+        band: channel_to_band(channel).to_string(),
+        tx_rate_kbps,
+        rx_rate_kbps,
+        trend: signal_trend(&state.recent_qualities).to_string(),
+    };
+    publish_wlan_status(status);
+}
 
-    if was_weak != context.is_signal_weak {
-        let strong = if context.is_signal_weak {
-            report_info_log!("WiFi 信号进入弱信号区间，质量={}", quality);
-            0
-        } else {
-            report_info_log!("WiFi 信号恢复，质量={}", quality);
-            1
-        };
+// 记录一次上报的信号质量，只保留最近 SIGNAL_TREND_WINDOW 次
+fn record_signal_quality(recent: &mut VecDeque<u32>, quality: u32) {
+    if recent.len() == SIGNAL_TREND_WINDOW {
+        recent.pop_front();
+    }
+    recent.push_back(quality);
+}
 
-        // WLAN 回调可能运行在非监控线程上，因此不能依赖 thread_local 的 MonitorState；
-        // 这里改用跨线程的原子网络状态来判断是否要上报。
-        if NETWORK_CONNECTED.load(Ordering::SeqCst) {
-            report_wlan_status(WlanStatus {
-                strong,
-                quality,
-                rssi,
-            });
-        }
+// 比较窗口内最新与最早的质量：差值超出 SIGNAL_TREND_STABLE_BAND 为上升/下降，样本不足两个时为稳定
+fn signal_trend(recent: &VecDeque<u32>) -> &'static str {
+    let (Some(&oldest), Some(&newest)) = (recent.front(), recent.back()) else {
+        return SIGNAL_TREND_STABLE;
+    };
+    if newest > oldest + SIGNAL_TREND_STABLE_BAND {
+        SIGNAL_TREND_RISING
+    } else if newest + SIGNAL_TREND_STABLE_BAND < oldest {
+        SIGNAL_TREND_FALLING
+    } else {
+        SIGNAL_TREND_STABLE
+    }
+}
+
+// 接口仍处于连接状态但查询信号失败：上报“已连接、信号未知”，避免界面继续展示过期的信号值
+fn report_unknown_signal(state: &mut InterfaceSignalState, now: Instant) {
+    state.last_status_at = Some(now);
+    state.pending_status = false;
+    report_info_log!(
+        "WiFi 信号查询失败，上报信号未知：接口={}（{}）",
+        state.interface_name,
+        guid_to_string(&state.interface_guid)
+    );
+    publish_wlan_status(unknown_signal_status(state));
+}
+
+// 信号未知时的 WlanStatus：仅保留接口标识与上一次的弱/强判定
+fn unknown_signal_status(state: &InterfaceSignalState) -> WlanStatus {
+    WlanStatus {
+        strong: if state.is_signal_weak { 0 } else { 1 },
+        is_weak: state.is_signal_weak,
+        weak_state_changed: false,
+        quality: 0,
+        rssi: DISCONNECTED_RSSI,
+        ssid: String::new(),
+        bssid: String::new(),
+        interface_guid: guid_to_string(&state.interface_guid),
+        interface_name: state.interface_name.clone(),
+        signal_valid: false,
+        security_enabled: false,
+        auth_algorithm: 0,
+        cipher_algorithm: 0,
+        phy_type: 0,
+        channel: 0,
+        band: String::new(),
+        tx_rate_kbps: 0,
+        rx_rate_kbps: 0,
+        trend: String::new(),
+    }
+}
+
+// 更新 WiFi 状态快照并在联网时上报
+fn publish_wlan_status(status: WlanStatus) {
+    LAST_WLAN_STATUS.lock().unwrap().replace(status.clone());
+
+    // WLAN 回调可能运行在非监控线程上，因此不能依赖 thread_local 的 MonitorState；
+    // 这里改用跨线程的原子网络状态来判断是否要上报。
+    if NETWORK_CONNECTED.load(Ordering::SeqCst) {
+        report_wlan_status(status);
     }
 }
 
 // 从初始化参数解析阈值，未提供时使用默认值
 fn resolve_signal_thresholds() -> (u32, u32) {
-    let drop = THRESHOLD_DROP.load(Ordering::SeqCst);
-    let recover = THRESHOLD_RECOVER.load(Ordering::SeqCst);
+    normalize_signal_thresholds(
+        THRESHOLD_DROP.load(Ordering::SeqCst),
+        THRESHOLD_RECOVER.load(Ordering::SeqCst),
+    )
+}
 
+// 阈值为 0 时回退到默认值，且保证 recover 大于 drop，避免滞回区间失效
+fn normalize_signal_thresholds(drop: u32, recover: u32) -> (u32, u32) {
     let drop = if drop == 0 { DEFAULT_SIGNAL_DROP } else { drop };
     let mut recover = if recover == 0 {
         DEFAULT_SIGNAL_RECOVER
@@ -274,3 +1141,227 @@ fn check_win32(error: WIN32_ERROR, context: &str) -> WinResult<()> {
         Err(WinError::from(HRESULT::from_win32(error.0)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DEFAULT_SIGNAL_DROP, DEFAULT_SIGNAL_RECOVER, DISCONNECTED_RSSI, WlanSecurity,
+        bssid_to_string, channel_to_band, debounce_elapsed, frequency_to_channel, guid_to_string,
+        msm_notification_name, next_signal_weak, normalize_signal_thresholds, quality_to_rssi,
+        record_signal_quality, resolve_security, signal_trend, ssid_to_string,
+        unknown_signal_status, utf16_to_string,
+    };
+    use crate::global::{
+        InterfaceSignalState, SIGNAL_TREND_FALLING, SIGNAL_TREND_RISING, SIGNAL_TREND_STABLE,
+        SIGNAL_TREND_WINDOW,
+    };
+    use std::collections::VecDeque;
+    use std::time::{Duration, Instant};
+
+    use windows::Win32::NetworkManagement::WiFi::{
+        DOT11_AUTH_ALGO_RSNA_PSK, DOT11_CIPHER_ALGO_CCMP, WLAN_SECURITY_ATTRIBUTES,
+        wlan_notification_msm_link_improved, wlan_notification_msm_radio_state_change,
+        wlan_notification_msm_roaming_start,
+    };
+    use windows::core::{BOOL, GUID};
+
+    #[test]
+    fn quality_maps_linearly_to_dbm() {
+        assert_eq!(quality_to_rssi(1), -100);
+        assert_eq!(quality_to_rssi(50), -75);
+        assert_eq!(quality_to_rssi(99), -51);
+        assert_eq!(quality_to_rssi(100), -50);
+        assert_eq!(quality_to_rssi(150), -50);
+    }
+
+    #[test]
+    fn zero_quality_reports_disconnected_rssi() {
+        assert_eq!(quality_to_rssi(0), DISCONNECTED_RSSI);
+    }
+
+    #[test]
+    fn ssid_and_bssid_are_formatted_for_display() {
+        let mut raw_ssid = [0u8; 32];
+        raw_ssid[..6].copy_from_slice(b"Office");
+        assert_eq!(ssid_to_string(&raw_ssid, 6), "Office");
+        assert_eq!(ssid_to_string(&raw_ssid, 0), "");
+        assert_eq!(
+            bssid_to_string(&[0xaa, 0xbb, 0x0c, 0x0d, 0xee, 0x01]),
+            "aa:bb:0c:0d:ee:01"
+        );
+    }
+
+    #[test]
+    fn signal_thresholds_fall_back_and_keep_recover_above_drop() {
+        assert_eq!(
+            normalize_signal_thresholds(0, 0),
+            (DEFAULT_SIGNAL_DROP, DEFAULT_SIGNAL_RECOVER)
+        );
+        assert_eq!(normalize_signal_thresholds(20, 35), (20, 35));
+        assert_eq!(normalize_signal_thresholds(50, 40), (50, 55));
+        assert_eq!(normalize_signal_thresholds(50, 50), (50, 55));
+    }
+
+    #[test]
+    fn guid_is_formatted_like_adapter_names() {
+        let guid = GUID::from_u128(0x0123_4567_89ab_cdef_0011_2233_4455_6677);
+        assert_eq!(
+            guid_to_string(&guid),
+            "{01234567-89AB-CDEF-0011-223344556677}"
+        );
+    }
+
+    #[test]
+    fn security_is_zeroed_unless_connected() {
+        let attributes = WLAN_SECURITY_ATTRIBUTES {
+            bSecurityEnabled: BOOL(1),
+            bOneXEnabled: BOOL(0),
+            dot11AuthAlgorithm: DOT11_AUTH_ALGO_RSNA_PSK,
+            dot11CipherAlgorithm: DOT11_CIPHER_ALGO_CCMP,
+        };
+        assert_eq!(
+            resolve_security(true, &attributes),
+            WlanSecurity {
+                enabled: true,
+                auth_algorithm: 7,
+                cipher_algorithm: 4,
+            }
+        );
+        assert_eq!(
+            resolve_security(false, &attributes),
+            WlanSecurity::default()
+        );
+    }
+
+    #[test]
+    fn msm_notification_names_match_codes() {
+        assert_eq!(
+            msm_notification_name(wlan_notification_msm_roaming_start.0 as u32),
+            "roaming_start"
+        );
+        assert_eq!(
+            msm_notification_name(wlan_notification_msm_radio_state_change.0 as u32),
+            "radio_state_change"
+        );
+        assert_eq!(
+            msm_notification_name(wlan_notification_msm_link_improved.0 as u32),
+            "link_improved"
+        );
+        assert_eq!(msm_notification_name(99), "unknown");
+    }
+
+    #[test]
+    fn channels_map_to_frequency_bands() {
+        assert_eq!(channel_to_band(1), "2.4GHz");
+        assert_eq!(channel_to_band(14), "2.4GHz");
+        assert_eq!(channel_to_band(36), "5GHz");
+        assert_eq!(channel_to_band(165), "5GHz");
+        assert_eq!(channel_to_band(37), "6GHz");
+        assert_eq!(channel_to_band(233), "6GHz");
+        assert_eq!(channel_to_band(0), "");
+        assert_eq!(channel_to_band(38), "");
+    }
+    #[test]
+    fn utf16_buffer_stops_at_nul() {
+        let mut buffer = [0u16; 16];
+        for (slot, unit) in buffer.iter_mut().zip("Wi-Fi 6".encode_utf16()) {
+            *slot = unit;
+        }
+        assert_eq!(utf16_to_string(&buffer), "Wi-Fi 6");
+        assert_eq!(utf16_to_string(&[0u16; 4]), "");
+        let full: Vec<u16> = "AX201".encode_utf16().collect();
+        assert_eq!(utf16_to_string(&full), "AX201");
+    }
+
+    #[test]
+    fn signal_drops_to_weak_at_or_below_drop_threshold() {
+        assert_eq!(next_signal_weak(false, 30, 30, 40), (true, true));
+        assert_eq!(next_signal_weak(false, 5, 30, 40), (true, true));
+        assert_eq!(next_signal_weak(true, 10, 30, 40), (true, false));
+    }
+
+    #[test]
+    fn weak_signal_recovers_at_or_above_recover_threshold() {
+        assert_eq!(next_signal_weak(true, 40, 30, 40), (false, true));
+        assert_eq!(next_signal_weak(true, 90, 30, 40), (false, true));
+        assert_eq!(next_signal_weak(false, 80, 30, 40), (false, false));
+    }
+
+    #[test]
+    fn quality_between_thresholds_keeps_prior_state() {
+        assert_eq!(next_signal_weak(false, 35, 30, 40), (false, false));
+        assert_eq!(next_signal_weak(true, 35, 30, 40), (true, false));
+        assert_eq!(next_signal_weak(true, 39, 30, 40), (true, false));
+        assert_eq!(next_signal_weak(false, 31, 30, 40), (false, false));
+    }
+
+    #[test]
+    fn debounce_allows_first_report_and_waits_for_interval() {
+        let now = Instant::now();
+        let debounce = Duration::from_millis(500);
+        assert!(debounce_elapsed(None, now, debounce));
+        let recent = Some(now);
+        assert!(!debounce_elapsed(
+            recent,
+            now + Duration::from_millis(499),
+            debounce
+        ));
+        assert!(debounce_elapsed(
+            recent,
+            now + Duration::from_millis(500),
+            debounce
+        ));
+        assert!(debounce_elapsed(recent, now, Duration::ZERO));
+    }
+
+    #[test]
+    fn unknown_signal_keeps_interface_and_prior_strength() {
+        let state = InterfaceSignalState {
+            interface_guid: GUID::from_u128(0x12345678_9abc_def0_1122_334455667788),
+            interface_name: "Wi-Fi".to_string(),
+            is_signal_weak: true,
+            last_quality: 25,
+            recent_qualities: VecDeque::new(),
+            last_status_at: None,
+            pending_status: false,
+        };
+        let status = unknown_signal_status(&state);
+        assert!(!status.signal_valid);
+        assert_eq!(status.strong, 0);
+        assert!(status.is_weak && !status.weak_state_changed);
+        assert_eq!(status.quality, 0);
+        assert_eq!(status.rssi, DISCONNECTED_RSSI);
+        assert_eq!(status.interface_name, "Wi-Fi");
+        assert_eq!(status.interface_guid, guid_to_string(&state.interface_guid));
+        assert!(status.ssid.is_empty() && status.band.is_empty());
+    }
+
+    #[test]
+    fn center_frequency_maps_to_channel_and_band() {
+        assert_eq!(frequency_to_channel(2_412_000), (1, "2.4GHz"));
+        assert_eq!(frequency_to_channel(2_484_000), (14, "2.4GHz"));
+        assert_eq!(frequency_to_channel(5_180_000), (36, "5GHz"));
+        assert_eq!(frequency_to_channel(5_825_000), (165, "5GHz"));
+        assert_eq!(frequency_to_channel(5_955_000), (1, "6GHz"));
+        assert_eq!(frequency_to_channel(6_115_000), (33, "6GHz"));
+        assert_eq!(frequency_to_channel(0), (0, ""));
+    }
+
+    #[test]
+    fn signal_trend_follows_recent_window() {
+        let mut recent = VecDeque::new();
+        assert_eq!(signal_trend(&recent), SIGNAL_TREND_STABLE);
+        for quality in [40, 45, 50, 55] {
+            record_signal_quality(&mut recent, quality);
+        }
+        assert_eq!(signal_trend(&recent), SIGNAL_TREND_RISING);
+        // 窗口只保留最近 5 次，最早的 40 被淘汰后与 45 比较
+        for quality in [48, 38] {
+            record_signal_quality(&mut recent, quality);
+        }
+        assert_eq!(recent.len(), SIGNAL_TREND_WINDOW);
+        assert_eq!(signal_trend(&recent), SIGNAL_TREND_FALLING);
+        record_signal_quality(&mut recent, 52);
+        assert_eq!(signal_trend(&recent), SIGNAL_TREND_STABLE);
+    }
+}
@@ -1,6 +1,6 @@
-const { doInitialize, enableNetQualityProb } = require('./index.js')
+const { doInitialize, enableNetQualityProb, formatLogEvent } = require('./index.js')
 
-doInitialize(
+const initResult = doInitialize(
   (err, { status }) => {
     if (status === 1) {
       console.log('网络已连接')
@@ -19,10 +19,26 @@ doInitialize(
     console.log('网络质量: ', info)
   },
   (err, log) => {
-    console.log('addon log: ', log)
+    if (log.level === 'error') {
+      console.error('addon log: ', formatLogEvent(log))
+    } else {
+      console.log('addon log: ', formatLogEvent(log))
+    }
   },
   false,
+  undefined,
+  (err, { uptimeSecs, lastEventAtMs }) => {
+    console.log(`监控线程心跳: 已运行 ${uptimeSecs}s, 最近事件 ${lastEventAtMs}`)
+  },
+  (err, { degraded, target, reason }) => {
+    console.log(degraded ? `网络质量劣化: ${target} (${reason})` : `网络质量恢复: ${target}`)
+  },
+  (err, { name, interfaceGuid }) => {
+    console.log(`WLAN 通知: ${name} (${interfaceGuid})`)
+  },
+  { enableWlan: true, enableQualityProbe: true },
 )
+console.log('初始化结果: ', initResult)
 
 setTimeout(() => {
   enableNetQualityProb(true)